@@ -15,12 +15,12 @@ export class LicenseController {
   }
 
   @Get('license')
-  async validateLicense(@Query('key') key: string) {
+  async validateLicense(@Query('key') key: string, @Query('genesis') genesis?: string) {
     if (!key) {
       throw new BadRequestException('License key is required');
     }
 
-    const isValid = await this.licenseService.validateLicense(key);
+    const isValid = await this.licenseService.validateLicense(key, genesis);
     return { valid: isValid };
   }
 }
@@ -14,6 +14,11 @@ export class License {
   @Column({ type: 'boolean', default: true })
   isActive: boolean;
 
+  // Genesis hash of the only chain this license is valid for, if bound. Stored as hex, with or
+  // without a `0x` prefix and in any case; nodes send it as lowercase hex without `0x`
+  @Column({ type: 'varchar', nullable: true })
+  genesisHash: string | null;
+
   @Column({ type: 'timestamp', default: () => 'CURRENT_TIMESTAMP' })
   createdAt: Date;
 }
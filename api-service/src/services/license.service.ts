@@ -3,6 +3,11 @@ import { InjectRepository } from '@nestjs/typeorm';
 import { Repository } from 'typeorm';
 import { License } from '../entities/license.entity';
 
+// Genesis hashes compare as lowercase hex, with or without a `0x` prefix
+function normalizeGenesisHash(hash: string): string {
+  return hash.toLowerCase().replace(/^0x/, '');
+}
+
 @Injectable()
 export class LicenseService {
   constructor(
@@ -10,7 +15,7 @@ export class LicenseService {
     private readonly licenseRepository: Repository<License>
   ) {}
 
-  async validateLicense(key: string): Promise<boolean> {
+  async validateLicense(key: string, genesis?: string): Promise<boolean> {
     const license = await this.licenseRepository.findOne({
       where: { key },
     });
@@ -19,6 +24,14 @@ export class LicenseService {
       return false;
     }
 
+    // A license bound to a chain is only valid for that chain's genesis hash
+    if (
+      license.genesisHash &&
+      (!genesis || normalizeGenesisHash(license.genesisHash) !== normalizeGenesisHash(genesis))
+    ) {
+      return false;
+    }
+
     // Check if license is active and not expired
     const now = new Date();
     return license.isActive && license.expiresAt > now;
@@ -132,6 +132,7 @@ pub fn create_benchmark_extrinsic(
         frame_system::CheckNonce::<runtime::Runtime>::from(nonce),
         frame_system::CheckWeight::<runtime::Runtime>::new(),
         pallet_transaction_payment::ChargeTransactionPayment::<runtime::Runtime>::from(0),
+        runtime::pallet_licensed_aura::filter::CheckNotHalted::<runtime::Runtime>::new(),
     );
 
     let raw_payload = runtime::SignedPayload::from_raw(
@@ -146,6 +147,7 @@ pub fn create_benchmark_extrinsic(
             (),
             (),
             (),
+            (),
         ),
     );
     let signature = raw_payload.using_encoded(|e| sender.sign(e));
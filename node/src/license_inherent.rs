@@ -0,0 +1,47 @@
+//! Inherent data provider for the Licensed Aura license proof.
+
+use codec::Decode;
+use licensable_parachain_runtime::pallet_licensed_aura::inherent::{
+    InherentError, InherentType, LicenseProof, INHERENT_IDENTIFIER, LICENSE_PROOF_STORAGE_KEY,
+};
+use sp_core::offchain::OffchainStorage;
+use sp_inherents::{InherentData, InherentIdentifier};
+
+/// Provides the license proof the local offchain worker last stored.
+pub struct LicenseInherentDataProvider(InherentType);
+
+impl LicenseInherentDataProvider {
+    /// Read the latest proof from the node's offchain database, if there is one.
+    pub fn from_offchain_storage<S: OffchainStorage>(storage: Option<&S>) -> Self {
+        let proof = storage
+            .and_then(|storage| storage.get(sp_offchain::STORAGE_PREFIX, LICENSE_PROOF_STORAGE_KEY))
+            .and_then(|raw| LicenseProof::decode(&mut &raw[..]).ok());
+        Self(proof)
+    }
+}
+
+#[async_trait::async_trait]
+impl sp_inherents::InherentDataProvider for LicenseInherentDataProvider {
+    async fn provide_inherent_data(
+        &self,
+        inherent_data: &mut InherentData,
+    ) -> Result<(), sp_inherents::Error> {
+        inherent_data.put_data(INHERENT_IDENTIFIER, &self.0)
+    }
+
+    async fn try_handle_error(
+        &self,
+        identifier: &InherentIdentifier,
+        mut error: &[u8],
+    ) -> Option<Result<(), sp_inherents::Error>> {
+        if *identifier != INHERENT_IDENTIFIER {
+            return None;
+        }
+
+        let error = InherentError::decode(&mut error).ok()?;
+        Some(Err(sp_inherents::Error::Application(Box::from(format!(
+            "{:?}",
+            error
+        )))))
+    }
+}
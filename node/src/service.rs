@@ -1,5 +1,6 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
+use crate::license_inherent::LicenseInherentDataProvider;
 use futures::FutureExt;
 use licensable_parachain_runtime::{self, opaque::Block, RuntimeApi};
 use sc_client_api::{Backend, BlockBackend};
@@ -9,6 +10,7 @@ use sc_service::{error::Error as ServiceError, Configuration, TaskManager, WarpS
 use sc_telemetry::{Telemetry, TelemetryWorker};
 use sc_transaction_pool_api::OffchainTransactionPoolFactory;
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
+use sp_core::offchain::OffchainStorage;
 use std::{sync::Arc, time::Duration};
 
 pub(crate) type FullClient = sc_service::TFullClient<
@@ -187,6 +189,15 @@ pub fn new_full<
         })?;
 
     if config.offchain_worker.enabled {
+        // Restart the license check warm-up, so that a license server that is briefly
+        // unreachable while the node starts cannot halt the chain.
+        if let Some(mut storage) = backend.offchain_storage() {
+            storage.remove(
+                sp_offchain::STORAGE_PREFIX,
+                licensable_parachain_runtime::pallet_licensed_aura::WARMUP_CHECKS_STORAGE_KEY,
+            );
+        }
+
         task_manager.spawn_handle().spawn(
             "offchain-workers-runner",
             "offchain-worker",
@@ -213,6 +224,7 @@ pub fn new_full<
     let name = config.network.node_name.clone();
     let enable_grandpa = !config.disable_grandpa;
     let prometheus_registry = config.prometheus_registry().cloned();
+    let offchain_storage = backend.offchain_storage();
 
     let rpc_extensions_builder = {
         let client = client.clone();
@@ -261,16 +273,22 @@ pub fn new_full<
                 select_chain,
                 block_import,
                 proposer_factory,
-                create_inherent_data_providers: move |_, ()| async move {
-                    let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
-
-                    let slot =
-						sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
-							*timestamp,
-							slot_duration,
-						);
-
-                    Ok((slot, timestamp))
+                create_inherent_data_providers: move |_, ()| {
+                    let license = LicenseInherentDataProvider::from_offchain_storage(
+                        offchain_storage.as_ref(),
+                    );
+
+                    async move {
+                        let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+
+                        let slot =
+							sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
+								*timestamp,
+								slot_duration,
+							);
+
+                        Ok((slot, timestamp, license))
+                    }
                 },
                 force_authoring,
                 backoff_authoring_blocks,
@@ -0,0 +1,83 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks for the Licensed Aura pallet.
+//!
+//! [`AuraHaltFilter`] runs for every extrinsic, so its cost is benchmarked on its slowest path:
+//! an operator's call, wrapped in sudo through [`Config::BenchmarkHelper`], that goes through
+//! the whole halt whitelist, including the [`Config::HaltAllowedPallets`] lookup, before being
+//! refused. The call carries a payload up to the size of a runtime upgrade, to catch the filter
+//! ever growing with the size of a call.
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use crate::filter::{
+    AuraHaltFilter, IsDefaultInherentExstrinsicCall, IsLicensedAuraCall, IsSudoCall,
+};
+use alloc::vec;
+use frame_benchmarking::v2::*;
+use frame_support::traits::Contains;
+
+/// Largest payload benchmarked, about the size of a runtime upgrade sent through sudo.
+const MAX_PAYLOAD_LEN: u32 = 4 * 1024 * 1024;
+
+/// [`Pallet::sudo_halt_production`] with a reason of `len` bytes, as the runtime's sudo pallet
+/// dispatches it. Halting is not on the halt whitelist, so the filter refuses it while halted.
+/// The call fails on reasons over 256 bytes, but only after the filter has let it through.
+fn sudo_halt_call<T: Config>(len: u32) -> <T as Config>::RuntimeCall
+where
+    <T as Config>::RuntimeCall: From<Call<T>>,
+{
+    let call = Call::<T>::sudo_halt_production {
+        reason: Some(vec![b'x'; len as usize]),
+    };
+    T::BenchmarkHelper::sudo(call.into())
+}
+
+#[benchmarks(
+    where <T as Config>::RuntimeCall: From<Call<T>>
+        + IsLicensedAuraCall
+        + IsDefaultInherentExstrinsicCall
+        + IsSudoCall<<T as Config>::RuntimeCall>
+        + core::fmt::Debug
+)]
+mod benchmarks {
+    use super::*;
+
+    /// The halt filter refusing a sudo call with a payload of `r` bytes while halted.
+    #[benchmark]
+    fn halt_filter_refused_sudo_call(r: Linear<0, MAX_PAYLOAD_LEN>) {
+        T::BenchmarkHelper::allow_another_pallet_while_halted();
+        HaltProduction::<T>::put(true);
+        let call = sudo_halt_call::<T>(r);
+        let allowed;
+
+        #[block]
+        {
+            allowed = AuraHaltFilter::<<T as Config>::RuntimeCall, T>::contains(&call);
+        }
+
+        assert!(!allowed);
+    }
+
+    impl_benchmark_test_suite!(
+        Pallet,
+        crate::mock::build_ext(vec![0, 1, 2, 3]),
+        crate::mock::Test
+    );
+}
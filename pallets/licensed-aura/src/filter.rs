@@ -19,10 +19,19 @@
 //!
 //! This filter enforces "empty blocks while halted" - when the Licensed Aura
 //! pallet is in a halted state, only specific whitelisted calls are allowed.
+//! In safe mode the whitelist shrinks to the sudo resume alone.
+//!
+//! [`CheckNotHalted`] applies the same whitelist to signed transactions at validation, so
+//! blocked calls never enter the transaction pool. [`AuraHaltFilter::halt_whitelist`] lists it
+//! by call index for wallets and UIs.
 
 use super::*;
-use frame_support::traits::Contains;
+use frame_support::traits::{Contains, GetCallIndex, GetCallName, PalletInfoAccess};
 use log::{error, warn};
+use sp_runtime::{
+    traits::{DispatchInfoOf, SignedExtension},
+    transaction_validity::TransactionValidityError,
+};
 
 const LOG_TARGET: &str = "licensed-aura";
 
@@ -30,9 +39,27 @@ const LOG_TARGET: &str = "licensed-aura";
 ///
 /// When the Licensed Aura pallet is halted (license invalid or manually halted),
 /// this filter blocks all extrinsics except:
-/// - Mandatory inherents (like timestamp)
-/// - Resume production calls (sudo_resume_production)
+/// - Mandatory inherents (like timestamp and the license proof)
+/// - Resume production calls (sudo_resume_production, sudo_force_resume)
 /// - Halt production calls (offchain_worker_halt_production)
+/// - License check reports (offchain_worker_report_check)
+/// - Authority license reports (offchain_worker_report_authority_check)
+/// - Unusable license key reports (offchain_worker_report_unusable_key)
+/// - Calls queued for resume (sudo_queue_call_for_resume)
+/// - Halt reason annotations (set_halt_reason)
+/// - Pausing auto-recovery (sudo_set_auto_recovery_paused)
+/// - Perpetual license installs (install_perpetual_license)
+/// - Any call of the pallets in [`Config::HaltAllowedPallets`]
+///
+/// Blocked extrinsics are dropped rather than kept for later; see [`crate::QueuedCalls`].
+///
+/// In safe mode only mandatory inherents and `sudo_resume_production` or `sudo_force_resume`,
+/// directly or through sudo, are allowed. A block of a disabled authority within
+/// [`Config::DisabledAuthorGrace`] allows mandatory inherents only.
+///
+/// The filter runs for every extrinsic, so it looks at most one level into a call, through
+/// sudo, and never recurses or encodes it: its cost grows neither with how deeply a call is
+/// nested nor with its size.
 pub struct AuraHaltFilter<RuntimeCall, T>(core::marker::PhantomData<(RuntimeCall, T)>);
 
 impl<RuntimeCall, T> AuraHaltFilter<RuntimeCall, T>
@@ -45,8 +72,19 @@ where
         match () {
             // Direct calls to the licensed aura pallet.
             _ if call.is_sudo_resume_production() => true,
+            _ if call.is_sudo_force_resume() => true,
             _ if call.is_offchain_worker_halt() => true,
             _ if call.is_offchain_worker_resume() => true,
+            _ if call.is_offchain_worker_report() => true,
+            _ if call.is_offchain_worker_authority_report() => true,
+            _ if call.is_offchain_worker_unusable_key_report() => true,
+            _ if call.is_queue_call_for_resume() => true,
+            _ if call.is_set_halt_reason() => true,
+            _ if call.is_set_auto_recovery_paused() => true,
+            _ if call.is_install_perpetual_license() => true,
+
+            // Calls of a pallet allowed as a whole.
+            _ if Self::in_allowed_pallet(call) => true,
 
             // Sudo wrapping an allowed call: sudo(Aura::sudo_resume_production { .. })
             _ if call.is_sudo_wrapping_allowed() => true,
@@ -55,6 +93,131 @@ where
             _ => false,
         }
     }
+
+    /// Helper: is `call` from one of the [`Config::HaltAllowedPallets`]?
+    fn in_allowed_pallet(call: &RuntimeCall) -> bool {
+        let allowed_pallets = T::HaltAllowedPallets::get();
+        if allowed_pallets.is_empty() {
+            return false;
+        }
+        allowed_pallets.contains(&call.pallet_index())
+    }
+
+    /// Helper: what is allowed in safe mode?
+    fn allowed_in_safe_mode(call: &RuntimeCall) -> bool {
+        call.is_sudo_resume_production()
+            || call.is_sudo_force_resume()
+            || call.is_sudo_wrapping_resume()
+    }
+
+    /// Whether `call` may be dispatched in the pallet's current halt state, without logging.
+    pub fn allows(call: &RuntimeCall) -> bool {
+        // Always allow mandatory inherents (like timestamp).
+        // This keeps block production working even while halted.
+        if call.is_timestamp_set() || call.is_license_proof() {
+            return true;
+        }
+
+        // A disabled authority's block within its grace carries nothing else.
+        if Pallet::<T>::is_disabled_author_block() {
+            return false;
+        }
+
+        // Safe mode overrides the halt whitelist with a stricter one.
+        if Pallet::<T>::is_safe_mode() {
+            return Self::allowed_in_safe_mode(call);
+        }
+
+        // Everything else is governed by the halt flag.
+        !Pallet::<T>::is_halted() || Self::allowed_while_halted(call)
+    }
+}
+
+/// Whether the [`AuraHaltFilter`] lets calls through.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum HaltFilterStance {
+    /// Production runs and every call is allowed.
+    Allow,
+    /// Production is halted and only the whitelisted calls are allowed.
+    Deny,
+}
+
+/// Everything that decides what the [`AuraHaltFilter`] lets through, for management UIs.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct HaltFilterConfig {
+    /// The current stance of the filter.
+    pub stance: HaltFilterStance,
+    /// [`Config::HaltAllowedPallets`], whose calls are all allowed while halted.
+    pub allowed_pallets: Vec<u8>,
+    /// [`AuraHaltFilter::halt_whitelist`].
+    pub allowed_calls: Vec<(u8, u8)>,
+    /// [`crate::SafeMode`], which narrows the whitelist to the sudo resume calls.
+    pub safe_mode: bool,
+}
+
+impl<RuntimeCall, T> AuraHaltFilter<RuntimeCall, T>
+where
+    T: Config,
+    RuntimeCall: HaltWhitelistCallIndices,
+{
+    /// The current stance of the filter together with its whitelists.
+    pub fn config() -> HaltFilterConfig {
+        let stance = if Pallet::<T>::is_halted() {
+            HaltFilterStance::Deny
+        } else {
+            HaltFilterStance::Allow
+        };
+        HaltFilterConfig {
+            stance,
+            allowed_pallets: T::HaltAllowedPallets::get(),
+            allowed_calls: Self::halt_whitelist(),
+            safe_mode: Pallet::<T>::is_safe_mode(),
+        }
+    }
+
+    /// The `(pallet index, call index)` pairs of the calls permitted while halted, sorted.
+    ///
+    /// Sudo calls are only permitted while wrapping one of this pallet's whitelisted calls. The
+    /// pallets of [`Config::HaltAllowedPallets`] are allowed as a whole and not listed here.
+    pub fn halt_whitelist() -> Vec<(u8, u8)> {
+        // Keep in sync with `allows` and `allowed_while_halted`.
+        let mut whitelist = call_indices::<Pallet<T>, Call<T>>(&[
+            "note_license_proof",
+            "sudo_resume_production",
+            "sudo_force_resume",
+            "offchain_worker_halt_production",
+            "offchain_worker_resume_production",
+            "offchain_worker_report_check",
+            "offchain_worker_report_authority_check",
+            "offchain_worker_report_unusable_key",
+            "sudo_queue_call_for_resume",
+            "set_halt_reason",
+            "sudo_set_auto_recovery_paused",
+            "install_perpetual_license",
+        ]);
+        whitelist.extend(RuntimeCall::inherent_call_indices());
+        whitelist.extend(RuntimeCall::sudo_call_indices());
+        whitelist.sort();
+        whitelist.dedup();
+        whitelist
+    }
+}
+
+/// The `(pallet index, call index)` pairs of the calls of pallet `P` named in `names`.
+///
+/// Names that are not calls of `P` are skipped.
+pub fn call_indices<P, C>(names: &[&str]) -> Vec<(u8, u8)>
+where
+    P: PalletInfoAccess,
+    C: GetCallName + GetCallIndex,
+{
+    let pallet_index = P::index() as u8;
+    C::get_call_names()
+        .iter()
+        .zip(C::get_call_indices())
+        .filter(|(name, _)| names.contains(name))
+        .map(|(_, call_index)| (pallet_index, *call_index))
+        .collect()
 }
 
 impl<RuntimeCall, T> Contains<RuntimeCall> for AuraHaltFilter<RuntimeCall, T>
@@ -66,18 +229,23 @@ where
         + core::fmt::Debug,
 {
     fn contains(call: &RuntimeCall) -> bool {
-        // Always allow mandatory inherents (like timestamp).
-        // This keeps block production working even while halted.
-        if call.is_timestamp_set() {
-            return true;
-        }
-
-        // Everything else is governed by the halt flag.
-        let halted = Pallet::<T>::is_halted();
+        let allowed = Self::allows(call);
 
-        if halted {
-            // Only log when we're actually *blocking* something, not for allowed ones.
-            if !Self::allowed_while_halted(call) {
+        // Only log when we're actually *blocking* something, not for allowed ones.
+        if !allowed {
+            if Pallet::<T>::is_disabled_author_block() {
+                warn!(
+                    target: LOG_TARGET,
+                    "Block of a disabled authority. Extrinsic {:?} cannot be processed.",
+                    call
+                );
+            } else if Pallet::<T>::is_safe_mode() {
+                error!(
+                    target: LOG_TARGET,
+                    "❌️ Licensed Aura is in safe mode. Extrinsic {:?} cannot be processed.",
+                    call
+                );
+            } else {
                 warn!(
                     target: LOG_TARGET,
                     "❗️ Licensed Aura is halted. Please renew your license."
@@ -88,34 +256,138 @@ where
                     call
                 );
             }
+        }
+
+        allowed
+    }
+}
+
+/// [`InvalidTransaction::Custom`] code for a transaction rejected by [`CheckNotHalted`].
+pub const INVALID_TX_HALTED: u8 = 0x4c;
 
-            // Only allow the whitelisted calls while halted.
-            Self::allowed_while_halted(call)
+/// Signed extension rejecting transactions the [`AuraHaltFilter`] would block, so that they are
+/// kept out of the transaction pool while the chain is halted.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckNotHalted<T: Config + Send + Sync>(core::marker::PhantomData<T>);
+
+impl<T: Config + Send + Sync> CheckNotHalted<T> {
+    /// Create new `SignedExtension` to check the halt state.
+    pub fn new() -> Self {
+        Self(core::marker::PhantomData)
+    }
+}
+
+impl<T: Config + Send + Sync> Default for CheckNotHalted<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Config + Send + Sync> core::fmt::Debug for CheckNotHalted<T> {
+    #[cfg(feature = "std")]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "CheckNotHalted")
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn fmt(&self, _: &mut core::fmt::Formatter) -> core::fmt::Result {
+        Ok(())
+    }
+}
+
+impl<T> SignedExtension for CheckNotHalted<T>
+where
+    T: Config + Send + Sync,
+    <T as frame_system::Config>::RuntimeCall: IsLicensedAuraCall
+        + IsDefaultInherentExstrinsicCall
+        + IsSudoCall<<T as frame_system::Config>::RuntimeCall>,
+{
+    const IDENTIFIER: &'static str = "CheckNotHalted";
+    type AccountId = T::AccountId;
+    type Call = <T as frame_system::Config>::RuntimeCall;
+    type AdditionalSigned = ();
+    type Pre = ();
+
+    fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        _who: &Self::AccountId,
+        call: &Self::Call,
+        _info: &DispatchInfoOf<Self::Call>,
+        _len: usize,
+    ) -> TransactionValidity {
+        if AuraHaltFilter::<Self::Call, T>::allows(call) {
+            Ok(ValidTransaction::default())
         } else {
-            // Normal mode: allow everything.
-            true
+            InvalidTransaction::Custom(INVALID_TX_HALTED).into()
         }
     }
+
+    fn pre_dispatch(
+        self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        info: &DispatchInfoOf<Self::Call>,
+        len: usize,
+    ) -> Result<(), TransactionValidityError> {
+        self.validate(who, call, info, len).map(|_| ())
+    }
 }
 
 /// Trait to check if a RuntimeCall is a call to the licensed aura pallet
 pub trait IsLicensedAuraCall {
     /// Check if this is a sudo_resume_production call
     fn is_sudo_resume_production(&self) -> bool;
+    /// Check if this is a sudo_force_resume call
+    fn is_sudo_force_resume(&self) -> bool;
     /// Check if this is an offchain_worker_halt_production call
     fn is_offchain_worker_halt(&self) -> bool;
     /// Check if this is an offchain_worker_resume_production call
     fn is_offchain_worker_resume(&self) -> bool;
+    /// Check if this is an offchain_worker_report_check call
+    fn is_offchain_worker_report(&self) -> bool;
+    /// Check if this is an offchain_worker_report_authority_check call
+    fn is_offchain_worker_authority_report(&self) -> bool;
+    /// Check if this is an offchain_worker_report_unusable_key call
+    fn is_offchain_worker_unusable_key_report(&self) -> bool;
+    /// Check if this is a sudo_queue_call_for_resume call
+    fn is_queue_call_for_resume(&self) -> bool;
+    /// Check if this is a set_halt_reason call
+    fn is_set_halt_reason(&self) -> bool;
+    /// Check if this is a sudo_set_auto_recovery_paused call
+    fn is_set_auto_recovery_paused(&self) -> bool;
+    /// Check if this is an install_perpetual_license call
+    fn is_install_perpetual_license(&self) -> bool;
+    /// The runtime index of the pallet this call belongs to, matched from the call's variant
+    /// rather than read from its encoding, as the filter asks for every extrinsic
+    fn pallet_index(&self) -> u8;
 }
 
 /// Trait to check if a RuntimeCall is a timestamp::set call
 pub trait IsDefaultInherentExstrinsicCall {
     /// Check if this is a timestamp::set call
     fn is_timestamp_set(&self) -> bool;
+    /// Check if this is the note_license_proof inherent
+    fn is_license_proof(&self) -> bool;
 }
 
 /// Trait to check if a RuntimeCall is a sudo call wrapping another call
 pub trait IsSudoCall<RuntimeCall> {
     /// Check if this is a sudo call wrapping an allowed call (resume or halt)
     fn is_sudo_wrapping_allowed(&self) -> bool;
+    /// Check if this is a sudo call wrapping sudo_resume_production or sudo_force_resume
+    fn is_sudo_wrapping_resume(&self) -> bool;
+}
+
+/// Call index metadata the runtime supplies for [`AuraHaltFilter::halt_whitelist`]
+pub trait HaltWhitelistCallIndices {
+    /// `(pallet index, call index)` of the mandatory inherents outside this pallet, like
+    /// timestamp::set
+    fn inherent_call_indices() -> Vec<(u8, u8)>;
+    /// `(pallet index, call index)` of the sudo calls that may wrap a whitelisted call
+    fn sudo_call_indices() -> Vec<(u8, u8)>;
 }
@@ -0,0 +1,510 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! HTTP access for the offchain license check.
+//!
+//! The offchain worker talks to the license server through [`LicenseHttpClient`], so the
+//! retry and backoff logic in [`fetch_with_retry`] can be driven by scripted responses in tests
+//! instead of a full offchain HTTP mock.
+//!
+//! Servers may gzip their responses; [`HttpResponse::decoded_body`] undoes that before parsing.
+//!
+//! Endpoints are `http://`, `https://` or `unix://` URLs, see [`EndpointScheme`]. The last are
+//! for a license agent on the validator's own machine and never leave it.
+
+use alloc::{string::String, vec::Vec};
+use sp_runtime::offchain::{http, Duration};
+
+use crate::LOG_TARGET;
+
+/// How long a single request may take before it is abandoned.
+pub const REQUEST_TIMEOUT_MS: u64 = 5_000;
+
+/// Offchain local storage key holding the shared secret used to sign license requests.
+///
+/// The `HmacSecret` deliberately lives in node-local offchain storage rather than on-chain state,
+/// which anyone can read. Operators set it on each validator, e.g. through the
+/// `offchain_localStorageSet` RPC with the `PERSISTENT` kind. Requests are left unsigned when it
+/// is absent.
+pub const HMAC_SECRET_STORAGE_KEY: &[u8] = b"licensed_aura::hmac_secret";
+
+/// URL the offchain worker queries for a `unix://` license endpoint.
+///
+/// The offchain HTTP API cannot open Unix domain sockets, so a license agent co-located with the
+/// validator is expected to answer on this loopback address as well. Unlike a socket guarded by
+/// file permissions, a loopback port can be bound by any local process, so the offchain worker
+/// only takes this route for requests signed with the [`HMAC_SECRET_STORAGE_KEY`] secret.
+pub const LOCAL_AGENT_URL: &str = "http://127.0.0.1:3000/license";
+
+/// How a license endpoint is reached, by its URL scheme.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EndpointScheme {
+    /// `http://`, over the network.
+    Http,
+    /// `https://`, over the network.
+    Https,
+    /// `unix://` and an absolute socket path, served through [`LOCAL_AGENT_URL`].
+    Unix,
+}
+
+impl EndpointScheme {
+    /// The scheme of `endpoint`, ignoring case, or `None` if it has none the offchain worker
+    /// can use.
+    pub fn of(endpoint: &str) -> Option<Self> {
+        let (scheme, rest) = endpoint.split_once("://")?;
+        if scheme.eq_ignore_ascii_case("unix") {
+            return (rest.len() > 1 && rest.starts_with('/')).then_some(Self::Unix);
+        }
+        if rest.is_empty() {
+            return None;
+        }
+        if scheme.eq_ignore_ascii_case("http") {
+            Some(Self::Http)
+        } else if scheme.eq_ignore_ascii_case("https") {
+            Some(Self::Https)
+        } else {
+            None
+        }
+    }
+}
+
+/// The URL queried for `endpoint`: [`LOCAL_AGENT_URL`] for a `unix://` endpoint, else the
+/// endpoint itself.
+pub fn route_endpoint(endpoint: &str) -> &str {
+    match EndpointScheme::of(endpoint) {
+        Some(EndpointScheme::Unix) => LOCAL_AGENT_URL,
+        _ => endpoint,
+    }
+}
+
+/// A license request about to be sent.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct LicenseRequest {
+    /// The full URL, including query parameters.
+    pub url: String,
+    /// Extra headers, in the order they are sent.
+    pub headers: Vec<(String, String)>,
+}
+
+impl LicenseRequest {
+    /// A request for `url` without extra headers.
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Attach `token` as a bearer token in the `Authorization` header.
+    pub fn authorize(&mut self, token: &str) {
+        self.headers.push((
+            String::from("Authorization"),
+            alloc::format!("Bearer {}", token),
+        ));
+    }
+
+    /// Attach `HMAC-SHA256(secret, key || nonce || timestamp)` so the server can check that the
+    /// request comes from a node holding the shared secret.
+    ///
+    /// `nonce` and `timestamp` are sent alongside as `X-License-Nonce` and `X-License-Timestamp`
+    /// (decimal unix milliseconds); the MAC is sent hex-encoded as `X-License-Signature`.
+    pub fn sign(&mut self, secret: &[u8], key: &[u8], nonce: &str, timestamp: u64) {
+        let timestamp = alloc::format!("{}", timestamp);
+        let signature = to_hex(&request_signature(secret, key, nonce, &timestamp));
+
+        self.headers
+            .push((String::from("X-License-Nonce"), String::from(nonce)));
+        self.headers
+            .push((String::from("X-License-Timestamp"), timestamp));
+        self.headers
+            .push((String::from("X-License-Signature"), signature));
+    }
+}
+
+/// `HMAC-SHA256(secret, key || nonce || timestamp)`.
+pub fn request_signature(secret: &[u8], key: &[u8], nonce: &str, timestamp: &str) -> [u8; 32] {
+    let mut message = Vec::with_capacity(key.len() + nonce.len() + timestamp.len());
+    message.extend_from_slice(key);
+    message.extend_from_slice(nonce.as_bytes());
+    message.extend_from_slice(timestamp.as_bytes());
+    hmac_sha256(secret, &message)
+}
+
+/// HMAC-SHA256 (RFC 2104) built on the `sp_io` SHA-256 host function.
+pub fn hmac_sha256(secret: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK_SIZE: usize = 64;
+
+    let mut key = [0u8; BLOCK_SIZE];
+    if secret.len() > BLOCK_SIZE {
+        key[..32].copy_from_slice(&sp_io::hashing::sha2_256(secret));
+    } else {
+        key[..secret.len()].copy_from_slice(secret);
+    }
+
+    let mut inner = Vec::with_capacity(BLOCK_SIZE + message.len());
+    inner.extend(key.iter().map(|b| b ^ 0x36));
+    inner.extend_from_slice(message);
+    let inner_hash = sp_io::hashing::sha2_256(&inner);
+
+    let mut outer = Vec::with_capacity(BLOCK_SIZE + inner_hash.len());
+    outer.extend(key.iter().map(|b| b ^ 0x5c));
+    outer.extend_from_slice(&inner_hash);
+    sp_io::hashing::sha2_256(&outer)
+}
+
+/// Lowercase hex encoding.
+pub fn to_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        out.push(DIGITS[(byte >> 4) as usize] as char);
+        out.push(DIGITS[(byte & 0x0f) as usize] as char);
+    }
+    out
+}
+
+/// A response received from the license server.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct HttpResponse {
+    /// The HTTP status code.
+    pub code: u16,
+    /// The full response body, as sent.
+    pub body: Vec<u8>,
+    /// The `Content-Encoding` header, if the server sent one.
+    pub content_encoding: Option<String>,
+}
+
+impl HttpResponse {
+    /// The body with its `Content-Encoding` undone, refused if longer than `max_bytes`.
+    pub fn decoded_body(&self, max_bytes: u32) -> Result<Vec<u8>, BodyError> {
+        let max_bytes = max_bytes as usize;
+        let body = match self.content_encoding.as_deref() {
+            None => self.body.clone(),
+            Some(encoding) if encoding.eq_ignore_ascii_case("identity") => self.body.clone(),
+            Some(encoding) if encoding.eq_ignore_ascii_case("gzip") => {
+                gunzip(&self.body, max_bytes)?
+            }
+            Some(_) => return Err(BodyError::UnsupportedEncoding),
+        };
+
+        if body.len() > max_bytes {
+            return Err(BodyError::TooLarge);
+        }
+        Ok(body)
+    }
+}
+
+/// Why a response body could not be decoded.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BodyError {
+    /// The body is longer than allowed, after decompression.
+    TooLarge,
+    /// The body is not valid gzip.
+    InvalidGzip,
+    /// The body uses a `Content-Encoding` other than `gzip`.
+    UnsupportedEncoding,
+}
+
+/// Decompress a single gzip member (RFC 1952), refusing output longer than `max_bytes`.
+///
+/// The output is checked against the CRC-32 and length in the gzip trailer.
+pub fn gunzip(data: &[u8], max_bytes: usize) -> Result<Vec<u8>, BodyError> {
+    const FHCRC: u8 = 0x02;
+    const FEXTRA: u8 = 0x04;
+    const FNAME: u8 = 0x08;
+    const FCOMMENT: u8 = 0x10;
+    const HEADER_LEN: usize = 10;
+    const TRAILER_LEN: usize = 8;
+
+    if data.len() < HEADER_LEN + TRAILER_LEN || data[..3] != [0x1f, 0x8b, 8] {
+        return Err(BodyError::InvalidGzip);
+    }
+    let flags = data[3];
+    let (header, trailer) = data.split_at(data.len() - TRAILER_LEN);
+
+    // Skip the optional header fields to find the start of the deflate stream.
+    let mut pos = HEADER_LEN;
+    if flags & FEXTRA != 0 {
+        let len = header.get(pos..pos + 2).ok_or(BodyError::InvalidGzip)?;
+        pos += 2 + u16::from_le_bytes([len[0], len[1]]) as usize;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let rest = header.get(pos..).ok_or(BodyError::InvalidGzip)?;
+            let end = rest
+                .iter()
+                .position(|b| *b == 0)
+                .ok_or(BodyError::InvalidGzip)?;
+            pos += end + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        pos += 2;
+    }
+    let deflated = header.get(pos..).ok_or(BodyError::InvalidGzip)?;
+
+    let inflated = miniz_oxide::inflate::decompress_to_vec_with_limit(deflated, max_bytes)
+        .map_err(|e| match e.status {
+            miniz_oxide::inflate::TINFLStatus::HasMoreOutput => BodyError::TooLarge,
+            _ => BodyError::InvalidGzip,
+        })?;
+
+    let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let len = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+    if crc32(&inflated) != crc || inflated.len() as u32 != len {
+        return Err(BodyError::InvalidGzip);
+    }
+    Ok(inflated)
+}
+
+/// CRC-32 (IEEE 802.3), as used by gzip.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Why a request did not produce a response.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HttpFailure {
+    /// The request could not be started.
+    SendFailed,
+    /// No response arrived before the deadline.
+    DeadlineReached,
+    /// The request failed after being sent (e.g. connection reset).
+    RequestFailed,
+    /// The request was not sent because the offchain worker's time budget is spent, see
+    /// [`BudgetedClient`].
+    BudgetExhausted,
+}
+
+/// How the offchain worker issues license requests.
+pub trait LicenseHttpClient {
+    /// Issue a `GET` for `request` and wait for the full response.
+    fn get(&mut self, request: &LicenseRequest) -> Result<HttpResponse, HttpFailure>;
+
+    /// Issue a `POST` of `body` for `request` and wait for the full response.
+    fn post(&mut self, request: &LicenseRequest, body: &[u8]) -> Result<HttpResponse, HttpFailure>;
+
+    /// Wait `millis` milliseconds before the next attempt.
+    fn sleep(&mut self, millis: u64);
+
+    /// The current time in unix milliseconds, by the clock requests and sleeps take time on.
+    fn now_millis(&self) -> u64 {
+        sp_io::offchain::timestamp().unix_millis()
+    }
+}
+
+/// A [`LicenseHttpClient`] that stops issuing requests once `budget_ms` milliseconds have passed
+/// since it was created.
+///
+/// Retries, failover, a backup key and a renewal each take their own time; wrapping the client
+/// once keeps all of them together within the offchain worker's time. Requests past the budget
+/// fail with [`HttpFailure::BudgetExhausted`] without being sent, and sleeps are cut short at
+/// the end of the budget. A budget of zero is unlimited.
+pub struct BudgetedClient<'a, C> {
+    inner: &'a mut C,
+    deadline: Option<u64>,
+}
+
+impl<'a, C: LicenseHttpClient> BudgetedClient<'a, C> {
+    /// Wrap `inner`, granting it `budget_ms` milliseconds from now.
+    pub fn new(inner: &'a mut C, budget_ms: u64) -> Self {
+        let deadline = (budget_ms > 0).then(|| inner.now_millis().saturating_add(budget_ms));
+        Self { inner, deadline }
+    }
+
+    /// Milliseconds left in the budget, or `None` if it is unlimited.
+    pub fn remaining_ms(&self) -> Option<u64> {
+        self.deadline
+            .map(|deadline| deadline.saturating_sub(self.inner.now_millis()))
+    }
+
+    fn check_budget(&self) -> Result<(), HttpFailure> {
+        match self.remaining_ms() {
+            Some(0) => {
+                log::warn!(target: LOG_TARGET, "Offchain time budget spent; not sending request");
+                Err(HttpFailure::BudgetExhausted)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<C: LicenseHttpClient> LicenseHttpClient for BudgetedClient<'_, C> {
+    fn get(&mut self, request: &LicenseRequest) -> Result<HttpResponse, HttpFailure> {
+        self.check_budget()?;
+        self.inner.get(request)
+    }
+
+    fn post(&mut self, request: &LicenseRequest, body: &[u8]) -> Result<HttpResponse, HttpFailure> {
+        self.check_budget()?;
+        self.inner.post(request, body)
+    }
+
+    fn sleep(&mut self, millis: u64) {
+        let millis = self
+            .remaining_ms()
+            .map_or(millis, |remaining| millis.min(remaining));
+        self.inner.sleep(millis);
+    }
+
+    fn now_millis(&self) -> u64 {
+        self.inner.now_millis()
+    }
+}
+
+/// [`LicenseHttpClient`] backed by the `sp_io` offchain HTTP API.
+pub struct OffchainHttpClient;
+
+impl LicenseHttpClient for OffchainHttpClient {
+    fn get(&mut self, request: &LicenseRequest) -> Result<HttpResponse, HttpFailure> {
+        Self::send(http::Request::get(&request.url), request)
+    }
+
+    fn post(&mut self, request: &LicenseRequest, body: &[u8]) -> Result<HttpResponse, HttpFailure> {
+        Self::send(
+            http::Request::post(&request.url, alloc::vec![body]),
+            request,
+        )
+    }
+
+    fn sleep(&mut self, millis: u64) {
+        let until = sp_io::offchain::timestamp().add(Duration::from_millis(millis));
+        sp_io::offchain::sleep_until(until);
+    }
+}
+
+impl OffchainHttpClient {
+    /// Send `http_request` with the headers of `request` and wait for the full response.
+    fn send<T: Default + IntoIterator<Item = I>, I: AsRef<[u8]>>(
+        mut http_request: http::Request<'_, T>,
+        request: &LicenseRequest,
+    ) -> Result<HttpResponse, HttpFailure> {
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(REQUEST_TIMEOUT_MS));
+
+        for (name, value) in &request.headers {
+            http_request = http_request.add_header(name, value);
+        }
+
+        let pending = http_request
+            .deadline(deadline)
+            .send()
+            .map_err(|_| HttpFailure::SendFailed)?;
+
+        let mut response = pending
+            .try_wait(deadline)
+            .map_err(|_| HttpFailure::DeadlineReached)?
+            .map_err(|_| HttpFailure::RequestFailed)?;
+
+        Ok(HttpResponse {
+            code: response.code,
+            content_encoding: response
+                .headers()
+                .find("content-encoding")
+                .map(String::from),
+            body: response.body().collect(),
+        })
+    }
+}
+
+/// How often, and how patiently, a license request is retried.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first. Treated as at least one.
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled for every further retry.
+    pub initial_backoff_ms: u64,
+}
+
+/// Fetch `request`, retrying transport failures and `5xx` responses with exponential backoff.
+///
+/// Any other response, including `4xx`, is returned as-is: the server has answered and asking
+/// again will not change its mind. Once attempts or the time budget run out the last outcome is
+/// returned.
+pub fn fetch_with_retry<C: LicenseHttpClient>(
+    client: &mut C,
+    request: &LicenseRequest,
+    policy: RetryPolicy,
+) -> Result<HttpResponse, HttpFailure> {
+    let max_attempts = policy.max_attempts.max(1);
+    let mut backoff = policy.initial_backoff_ms;
+    let mut attempt = 1;
+
+    loop {
+        let outcome = client.get(request);
+        let retryable = match &outcome {
+            Ok(response) => response.code >= 500,
+            Err(HttpFailure::BudgetExhausted) => false,
+            Err(_) => true,
+        };
+
+        if !retryable || attempt >= max_attempts {
+            return outcome;
+        }
+
+        log::debug!(
+            target: LOG_TARGET,
+            "License request attempt {} of {} failed ({:?}); retrying in {}ms",
+            attempt,
+            max_attempts,
+            outcome.as_ref().map(|r| r.code),
+            backoff
+        );
+        client.sleep(backoff);
+        backoff = backoff.saturating_mul(2);
+        attempt += 1;
+    }
+}
+
+/// Try each of `requests` in turn with [`fetch_with_retry`], moving on to the next one while the
+/// current one still fails in transit or with a `5xx` after all retries.
+///
+/// Returns the first other response, or the last outcome if every request failed. Stops early
+/// once the time budget is spent.
+pub fn fetch_with_failover<C: LicenseHttpClient>(
+    client: &mut C,
+    requests: &[LicenseRequest],
+    policy: RetryPolicy,
+) -> Result<HttpResponse, HttpFailure> {
+    let mut outcome = Err(HttpFailure::SendFailed);
+
+    for (index, request) in requests.iter().enumerate() {
+        outcome = fetch_with_retry(client, request, policy);
+        match &outcome {
+            Ok(response) if response.code < 500 => break,
+            Err(HttpFailure::BudgetExhausted) => break,
+            // Never the URL: it may carry the license key.
+            _ => log::warn!(
+                target: LOG_TARGET,
+                "License endpoint #{} unavailable; failing over",
+                index
+            ),
+        }
+    }
+
+    outcome
+}
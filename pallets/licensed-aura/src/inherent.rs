@@ -0,0 +1,80 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! License proof inherent for Licensed Aura
+//!
+//! With [`crate::Config::EnforceLicenseInherent`] enabled, every block carries the author's
+//! latest license proof as a mandatory inherent. The offchain worker stores the proof in
+//! offchain local storage under [`LICENSE_PROOF_STORAGE_KEY`] after each check, and the node's
+//! inherent data provider hands it to the block author under [`INHERENT_IDENTIFIER`].
+//!
+//! Every validator evaluates the proof while executing the block:
+//! - a block without the inherent, or with a proof that is dated after the block, stale, failed
+//!   or for another key, is rejected;
+//! - an inherent without a proof, from an author that has not checked its license yet, halts
+//!   production within that block;
+//! - a fresh, valid proof resumes production if an earlier missing proof halted it, unless an
+//!   operator has taken over the halt since.
+//!
+//! The proof is the author's attestation of its own check. It is not signed by the license
+//! server.
+
+use codec::{Decode, Encode};
+use frame_support::inherent::{InherentIdentifier, IsFatalError};
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+
+/// The identifier of the license proof inherent.
+pub const INHERENT_IDENTIFIER: InherentIdentifier = *b"licaura0";
+
+/// Offchain local storage key under which the offchain worker keeps its latest proof.
+pub const LICENSE_PROOF_STORAGE_KEY: &[u8] = b"licensed_aura::license_proof";
+
+/// The inherent data: the author's latest proof, if it has one.
+pub type InherentType = Option<LicenseProof>;
+
+/// The outcome of an offchain license check, as carried by the inherent.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct LicenseProof {
+    /// `blake2_256` of the license key the check was made for.
+    pub key_hash: [u8; 32],
+    /// Whether the license server considered the key valid.
+    pub valid: bool,
+    /// When the check was made, in unix milliseconds.
+    pub checked_at: u64,
+}
+
+impl LicenseProof {
+    /// The hash a proof for `license_key` must carry.
+    pub fn key_hash(license_key: &[u8]) -> [u8; 32] {
+        sp_io::hashing::blake2_256(license_key)
+    }
+}
+
+/// Errors that can occur while checking the license proof inherent.
+#[derive(Encode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Decode))]
+pub enum InherentError {
+    /// The block does not contain the license proof inherent.
+    Missing,
+}
+
+impl IsFatalError for InherentError {
+    fn is_fatal_error(&self) -> bool {
+        true
+    }
+}
@@ -41,34 +41,285 @@
 
 extern crate alloc;
 
+use alloc::boxed::Box;
 use alloc::string::String;
 use alloc::vec::Vec;
 use codec::{Decode, Encode, MaxEncodedLen};
 use frame_support::{
-    dispatch::DispatchResult,
+    dispatch::{DispatchResult, GetDispatchInfo, PostDispatchInfo},
     traits::{ConstU32, DisabledValidators, FindAuthor, Get, OnTimestampSet, OneSessionHandler},
-    BoundedSlice, BoundedVec, ConsensusEngineId, Parameter,
+    BoundedVec, ConsensusEngineId, Parameter,
 };
+use http_client::{
+    fetch_with_failover, BudgetedClient, EndpointScheme, HttpFailure, HttpResponse,
+    LicenseHttpClient, LicenseRequest, OffchainHttpClient, RetryPolicy, HMAC_SECRET_STORAGE_KEY,
+};
+use inherent::{LicenseProof, LICENSE_PROOF_STORAGE_KEY};
 use log;
+use response::{LicenseResponse, ParseError, ResponseField};
+use scale_info::TypeInfo;
+use sp_application_crypto::ed25519;
 use sp_consensus_aura::{AuthorityIndex, ConsensusLog, Slot, AURA_ENGINE_ID};
 use sp_runtime::{
     generic::DigestItem,
-    traits::{IsMember, Member, SaturatedConversion, Saturating, Zero},
+    traits::{Dispatchable, IsMember, Member, One, SaturatedConversion, Saturating, Zero},
     transaction_validity::{
         InvalidTransaction, TransactionSource, TransactionValidity, ValidTransaction,
     },
-    RuntimeAppPublic,
+    PerThing, Percent, RuntimeAppPublic, RuntimeDebug,
 };
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
 pub mod filter;
+pub mod http_client;
+pub mod inherent;
 pub mod migrations;
 mod mock;
+pub mod response;
+pub mod runtime_api;
 mod tests;
 
 pub use pallet::*;
 
 const LOG_TARGET: &str = "runtime::aura";
 
+/// Number of recent license check outcomes kept for the health score.
+pub const CHECK_HISTORY_LEN: u32 = 16;
+
+/// Halt reason recorded when the license server rejects the license or cannot be used.
+pub const REASON_LICENSE_INVALID: &[u8] = b"License validation failed";
+
+/// Halt reason recorded when the license server cannot be reached under [`Config::FailClosed`].
+pub const REASON_LICENSE_UNREACHABLE: &[u8] = b"License server unreachable";
+
+/// Halt reason recorded when the license server reports a license whose `expires_at` has passed.
+pub const REASON_LICENSE_EXPIRED: &[u8] = b"License expired";
+
+/// Halt reason recorded when the license is valid but its `not_before` time has not come yet.
+pub const REASON_LICENSE_NOT_YET_ACTIVE: &[u8] = b"License not yet active";
+
+/// Halt reason recorded when the [`Config::LicenseQuorum`] of authority licenses is invalid.
+pub const REASON_LICENSE_QUORUM: &[u8] = b"Authority license quorum invalid";
+
+/// Halt reason recorded when no offchain worker has checked the license for too long, see
+/// [`Config::HaltIfOcwAbsent`].
+pub const REASON_OCW_ABSENT: &[u8] = b"License offchain worker absent";
+
+/// Halt reason recorded in place of an internally generated one that does not fit [`HaltReason`].
+pub const REASON_FALLBACK: &[u8] = b"Halted by license enforcement (reason too long)";
+
+/// Prefix of the [`HaltReason`] recorded for a halt started by `sudo_simulate_halt`.
+pub const REASON_SIMULATION_PREFIX: &[u8] = b"[simulation] ";
+
+/// Offchain local storage key counting license checks since the node started.
+///
+/// The node clears it on startup to restart the [`Config::OffchainWarmupChecks`] warm-up.
+pub const WARMUP_CHECKS_STORAGE_KEY: &[u8] = b"licensed_aura::warmup_checks";
+
+/// Offchain local storage key holding this node's random instance ID, generated on first use.
+///
+/// Sent with every license request, so the server can spot one license key used by several
+/// nodes. It never goes on-chain.
+pub const INSTANCE_ID_STORAGE_KEY: &[u8] = b"licensed_aura::instance_id";
+
+/// Offchain local storage key holding the block before which the offchain worker does not retry
+/// a failed transaction submission, see [`Config::SubmissionRetryBackoff`].
+pub const SUBMIT_BACKOFF_STORAGE_KEY: &[u8] = b"licensed_aura::submit_backoff_until";
+
+/// Offchain local storage key holding the [`CurrentSession`] of this node's last license check,
+/// see [`Config::CheckOncePerSession`].
+pub const CHECKED_SESSION_STORAGE_KEY: &[u8] = b"licensed_aura::checked_session";
+
+/// Offchain local storage key of the lock that keeps offchain workers of overlapping blocks from
+/// checking the license at the same time.
+pub const CHECK_LOCK_STORAGE_KEY: &[u8] = b"licensed_aura::check_lock";
+
+/// How long a license check may hold [`CHECK_LOCK_STORAGE_KEY`], in milliseconds, before a
+/// worker that died holding it is assumed gone.
+pub const CHECK_LOCK_DEADLINE_MS: u64 = 5 * 60 * 1000;
+
+/// Prefix of the offchain index keys under which a [`HaltRecord`] is written whenever production
+/// halts, see [`Pallet::halt_record_key`].
+pub const HALT_RECORD_INDEX_PREFIX: &[u8] = b"licensed_aura::halt_record::";
+
+/// Domain separator of the message signed to grant a perpetual license, see
+/// [`Pallet::perpetual_license_message`].
+pub const PERPETUAL_LICENSE_CONTEXT: &[u8] = b"licensed-aura:perpetual";
+
+/// Maximum length of a field name in [`pallet::ResponseFieldNames`], in bytes.
+pub const MAX_RESPONSE_FIELD_NAME_LEN: u32 = 64;
+
+/// Maximum length of a perpetual license token, in bytes.
+pub const MAX_PERPETUAL_TOKEN_LEN: u32 = 256;
+
+/// Number of recent block authors kept in [`pallet::RecentAuthors`].
+pub const AUTHOR_HISTORY_LEN: u32 = 256;
+
+/// Number of recent blocks whose slot is kept in [`pallet::SlotHistory`].
+pub const SLOT_HISTORY_LEN: u32 = 64;
+
+/// Number of recent halts kept in [`pallet::HaltLog`].
+pub const HALT_LOG_LEN: u32 = 64;
+
+/// Number of ended halts, with their reasons, kept in [`pallet::HaltHistory`].
+pub const HALT_HISTORY_LEN: u32 = 16;
+
+/// Maximum number of license endpoints.
+pub const MAX_LICENSE_ENDPOINTS: u32 = 8;
+
+/// Maximum length of a license endpoint URL, in bytes.
+pub const MAX_LICENSE_ENDPOINT_LEN: u32 = 256;
+
+/// Shortest check interval [`Pallet::sudo_set_check_interval`] accepts, in milliseconds.
+pub const MIN_CHECK_INTERVAL: u64 = 10_000;
+
+/// Longest check interval [`Pallet::sudo_set_check_interval`] accepts, in milliseconds.
+pub const MAX_CHECK_INTERVAL: u64 = 24 * 60 * 60 * 1000;
+
+/// Shortest non-zero auto-recovery delay, in blocks. Smaller delays are raised to it, so that a
+/// misconfigured delay cannot resume production right after a halt.
+pub const MIN_AUTO_RECOVERY_BLOCKS: u32 = 10;
+
+/// The license endpoint queried when none are configured.
+pub const DEFAULT_LICENSE_ENDPOINT: &str = "http://localhost:3000/license";
+
+/// Maximum length of a licensed feature name, in bytes.
+pub const MAX_LICENSE_FEATURE_LEN: u32 = 64;
+
+/// The name of a licensed feature.
+pub type LicenseFeature = BoundedVec<u8, ConstU32<MAX_LICENSE_FEATURE_LEN>>;
+
+/// A license endpoint URL.
+pub type LicenseEndpoint = BoundedVec<u8, ConstU32<MAX_LICENSE_ENDPOINT_LEN>>;
+
+/// A snapshot of the pallet's license and halt state, for debugging and tooling.
+///
+/// The license key itself is never included, only its `blake2_256` fingerprint.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct LicensedAuraDebug<BlockNumber> {
+    /// [`pallet::HaltProduction`].
+    pub halted: bool,
+    /// [`pallet::HaltReason`].
+    pub halt_reason: Option<Vec<u8>>,
+    /// [`pallet::HaltedByLicenseProof`].
+    pub halted_by_license_proof: bool,
+    /// `blake2_256` of [`pallet::LicenseKey`].
+    pub license_key_fingerprint: Option<[u8; 32]>,
+    /// [`pallet::LicenseTerm`].
+    pub license_term: Option<(u64, u64)>,
+    /// [`pallet::LicenseUsageThresholdReached`].
+    pub license_usage_threshold_reached: u8,
+    /// [`pallet::BypassCacheOnce`].
+    pub bypass_cache_once: bool,
+    /// [`pallet::CurrentSlot`].
+    pub current_slot: u64,
+    /// Number of entries in [`pallet::Authorities`].
+    pub authorities_count: u32,
+    /// Number of outcomes in [`pallet::CheckHistory`].
+    pub recent_checks: u32,
+    /// Number of valid outcomes in [`pallet::CheckHistory`].
+    pub recent_valid_checks: u32,
+    /// [`pallet::LastCheckReportedAt`].
+    pub last_check_reported_at: Option<BlockNumber>,
+    /// [`Pallet::enforcement_health`], staleness penalty included.
+    pub enforcement_health: u8,
+    /// [`pallet::HaltedAtBlock`].
+    pub halted_at_block: Option<BlockNumber>,
+    /// [`pallet::SafeMode`].
+    pub safe_mode: bool,
+    /// [`pallet::HaltedByLicenseQuorum`].
+    pub halted_by_license_quorum: bool,
+    /// [`pallet::SimulatedHaltUntil`].
+    pub simulated_halt_until: Option<BlockNumber>,
+    /// [`pallet::AutoRecoveryPaused`].
+    pub auto_recovery_paused: bool,
+    /// [`pallet::PerpetuallyLicensed`].
+    pub perpetually_licensed: bool,
+    /// [`pallet::ConsecutiveCheckFailures`].
+    pub consecutive_check_failures: u32,
+}
+
+/// Whether a halted chain will resume on its own, and if not why, for operators.
+///
+/// See [`Pallet::auto_recovery_status`].
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum AutoRecoveryStatus<BlockNumber> {
+    /// Production is not halted, so there is nothing to recover from.
+    NotHalted,
+    /// The auto-recovery delay in effect is zero, or the halt block is unknown.
+    Disabled,
+    /// An operator paused auto-recovery, see [`AutoRecoveryPaused`].
+    Paused,
+    /// The chain is in safe mode, which only a sudo resume ends.
+    PermanentHalt,
+    /// Production resumes on its own at the start of this block.
+    PendingAtBlock(BlockNumber),
+}
+
+/// What `on_initialize` of the next block would do to production, for operators.
+///
+/// See [`Pallet::simulate_on_initialize_decision`].
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum EnforcementDecision {
+    /// Production halts, with this reason.
+    Halt(Vec<u8>),
+    /// A halted chain resumes production.
+    Resume,
+    /// Production carries on as it is, halted or not.
+    Continue,
+    /// The block cannot be imported: its author is disabled and past its grace period.
+    Panic,
+}
+
+/// The license enforcement policy in effect, overrides included, for dashboards.
+///
+/// Secrets such as the license key, auth token and HMAC secret are never included.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct EnforcementConfig<BlockNumber> {
+    /// [`Pallet::license_endpoints`], highest priority first.
+    pub license_endpoints: Vec<Vec<u8>>,
+    /// [`Config::EndpointSelection`].
+    pub endpoint_selection: EndpointSelection,
+    /// [`Pallet::effective_check_interval`], in milliseconds.
+    pub check_interval: u64,
+    /// [`pallet::CheckIntervalOverride`].
+    pub check_interval_override: Option<u64>,
+    /// [`Config::CheckEverySlots`].
+    pub check_every_slots: Option<u32>,
+    /// [`pallet::ServerMinCheckInterval`].
+    pub server_min_check_interval: Option<u64>,
+    /// [`Config::LicenseCacheTtl`], in milliseconds.
+    pub license_cache_ttl: u64,
+    /// [`Config::OffchainWarmupChecks`].
+    pub offchain_warmup_checks: u32,
+    /// [`Config::LicenseProofMaxAge`], in milliseconds.
+    pub license_proof_max_age: u64,
+    /// [`Config::MaxClockDrift`], in milliseconds.
+    pub max_clock_drift: u64,
+    /// [`Pallet::auto_recovery_delay`]. Zero if auto-recovery is disabled.
+    pub auto_recovery_delay: BlockNumber,
+    /// [`Config::FailClosed`].
+    pub fail_closed: bool,
+    /// [`Config::EnforceLicenseInherent`].
+    pub enforce_license_inherent: bool,
+    /// [`Config::HaltIfOcwAbsent`].
+    pub halt_if_ocw_absent: bool,
+    /// [`Config::OcwAbsenceThreshold`].
+    pub ocw_absence_threshold: BlockNumber,
+    /// Whether authorities check their own keys, see [`Pallet::has_authority_license_keys`].
+    pub per_authority_keys: bool,
+    /// [`Config::LicenseQuorum`].
+    pub license_quorum: Percent,
+    /// [`pallet::SafeMode`].
+    pub safe_mode: bool,
+}
+
+/// Reported when the runtime is configured with a zero [`Config::SlotDuration`].
+pub(crate) const ZERO_SLOT_DURATION: &str =
+    "Licensed Aura `SlotDuration` must not be zero; it is used to derive the slot from the \
+     timestamp. Configure a non-zero value, e.g. twice the timestamp `MinimumPeriod`.";
+
 /// A slot duration provider which infers the slot duration from the
 /// [`pallet_timestamp::Config::MinimumPeriod`] by multiplying it by two, to ensure
 /// that authors have the majority of their slot to author within.
@@ -83,6 +334,370 @@ impl<T: pallet_timestamp::Config> Get<T::Moment> for MinimumPeriodTimesTwo<T> {
     }
 }
 
+/// What to do when a new session hands over more authorities than [`Config::MaxAuthorities`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OversizedAuthoritySetPolicy {
+    /// Keep the first `MaxAuthorities` authorities and drop the rest.
+    Truncate,
+    /// Keep the previous authority set unchanged.
+    Reject,
+}
+
+/// What to do with an authority set listing the same authority more than once, which would give
+/// it several turns of the round-robin.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DuplicateAuthorityPolicy {
+    /// Keep the first occurrence of each authority and drop the repeats.
+    Dedup,
+    /// Keep the previous authority set unchanged, and refuse a genesis set outright.
+    Reject,
+}
+
+/// How a new session's authority set replaces the current one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AuthorityUpdatePolicy {
+    /// Take the session's order as is.
+    Replace,
+    /// Keep the remaining authorities in their current order and append the new ones, so that
+    /// as many authorities as possible keep their index, and with it their place in the
+    /// round-robin.
+    PreserveIndices,
+}
+
+/// How the offchain worker picks the license endpoint it queries first.
+///
+/// Whichever endpoint is picked, the others are still tried in turn while it is unreachable.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug, TypeInfo)]
+pub enum EndpointSelection {
+    /// Always start with the highest priority endpoint.
+    Failover,
+    /// Start with the next endpoint on every check.
+    RoundRobin,
+    /// Start with an endpoint picked from the offchain random seed.
+    Random,
+}
+
+/// How a `200` license response without a validity field is treated.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug, TypeInfo)]
+pub enum MissingValidFieldPolicy {
+    /// The license is invalid. The historical behavior.
+    Invalid,
+    /// The response is unusable, like an unreachable server: production only halts under
+    /// [`Config::FailClosed`].
+    Unparseable,
+    /// The license is valid; servers that only answer for licensed keys need not say so.
+    Valid,
+}
+
+/// The runtime-internal caller of [`Pallet::request_halt`].
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum HaltSource {
+    /// A governance pallet.
+    Governance,
+    /// An emergency or monitoring pallet.
+    Emergency,
+    /// Another pallet, by its index in the runtime.
+    Pallet(u8),
+}
+
+/// What caused a halt, as recorded in [`pallet::HaltLog`].
+#[derive(
+    Encode,
+    Decode,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    RuntimeDebug,
+    TypeInfo,
+    MaxEncodedLen,
+)]
+pub enum HaltReasonKind {
+    /// An operator halted production, possibly into safe mode.
+    Manual,
+    /// The license server rejected the license, or it is not active yet.
+    LicenseInvalid,
+    /// The license server could not be reached under [`Config::FailClosed`].
+    NetworkFailure,
+    /// No fresh license proof was included, see [`Config::LicenseProofMaxAge`].
+    LicenseProofLapsed,
+    /// The [`Config::LicenseQuorum`] of authority licenses was invalid.
+    LicenseQuorum,
+    /// No offchain worker reported for too long, see [`Config::HaltIfOcwAbsent`].
+    OffchainWorkerAbsent,
+    /// Runtime code called [`Pallet::request_halt`].
+    Requested,
+    /// A rehearsal started by `sudo_simulate_halt`.
+    Simulated,
+}
+
+impl HaltReasonKind {
+    /// Whether halts of this kind come from license enforcement rather than from an operator or
+    /// other runtime code.
+    pub fn is_license_enforcement(self) -> bool {
+        matches!(
+            self,
+            Self::LicenseInvalid
+                | Self::NetworkFailure
+                | Self::LicenseProofLapsed
+                | Self::LicenseQuorum
+                | Self::OffchainWorkerAbsent
+        )
+    }
+
+    /// The kind of a halt the offchain worker submitted with `reason`.
+    pub fn of_offchain_reason(reason: Option<&[u8]>) -> Self {
+        match reason {
+            Some(REASON_LICENSE_UNREACHABLE) => Self::NetworkFailure,
+            _ => Self::LicenseInvalid,
+        }
+    }
+}
+
+/// A halt as written to the offchain index, so indexers can rebuild the halt timeline without
+/// replaying blocks.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct HaltRecord<BlockNumber> {
+    /// The block production halted in.
+    pub block: BlockNumber,
+    /// The halt reason recorded on-chain, empty if there was none.
+    pub reason: Vec<u8>,
+    /// What caused the halt.
+    pub source: HaltReasonKind,
+}
+
+/// A halt that has ended, as kept in [`pallet::HaltHistory`].
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct EndedHalt<BlockNumber> {
+    /// The block the halt started in, if known.
+    pub halted_at: Option<BlockNumber>,
+    /// The block production resumed in.
+    pub resumed_at: BlockNumber,
+    /// What caused the halt.
+    pub kind: Option<HaltReasonKind>,
+    /// The halt reason recorded on-chain when production resumed.
+    pub reason: Option<BoundedVec<u8, ConstU32<256>>>,
+}
+
+/// Why an offchain worker license check could not be completed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OffchainError {
+    /// No license key is set on-chain.
+    KeyNotSet,
+    /// The license key is not valid UTF-8.
+    BadKeyUtf8,
+    /// The license request could not be started.
+    SendFailed,
+    /// No response arrived before the request deadline.
+    DeadlineReached,
+    /// The license request failed after being sent.
+    RequestFailed,
+    /// The license response body is not valid UTF-8.
+    BadResponseUtf8,
+    /// Another offchain worker is already checking the license.
+    LockBusy,
+    /// The local authority key could not sign its license report.
+    SigningFailed,
+    /// The license server did not answer the renewal request with `200`.
+    RenewalRejected,
+    /// The renewal response lacks a license key or an expiry.
+    BadRenewalResponse,
+    /// The renewal response does not say the license is valid, or does not attest the
+    /// [`PinnedCertFingerprint`].
+    UnverifiedRenewal,
+    /// The license key contains characters that cannot be sent in a renewal request.
+    UnsafeRenewalKey,
+    /// The license response has no validity field, see [`MissingValidFieldPolicy::Unparseable`].
+    MissingValidField,
+    /// The offchain worker ran out of [`Config::OffchainTimeBudget`].
+    TimeBudgetExhausted,
+}
+
+impl OffchainError {
+    /// A short description for the logs.
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::KeyNotSet => "License key not set",
+            Self::BadKeyUtf8 => "Invalid license key UTF8",
+            Self::SendFailed => "send failed",
+            Self::DeadlineReached => "wait failed",
+            Self::RequestFailed => "http error",
+            Self::BadResponseUtf8 => "Invalid UTF8 in license response",
+            Self::LockBusy => "license check already in progress",
+            Self::SigningFailed => "Failed to sign authority license report",
+            Self::RenewalRejected => "License server refused the renewal",
+            Self::BadRenewalResponse => "Renewal response lacks a license key or expiry",
+            Self::UnverifiedRenewal => "Renewal response is not valid or not attested",
+            Self::UnsafeRenewalKey => "License key cannot be sent in a renewal request",
+            Self::MissingValidField => "License response has no validity field",
+            Self::TimeBudgetExhausted => "offchain time budget exhausted",
+        }
+    }
+}
+
+impl From<HttpFailure> for OffchainError {
+    fn from(failure: HttpFailure) -> Self {
+        match failure {
+            HttpFailure::SendFailed => Self::SendFailed,
+            HttpFailure::DeadlineReached => Self::DeadlineReached,
+            HttpFailure::RequestFailed => Self::RequestFailed,
+            HttpFailure::BudgetExhausted => Self::TimeBudgetExhausted,
+        }
+    }
+}
+
+/// Outcome of a single license check, reported on-chain by the offchain worker.
+///
+/// Timestamps are unix milliseconds, matching [`pallet_timestamp`].
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct LicenseCheckReport {
+    /// Whether the license server considered the key valid.
+    pub valid: bool,
+    /// Start of the license term, if the server reported `issued_at`.
+    pub issued_at: Option<u64>,
+    /// End of the license term, if the server reported `expires_at`.
+    pub expires_at: Option<u64>,
+    /// Minimum milliseconds between checks, if the server reported `min_check_interval`.
+    pub min_check_interval: Option<u64>,
+    /// Activation time of the license, if the server reported `not_before`.
+    pub not_before: Option<u64>,
+    /// Names of the licensed features, if the server reported `features`.
+    pub features: Option<Vec<Vec<u8>>>,
+}
+
+impl LicenseCheckReport {
+    /// A report for a check that did not yield a usable answer.
+    pub fn invalid() -> Self {
+        Self {
+            valid: false,
+            issued_at: None,
+            expires_at: None,
+            min_check_interval: None,
+            not_before: None,
+            features: None,
+        }
+    }
+
+    /// Whether the license has yet to become active at `now`.
+    pub fn is_not_yet_active(&self, now: u64) -> bool {
+        self.not_before.is_some_and(|not_before| now < not_before)
+    }
+
+    /// Whether the license term ended by `now`.
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// An authority's outcome of checking its own license key, signed with its authority key.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct AuthorityLicenseReport<BlockNumber, AuthorityId> {
+    /// The authority whose key was checked.
+    pub authority: AuthorityId,
+    /// Whether the license server considered the key valid.
+    pub valid: bool,
+    /// The block the check was made at. Reports must be newer than the last one accepted.
+    pub block_number: BlockNumber,
+}
+
+/// A license renewal obtained from [`Config::RenewalUrl`], signed by the authority whose
+/// offchain worker requested it.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct LicenseRenewal<AuthorityId> {
+    /// The authority that requested the renewal.
+    pub authority: AuthorityId,
+    /// The license key to use from now on.
+    pub license_key: Vec<u8>,
+    /// Start of the renewed term, unix milliseconds.
+    pub issued_at: u64,
+    /// End of the renewed term, unix milliseconds. Must extend the current [`LicenseTerm`].
+    pub expires_at: u64,
+}
+
+/// Attributes blocks that share a slot to their actual authors, see
+/// [`Config::AllowMultipleBlocksPerSlot`].
+///
+/// Aura assigns every slot to a single authority, so by default each block of a slot is
+/// attributed to it. A pallet that lets several authorities build on one slot implements this to
+/// tell them apart. `()` keeps the slot's authority for every block.
+pub trait SameSlotAuthor {
+    /// The authority index that authored block `position` (counting from zero) of `slot`, where
+    /// `assigned` is the index Aura assigns to the slot and `authorities` the size of the set.
+    ///
+    /// An index outside the authority set is ignored in favour of `assigned`.
+    fn author_index(slot: Slot, position: u32, assigned: u32, authorities: u32) -> u32;
+}
+
+impl SameSlotAuthor for () {
+    fn author_index(_slot: Slot, _position: u32, assigned: u32, _authorities: u32) -> u32 {
+        assigned
+    }
+}
+
+/// Shapes the license endpoint URL of a request, for license servers expecting the key or the
+/// block in the path, see [`Config::LicenseUrlBuilder`]. `()` leaves the endpoint as it is.
+///
+/// [`Config::RequestUrlTransformer`] then turns the URL built into the request URL.
+pub trait LicenseUrlBuilder<BlockNumber> {
+    /// The URL of the license endpoint `base` for `key` at block `block`.
+    fn build_url(base: &str, key: &[u8], block: BlockNumber) -> String;
+}
+
+impl<BlockNumber> LicenseUrlBuilder<BlockNumber> for () {
+    fn build_url(base: &str, _key: &[u8], _block: BlockNumber) -> String {
+        String::from(base)
+    }
+}
+
+/// Turns a license endpoint URL and the license key into the URL of a request, see
+/// [`Config::RequestUrlTransformer`]. `()` appends `?key=<license key>`.
+///
+/// Transformers run in the offchain worker for every request, so they may add parameters
+/// computed at request time, such as a timestamp from `sp_io::offchain::timestamp` or a
+/// signature. The offchain worker then adds its `genesis` and `instance` query parameters.
+pub trait UrlTransformer {
+    /// The URL asking the license endpoint `base` about `key`.
+    fn transform(base: &str, key: &str) -> String;
+}
+
+impl UrlTransformer for () {
+    fn transform(base: &str, key: &str) -> String {
+        alloc::format!("{}?key={}", base, key)
+    }
+}
+
+/// Builds the runtime calls and state the benchmarks need but this pallet cannot name, see
+/// [`Config::BenchmarkHelper`].
+#[cfg(feature = "runtime-benchmarks")]
+pub trait BenchmarkHelper<RuntimeCall> {
+    /// `call` dispatched through the runtime's sudo pallet. `()` returns `call` unwrapped, for
+    /// runtimes without one.
+    fn sudo(call: RuntimeCall) -> RuntimeCall;
+
+    /// Make [`Config::HaltAllowedPallets`] list a pallet that the calls of [`Self::sudo`] do not
+    /// belong to, for runtimes that can change it, so that the halt filter looks up the pallet
+    /// of every call it refuses. Does nothing by default.
+    fn allow_another_pallet_while_halted() {}
+}
+
+#[cfg(feature = "runtime-benchmarks")]
+impl<RuntimeCall> BenchmarkHelper<RuntimeCall> for () {
+    fn sudo(call: RuntimeCall) -> RuntimeCall {
+        call
+    }
+}
+
+/// Append `item` to the bounded history `ring`, dropping its oldest entry when it is full.
+fn push_evicting_oldest<Item, Bound: Get<u32>>(ring: &mut BoundedVec<Item, Bound>, item: Item) {
+    if ring.is_full() {
+        ring.remove(0);
+    }
+    // Cannot fail: we just made room.
+    let _ = ring.try_push(item);
+}
+
 #[frame_support::pallet]
 pub mod pallet {
     use super::*;
@@ -98,6 +713,11 @@ pub mod pallet {
         /// The overarching event type.
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
+        /// The origin allowed to halt and resume production and to set the license key.
+        ///
+        /// Usually `EnsureRoot`; governance-run chains may use a collective or an admin account.
+        type HaltOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
         /// The identifier type for an authority.
         type AuthorityId: Member
             + Parameter
@@ -107,11 +727,38 @@ pub mod pallet {
         /// The maximum number of authorities that the pallet can hold.
         type MaxAuthorities: Get<u32>;
 
+        /// The fewest authorities [`Pallet::sudo_set_authorities`] accepts. Sets are never empty,
+        /// whatever this is.
+        #[pallet::constant]
+        type MinAuthorities: Get<u32>;
+
+        /// How to handle a session authority set larger than `MaxAuthorities`.
+        ///
+        /// [`OversizedAuthoritySetPolicy::Truncate`] was the historical behavior and silently
+        /// drops the surplus validators.
+        type OversizedAuthoritySetPolicy: Get<OversizedAuthoritySetPolicy>;
+
+        /// How to handle an authority set with duplicate authorities, at genesis and on
+        /// session changes.
+        type DuplicateAuthorityPolicy: Get<DuplicateAuthorityPolicy>;
+
+        /// How a session authority set replaces the current one.
+        ///
+        /// With [`AuthorityUpdatePolicy::PreserveIndices`] authority indices no longer follow
+        /// the session's validator order, so [`Config::DisabledValidators`] must not rely on it.
+        type AuthorityUpdatePolicy: Get<AuthorityUpdatePolicy>;
+
         /// A way to check whether a given validator is disabled and should not be authoring blocks.
         /// Blocks authored by a disabled validator will lead to a panic as part of this module's
         /// initialization.
         type DisabledValidators: DisabledValidators;
 
+        /// Blocks for which a newly disabled authority's blocks are emptied instead of panicking,
+        /// counted from [`DisabledAuthorSince`], so a disablement can propagate to its node.
+        ///
+        /// Zero keeps the historical panic from the first block.
+        type DisabledAuthorGrace: Get<BlockNumberFor<Self>>;
+
         /// Whether to allow block authors to create multiple blocks per slot.
         ///
         /// If this is `true`, the pallet will allow slots to stay the same across sequential
@@ -126,12 +773,294 @@ pub mod pallet {
         /// using the same slot.
         type AllowMultipleBlocksPerSlot: Get<bool>;
 
+        /// Who authored each of several blocks built on the same slot, for [`FindAuthor`] and
+        /// [`RecentAuthors`]. Only consulted with [`Config::AllowMultipleBlocksPerSlot`].
+        type SameSlotAuthor: SameSlotAuthor;
+
         /// The slot duration Aura should run with, expressed in milliseconds.
         /// The effective value of this type should not change while the chain is running.
         ///
         /// For backwards compatibility either use [`MinimumPeriodTimesTwo`] or a const.
         #[pallet::constant]
         type SlotDuration: Get<<Self as pallet_timestamp::Config>::Moment>;
+
+        /// Percentages of the license term at which [`Event::LicenseUsageThreshold`] is
+        /// emitted, e.g. `[50, 80]`.
+        ///
+        /// Only used when the license server reports both `issued_at` and `expires_at`. Each
+        /// threshold is emitted once per license term.
+        type LicenseUsageThresholds: Get<Vec<u8>>;
+
+        /// How long, in milliseconds, the offchain worker trusts a valid license check before
+        /// asking the server again. `0` disables caching.
+        ///
+        /// The cache can be bypassed once with [`Pallet::sudo_force_fresh_check`].
+        #[pallet::constant]
+        type LicenseCacheTtl: Get<u64>;
+
+        /// Whether the offchain worker counts the last check reported on-chain, by any node,
+        /// towards the check interval, see [`LastCheckReportedTime`].
+        ///
+        /// Otherwise each node only counts its own checks, which are kept in offchain storage, so
+        /// a node that was just swapped in or restarted checks again right away.
+        #[pallet::constant]
+        type SharedCheckResults: Get<bool>;
+
+        /// Default interval between offchain license checks, in milliseconds.
+        ///
+        /// See [`Pallet::effective_check_interval`] for what can override it.
+        #[pallet::constant]
+        type LicenseCheckInterval: Get<u64>;
+
+        /// Check every this many slots instead, if set, so that the cadence follows the block
+        /// time: the interval becomes this times [`Pallet::slot_duration`].
+        ///
+        /// Takes the place of [`Config::LicenseCheckInterval`], not of [`CheckIntervalOverride`].
+        #[pallet::constant]
+        type CheckEverySlots: Get<Option<u32>>;
+
+        /// Whether the offchain worker checks the license once per session, when
+        /// [`CurrentSession`] changes, instead of on [`Pallet::effective_check_interval`], so
+        /// that checks follow validator rotation.
+        ///
+        /// Requires the pallet to be a session handler. On-chain requests for a fresh check are
+        /// still served at once.
+        #[pallet::constant]
+        type CheckOncePerSession: Get<bool>;
+
+        /// How many times the offchain worker tries a license request that failed in transit or
+        /// with a server error, including the first attempt.
+        #[pallet::constant]
+        type LicenseRequestAttempts: Get<u32>;
+
+        /// Delay in milliseconds before the first retry of a license request. Doubled for every
+        /// further retry.
+        #[pallet::constant]
+        type LicenseRetryBackoff: Get<u64>;
+
+        /// Milliseconds one offchain worker run may spend on license requests, across retries,
+        /// failover, the backup key and renewal. Zero leaves the run unlimited.
+        ///
+        /// Once it is spent the worker stops sending requests. If that leaves the check without
+        /// an answer, the worker gives up on it without reporting, whatever
+        /// [`Config::FailClosed`] says, and tries again on a later block.
+        #[pallet::constant]
+        type OffchainTimeBudget: Get<u64>;
+
+        /// Number of blocks between check reports after which the health score takes its full
+        /// staleness penalty.
+        #[pallet::constant]
+        type HealthStalenessBlocks: Get<BlockNumberFor<Self>>;
+
+        /// Health scores at which [`Event::EnforcementHealthThresholdCrossed`] is emitted when
+        /// the score moves across them, in either direction.
+        type HealthScoreThresholds: Get<Vec<u8>>;
+
+        /// Whether a license server that cannot be reached counts as an invalid license.
+        ///
+        /// With `false` (fail-open) the offchain worker gives up on the check and leaves the
+        /// chain running. With `true` (fail-closed) a request that still fails once
+        /// [`Config::LicenseRequestAttempts`] are exhausted is reported as invalid and production
+        /// is halted, just as for an unparseable or non-`200` response.
+        #[pallet::constant]
+        type FailClosed: Get<bool>;
+
+        /// Whether license requests carry the runtime's `spec_version` and `impl_version`, from
+        /// `frame_system::Config::Version`, so the license server can track deployed versions.
+        #[pallet::constant]
+        type ReportNodeVersion: Get<bool>;
+
+        /// Whether license requests carry the current block number, so the license server can
+        /// correlate checks with chain progress and spot a stalled chain.
+        #[pallet::constant]
+        type ReportBlockNumber: Get<bool>;
+
+        /// The overarching call type, for calls queued to run once production resumes.
+        type RuntimeCall: Parameter
+            + Dispatchable<RuntimeOrigin = Self::RuntimeOrigin, PostInfo = PostDispatchInfo>
+            + GetDispatchInfo;
+
+        /// Wraps calls the way the runtime dispatches them in practice, so the halt filter is
+        /// benchmarked on the calls it actually sees.
+        #[cfg(feature = "runtime-benchmarks")]
+        type BenchmarkHelper: BenchmarkHelper<Self::RuntimeCall>;
+
+        /// Most calls [`Pallet::sudo_queue_call_for_resume`] holds at a time.
+        #[pallet::constant]
+        type MaxQueuedCalls: Get<u32>;
+
+        /// Maximum length of the [`AuthToken`], in bytes.
+        #[pallet::constant]
+        type MaxAuthTokenLen: Get<u32>;
+
+        /// Whether every block must carry the author's license proof as a mandatory inherent.
+        ///
+        /// See the [`inherent`] module for how proofs are produced and judged.
+        #[pallet::constant]
+        type EnforceLicenseInherent: Get<bool>;
+
+        /// How old, in milliseconds, a license proof may be relative to the block timestamp
+        /// before it counts as lapsed. Should comfortably exceed [`Config::LicenseCacheTtl`],
+        /// during which the offchain worker does not refresh its proof.
+        #[pallet::constant]
+        type LicenseProofMaxAge: Get<u64>;
+
+        /// Number of blocks the offchain worker waits before submitting another transaction
+        /// after a submission failed, e.g. because the pool is full or the node is syncing.
+        #[pallet::constant]
+        type SubmissionRetryBackoff: Get<BlockNumberFor<Self>>;
+
+        /// Share of the authorities holding their own license key, see [`AuthorityLicenseKeys`],
+        /// whose licenses must be invalid for production to halt.
+        ///
+        /// Rounded up, and never less than one authority.
+        type LicenseQuorum: Get<Percent>;
+
+        /// Maximum number of [`LicensedFeatures`] kept.
+        ///
+        /// When the license server reports more, the first ones are kept and
+        /// [`Event::FeaturesTruncated`] is emitted.
+        #[pallet::constant]
+        type MaxLicensedFeatures: Get<u32>;
+
+        /// Number of consecutive checks that must report the same new feature set before it
+        /// replaces [`LicensedFeatures`], so that a server flapping between answers does not
+        /// flap the features with it. Zero and one apply a new set at once.
+        #[pallet::constant]
+        type FeatureChangeConfirmations: Get<u32>;
+
+        /// How far, in milliseconds, the node's offchain clock may drift from the on-chain
+        /// timestamp before the offchain worker stops trusting it for time-based decisions,
+        /// such as whether the license is active yet, and relies on the server's answer alone.
+        #[pallet::constant]
+        type MaxClockDrift: Get<u64>;
+
+        /// Number of offchain license checks after a node start whose failures are only logged,
+        /// never halting production, so that a server briefly unreachable during startup cannot
+        /// halt the chain.
+        ///
+        /// The node resets the count on startup by clearing [`WARMUP_CHECKS_STORAGE_KEY`].
+        #[pallet::constant]
+        type OffchainWarmupChecks: Get<u32>;
+
+        /// Indices of the pallets, as in `construct_runtime!`, any call of which is allowed while
+        /// halted, on top of the [`filter::AuraHaltFilter`] whitelist. E.g. a pallet for balance
+        /// transfers. Not consulted in safe mode.
+        type HaltAllowedPallets: Get<Vec<u8>>;
+
+        /// The license provider's ed25519 public key, which signs perpetual license tokens for
+        /// `install_perpetual_license`. `None` rules perpetual licenses out.
+        type LicensePublicKey: Get<Option<[u8; 32]>>;
+
+        /// Whether a [`PinnedCertFingerprint`] must be attested with `cert_fingerprint_sig`.
+        ///
+        /// Offchain HTTP does not expose the server's certificate, so a pin alone only checks the
+        /// fingerprint the response reports, which anyone can echo. The HMAC signature is the
+        /// actual security boundary; with this set, a node without an HMAC secret cannot verify
+        /// the pin and counts every response as invalid while one is pinned.
+        type RequireSignedCertFingerprint: Get<bool>;
+
+        /// BLAKE2-256 hashes of the license keys valid for this runtime, for enforcement without
+        /// a license server.
+        ///
+        /// When not empty, the offchain worker checks the key against this list instead of
+        /// asking the license server, and makes no HTTP requests at all: no renewal and no
+        /// backup key either. An absent key halts production like an invalid license.
+        type OfflineKeyHashes: Get<&'static [[u8; 32]]>;
+
+        /// How the offchain worker spreads license checks over the configured endpoints.
+        ///
+        /// [`EndpointSelection::Failover`] keeps the strict priority order of
+        /// [`LicenseEndpoints`].
+        type EndpointSelection: Get<EndpointSelection>;
+
+        /// How the endpoint URL of a license request is shaped from the endpoint, the license
+        /// key and the block. `()` keeps the endpoint as it is.
+        type LicenseUrlBuilder: LicenseUrlBuilder<BlockNumberFor<Self>>;
+
+        /// How the request URL is made from the URL built by [`Config::LicenseUrlBuilder`] and
+        /// the license key, at request time. `()` queries `<url>?key=<license key>`.
+        type RequestUrlTransformer: UrlTransformer;
+
+        /// Where the offchain worker `POST`s `{"key": "<license key>"}` to renew the license, or
+        /// `None` to never renew automatically.
+        ///
+        /// A renewal is attempted after each valid check once the highest of
+        /// [`Config::LicenseUsageThresholds`] has been reached for the current [`LicenseTerm`].
+        /// The server answers `"valid":true` with the renewed `key`, its `expires_at` and
+        /// optionally `issued_at`, attesting any [`PinnedCertFingerprint`] like a license check;
+        /// the first local authority key signs the outcome, see
+        /// [`Pallet::offchain_worker_report_renewal`].
+        type RenewalUrl: Get<Option<&'static str>>;
+
+        /// How a license response lacking both the field at [`Config::ValidityFieldPath`] and
+        /// [`Config::AlternativeValidField`] is treated.
+        type MissingValidField: Get<MissingValidFieldPolicy>;
+
+        /// A boolean field read in place of `valid` when the response has no `valid` field, for
+        /// servers that name it differently, e.g. `"licensed"`.
+        type AlternativeValidField: Get<Option<&'static str>>;
+
+        /// Path through nested objects to the boolean verdict of a license response, for
+        /// servers that wrap it, e.g. `&["data", "license", "valid"]` for
+        /// `{"data":{"license":{"valid":true}}}`.
+        ///
+        /// Empty reads the top-level `valid`. A response missing any object on the path has no
+        /// verdict, see [`Config::MissingValidField`].
+        type ValidityFieldPath: Get<&'static [&'static str]>;
+
+        /// Whether a `200` license response with an empty body means the license is valid, for
+        /// servers that answer nothing but the status. Otherwise it is invalid.
+        ///
+        /// An empty body cannot attest a [`PinnedCertFingerprint`], so with one pinned it is
+        /// always invalid.
+        type EmptyBodyMeansValid: Get<bool>;
+
+        /// Largest license response body accepted, in bytes, after undoing any gzip compression.
+        ///
+        /// Bounds the memory a compressed response can expand to.
+        #[pallet::constant]
+        type MaxResponseBytes: Get<u32>;
+
+        /// Blocks after a halt at which production resumes on its own, or zero to never
+        /// auto-recover. Operators can override it with [`Pallet::sudo_set_auto_recovery_delay`].
+        ///
+        /// Safe mode is never auto-recovered. If the license is still invalid the offchain worker
+        /// halts again on its next check. Non-zero delays below [`MIN_AUTO_RECOVERY_BLOCKS`] are
+        /// raised to it.
+        #[pallet::constant]
+        type AutoRecoveryDelay: Get<BlockNumberFor<Self>>;
+
+        /// Minimum blocks between two [`Event::LicenseCheckFailed`] events, so that a long
+        /// outage does not emit one every check. Failures in between are only counted. Zero
+        /// disables the throttle.
+        #[pallet::constant]
+        type EventThrottleBlocks: Get<BlockNumberFor<Self>>;
+
+        /// Minimum blocks between two [`Event::ProductionHalted`] events, so that rapid halt and
+        /// resume cycles raise one alert. Halts in between still halt production. Zero disables
+        /// the debounce.
+        #[pallet::constant]
+        type HaltEventDebounce: Get<BlockNumberFor<Self>>;
+
+        /// Whether to halt once no offchain worker transaction has landed for
+        /// [`Config::OcwAbsenceThreshold`] blocks while a license key is set, so that the license
+        /// cannot be evaded by running every node with the offchain worker disabled.
+        #[pallet::constant]
+        type HaltIfOcwAbsent: Get<bool>;
+
+        /// Blocks without an offchain worker transaction, see [`LastOcwRun`], after which the
+        /// worker counts as absent.
+        ///
+        /// A worker serving cached results reports nothing, so this must comfortably exceed
+        /// [`Config::LicenseCacheTtl`] plus the check interval.
+        #[pallet::constant]
+        type OcwAbsenceThreshold: Get<BlockNumberFor<Self>>;
+
+        /// Blocks between two [`Event::EnforcementHeartbeat`] events, which let enforcement be
+        /// monitored from events alone. Zero disables the heartbeat.
+        #[pallet::constant]
+        type HeartbeatInterval: Get<BlockNumberFor<Self>>;
     }
 
     #[pallet::pallet]
@@ -140,44 +1069,85 @@ pub mod pallet {
     #[pallet::hooks]
     impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
         fn offchain_worker(block_number: BlockNumberFor<T>) {
-            if let Err(e) = Self::check_license_and_halt_if_needed() {
+            if let Err(e) = Self::check_license_and_halt_if_needed(&mut OffchainHttpClient) {
                 log::error!(
                     target: LOG_TARGET,
-                    "Error in offchain worker at block {:?}: {:?}",
+                    "Error in offchain worker at block {:?}: {}",
                     block_number,
-                    e
+                    e.message()
                 );
             }
         }
 
-        fn on_initialize(_n: BlockNumberFor<T>) -> Weight {
-            if let Some(new_slot) = Self::current_slot_from_digests() {
+        fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+            let recovery_weight = Self::end_simulated_halt_if_due(n)
+                .saturating_add(Self::auto_recover_if_due(n))
+                .saturating_add(Self::dispatch_queued_calls())
+                .saturating_add(Self::halt_if_ocw_absent(n))
+                .saturating_add(Self::emit_heartbeat_if_due(n));
+
+            let slot_weight = if let Some(new_slot) = Self::current_slot_from_digests() {
                 let current_slot = CurrentSlot::<T>::get();
 
                 if T::AllowMultipleBlocksPerSlot::get() {
-                    assert!(current_slot <= new_slot, "Slot must not decrease");
+                    assert!(
+                        current_slot <= new_slot,
+                        "Slot must not decrease: current slot {}, new slot {} \
+                         (AllowMultipleBlocksPerSlot = true)",
+                        *current_slot,
+                        *new_slot,
+                    );
                 } else {
-                    assert!(current_slot < new_slot, "Slot must increase");
+                    assert!(
+                        current_slot < new_slot,
+                        "Slot must increase: current slot {}, new slot {} \
+                         (AllowMultipleBlocksPerSlot = false)",
+                        *current_slot,
+                        *new_slot,
+                    );
                 }
 
                 CurrentSlot::<T>::put(new_slot);
+                Self::note_block_slot(n, new_slot);
+                if T::AllowMultipleBlocksPerSlot::get() {
+                    let position = match SlotBlockPosition::<T>::get() {
+                        Some((slot, position)) if slot == new_slot => position.saturating_add(1),
+                        _ => 0,
+                    };
+                    SlotBlockPosition::<T>::put((new_slot, position));
+                }
 
                 if let Some(n_authorities) = <Authorities<T>>::decode_len() {
                     let authority_index = *new_slot % n_authorities as u64;
                     if T::DisabledValidators::is_disabled(authority_index as u32) {
-                        panic!(
-							"Validator with index {:?} is disabled and should not be attempting to author blocks.",
-							authority_index,
-						);
+                        Self::note_disabled_author(n, authority_index as u32);
                     }
+                    Self::note_block_author(Self::same_slot_author(
+                        new_slot,
+                        authority_index as u32,
+                        n_authorities as u32,
+                    ));
                 }
 
                 // TODO [#3398] Generate offence report for all authorities that skipped their
                 // slots.
 
-                T::DbWeight::get().reads_writes(3, 2) // Updated: Added reads for HaltProduction check
+                T::DbWeight::get().reads_writes(8, 8) // Updated: Added reads for HaltProduction check and license proof
             } else {
-                T::DbWeight::get().reads(2) // Updated: Added read for HaltProduction check
+                T::DbWeight::get().reads_writes(3, 1) // Updated: Added read for HaltProduction check and license proof
+            };
+
+            recovery_weight.saturating_add(slot_weight)
+        }
+
+        fn on_finalize(_n: BlockNumberFor<T>) {
+            DisabledAuthorBlock::<T>::kill();
+            let included = LicenseProofIncluded::<T>::take();
+            if T::EnforceLicenseInherent::get() {
+                assert!(
+                    included,
+                    "Licensed Aura license proof inherent must be included once per block"
+                );
             }
         }
 
@@ -185,6 +1155,10 @@ pub mod pallet {
         fn try_state(_: BlockNumberFor<T>) -> Result<(), sp_runtime::TryRuntimeError> {
             Self::do_try_state()
         }
+
+        fn integrity_test() {
+            assert!(!T::SlotDuration::get().is_zero(), "{}", ZERO_SLOT_DURATION);
+        }
     }
 
     /// The current authority set.
@@ -210,18 +1184,423 @@ pub mod pallet {
     #[pallet::storage]
     pub type HaltReason<T: Config> = StorageValue<_, BoundedVec<u8, ConstU32<256>>, OptionQuery>;
 
+    /// Whether an operator owns the ongoing halt, through a manual halt or `set_halt_reason`, so
+    /// that other halts landing on it keep its [`HaltReason`]. Cleared when production resumes.
+    #[pallet::storage]
+    pub type HaltReasonIsManual<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    /// Strict lockdown: while set, [`crate::filter::AuraHaltFilter`] allows nothing but mandatory
+    /// inherents, `sudo_resume_production` and `sudo_force_resume`, both of which also leave
+    /// safe mode. Once the [`LicenseTerm`] has expired only `sudo_force_resume` does.
+    #[pallet::storage]
+    pub type SafeMode<T: Config> = StorageValue<_, bool, ValueQuery>;
+
     /// License key for validation against the API.
     #[pallet::storage]
     pub type LicenseKey<T: Config> = StorageValue<_, BoundedVec<u8, ConstU32<128>>, OptionQuery>;
 
-    /// Events for the pallet.
-    #[pallet::event]
-    #[pallet::generate_deposit(pub(super) fn deposit_event)]
-    pub enum Event<T: Config> {
-        /// Block production (i.e. transaction execution) was halted.
-        ProductionHalted,
-        /// Block production resumed.
-        ProductionResumed,
+    /// License key checked by the offchain worker when the server finds [`LicenseKey`] invalid.
+    /// Production only halts if both are invalid.
+    #[pallet::storage]
+    pub type BackupLicenseKey<T: Config> =
+        StorageValue<_, BoundedVec<u8, ConstU32<128>>, OptionQuery>;
+
+    /// Whether [`Event::LicenseKeyUnusable`] was emitted for the current [`LicenseKey`]. Cleared
+    /// whenever the key changes.
+    #[pallet::storage]
+    pub type LicenseKeyUnusableReported<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    /// Bearer token sent to the license server in an `Authorization` header, if set.
+    ///
+    /// Like the [`LicenseKey`] it is public on-chain state; it identifies the chain to the server
+    /// but is no secret.
+    #[pallet::storage]
+    pub type AuthToken<T: Config> =
+        StorageValue<_, BoundedVec<u8, T::MaxAuthTokenLen>, OptionQuery>;
+
+    /// The `(issued_at, expires_at)` term last reported by the license server.
+    #[pallet::storage]
+    pub type LicenseTerm<T: Config> = StorageValue<_, (u64, u64), OptionQuery>;
+
+    /// The highest usage threshold already emitted for the current [`LicenseTerm`].
+    #[pallet::storage]
+    pub type LicenseUsageThresholdReached<T: Config> = StorageValue<_, u8, ValueQuery>;
+
+    /// When set, the offchain worker ignores its cached valid result for the next check.
+    ///
+    /// Cleared once the outcome of that check is reported on-chain.
+    #[pallet::storage]
+    pub type BypassCacheOnce<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    /// The block and kind of the most recent halts, oldest first. A halt is only logged when it
+    /// starts, not when an ongoing one is taken over.
+    #[pallet::storage]
+    pub type HaltLog<T: Config> = StorageValue<
+        _,
+        BoundedVec<(BlockNumberFor<T>, HaltReasonKind), ConstU32<HALT_LOG_LEN>>,
+        ValueQuery,
+    >;
+
+    /// The most recent halts that ended, oldest first, with the reason that resuming cleared
+    /// from [`HaltReason`].
+    #[pallet::storage]
+    pub type HaltHistory<T: Config> = StorageValue<
+        _,
+        BoundedVec<EndedHalt<BlockNumberFor<T>>, ConstU32<HALT_HISTORY_LEN>>,
+        ValueQuery,
+    >;
+
+    /// Authority indices of the authors of the most recent blocks, oldest first.
+    #[pallet::storage]
+    pub type RecentAuthors<T: Config> =
+        StorageValue<_, BoundedVec<u32, ConstU32<AUTHOR_HISTORY_LEN>>, ValueQuery>;
+
+    /// The slots of the most recent blocks, oldest first, to tell slot stalls and jumps apart
+    /// without scanning block headers.
+    #[pallet::storage]
+    pub type SlotHistory<T: Config> = StorageValue<
+        _,
+        BoundedVec<(BlockNumberFor<T>, Slot), ConstU32<SLOT_HISTORY_LEN>>,
+        ValueQuery,
+    >;
+
+    /// The slot of the current block and how many blocks of that slot came before it.
+    ///
+    /// Only kept with [`Config::AllowMultipleBlocksPerSlot`], for [`Config::SameSlotAuthor`].
+    #[pallet::storage]
+    pub type SlotBlockPosition<T: Config> = StorageValue<_, (Slot, u32), OptionQuery>;
+
+    /// Outcomes of the most recent reported license checks, oldest first.
+    #[pallet::storage]
+    pub type CheckHistory<T: Config> =
+        StorageValue<_, BoundedVec<bool, ConstU32<CHECK_HISTORY_LEN>>, ValueQuery>;
+
+    /// License endpoints as `(priority, base URL)`, highest priority first.
+    ///
+    /// The offchain worker builds each request URL with [`Config::LicenseUrlBuilder`] and
+    /// [`Config::RequestUrlTransformer`], adds `genesis=<genesis hash>` to it and fails over to
+    /// the next endpoint while one is unreachable. [`DEFAULT_LICENSE_ENDPOINT`] is used when
+    /// empty.
+    #[pallet::storage]
+    pub type LicenseEndpoints<T: Config> = StorageValue<
+        _,
+        BoundedVec<(u8, LicenseEndpoint), ConstU32<MAX_LICENSE_ENDPOINTS>>,
+        ValueQuery,
+    >;
+
+    /// Activation time of the license last reported by the license server, in unix milliseconds.
+    #[pallet::storage]
+    pub type LicenseNotBefore<T: Config> = StorageValue<_, u64, OptionQuery>;
+
+    /// Features enabled by the license, as last reported by a valid check.
+    #[pallet::storage]
+    pub type LicensedFeatures<T: Config> =
+        StorageValue<_, BoundedVec<LicenseFeature, T::MaxLicensedFeatures>, ValueQuery>;
+
+    /// A reported feature set that differs from [`LicensedFeatures`], with the number of
+    /// consecutive checks that reported it, until [`Config::FeatureChangeConfirmations`] is
+    /// reached.
+    #[pallet::storage]
+    pub type PendingFeatures<T: Config> =
+        StorageValue<_, (BoundedVec<LicenseFeature, T::MaxLicensedFeatures>, u32), OptionQuery>;
+
+    /// Block at which [`LicenseKey`] last changed, until a check of the new key is reported.
+    ///
+    /// The offchain worker checks the new key straight away, discarding its cached result and
+    /// last check time.
+    #[pallet::storage]
+    pub type RecheckNow<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    /// Per-authority license keys for consortium chains.
+    ///
+    /// While any are set, each authority's offchain worker checks only its own key instead of
+    /// [`LicenseKey`], and production halts once [`Config::LicenseQuorum`] of them are invalid.
+    #[pallet::storage]
+    pub type AuthorityLicenseKeys<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AuthorityId, BoundedVec<u8, ConstU32<128>>, OptionQuery>;
+
+    /// Latest reported validity of each authority's own license, and the block it was checked at.
+    #[pallet::storage]
+    pub type AuthorityLicenseStatus<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AuthorityId, (bool, BlockNumberFor<T>), OptionQuery>;
+
+    /// Authorities whose offchain workers check the license on every block, ignoring the check
+    /// interval and the cache, to notice license problems early.
+    ///
+    /// Keep the set small: every canary queries the license server once per block.
+    #[pallet::storage]
+    pub type CanaryAuthorities<T: Config> =
+        StorageValue<_, BoundedVec<T::AuthorityId, T::MaxAuthorities>, ValueQuery>;
+
+    /// Whether the current halt was caused by the authority license quorum, so only a
+    /// recovered quorum may resume it.
+    #[pallet::storage]
+    pub type HaltedByLicenseQuorum<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    /// Block at which a status refresh was requested through [`Pallet::request_status_refresh`].
+    ///
+    /// Cleared once the outcome of the refreshed check is reported on-chain.
+    #[pallet::storage]
+    pub type StatusRefreshRequestedAt<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    /// SHA-256 fingerprint of the license server's TLS certificate that responses must attest.
+    ///
+    /// See [`Pallet::verify_cert_fingerprint`].
+    #[pallet::storage]
+    pub type PinnedCertFingerprint<T: Config> = StorageValue<_, [u8; 32], OptionQuery>;
+
+    /// Calls queued by root while halted, dispatched as root at the start of the first block after
+    /// production resumes.
+    ///
+    /// Extrinsics rejected by the [`filter::AuraHaltFilter`] while halted are dropped, not kept:
+    /// their senders must resubmit them after [`Event::ProductionResumed`]. Only calls queued here
+    /// are replayed.
+    #[pallet::storage]
+    #[pallet::unbounded]
+    pub type QueuedCalls<T: Config> =
+        StorageValue<_, BoundedVec<<T as Config>::RuntimeCall, T::MaxQueuedCalls>, ValueQuery>;
+
+    /// Block at which [`Event::LicenseCheckFailed`] was last emitted.
+    #[pallet::storage]
+    pub type LastCheckFailedEventAt<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    /// Failed checks not emitted as [`Event::LicenseCheckFailed`] since the last one, see
+    /// [`Config::EventThrottleBlocks`].
+    #[pallet::storage]
+    pub type SuppressedCheckFailedEvents<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Block at which [`Event::ProductionHalted`] was last emitted, see
+    /// [`Config::HaltEventDebounce`].
+    #[pallet::storage]
+    pub type LastHaltEventAt<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    /// Block at which production was last halted, until it resumes.
+    #[pallet::storage]
+    pub type HaltedAtBlock<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    /// Whether auto-recovery is paused, keeping the chain halted while an incident is
+    /// investigated. The recovery block is still counted from [`HaltedAtBlock`], so a halt
+    /// past it recovers on the first block after unpausing.
+    #[pallet::storage]
+    pub type AutoRecoveryPaused<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    /// Block at whose start a halt from `sudo_simulate_halt` ends, while one is running.
+    ///
+    /// Cleared by any resume, and by a real halt, which takes over from the simulation.
+    #[pallet::storage]
+    pub type SimulatedHaltUntil<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    /// Whether the license provider granted this chain a perpetual license, see
+    /// `install_perpetual_license`. License checks and license-driven halts stop for good.
+    #[pallet::storage]
+    pub type PerpetuallyLicensed<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    /// Operator override of [`Config::AutoRecoveryDelay`], in blocks; zero disables auto-recovery.
+    #[pallet::storage]
+    pub type AutoRecoveryDelayOverride<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    /// Operator override of [`Config::LicenseCheckInterval`], in milliseconds.
+    #[pallet::storage]
+    pub type CheckIntervalOverride<T: Config> = StorageValue<_, u64, OptionQuery>;
+
+    /// Minimum interval between checks mandated by the license server, in milliseconds.
+    ///
+    /// Updated by every valid check report.
+    #[pallet::storage]
+    pub type ServerMinCheckInterval<T: Config> = StorageValue<_, u64, OptionQuery>;
+
+    /// Block of the last manual resume. Each offchain worker drops its backoff state once per
+    /// new value, so that checks and submissions pick up again at their base pace.
+    #[pallet::storage]
+    pub type ResetBackoff<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    /// Block from which each authority is known to be disabled, by authority index, for
+    /// [`Config::DisabledAuthorGrace`]. Cleared when the authorities change.
+    #[pallet::storage]
+    pub type DisabledAuthorSince<T: Config> =
+        StorageMap<_, Twox64Concat, u32, BlockNumberFor<T>, OptionQuery>;
+
+    /// Whether the current block is authored by a disabled authority within its grace, and so
+    /// carries mandatory inherents only.
+    ///
+    /// Killed in `on_finalize`.
+    #[pallet::storage]
+    pub type DisabledAuthorBlock<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    /// Whether the license proof inherent was included in the current block.
+    ///
+    /// Killed in `on_finalize`.
+    #[pallet::storage]
+    pub type LicenseProofIncluded<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    /// Whether production is halted because of a lapsed license proof, in which case a fresh
+    /// valid proof resumes it.
+    #[pallet::storage]
+    pub type HaltedByLicenseProof<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    /// Block at which a transaction from the offchain worker was last accepted, showing that some
+    /// node still runs it. See [`Config::HaltIfOcwAbsent`].
+    #[pallet::storage]
+    pub type LastOcwRun<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    /// Block at which the last license check was reported.
+    #[pallet::storage]
+    pub type LastCheckReportedAt<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    /// On-chain time, in unix milliseconds, and outcome of the last license check reported for
+    /// the chain-wide key, whichever node made it.
+    #[pallet::storage]
+    pub type LastCheckReportedTime<T: Config> = StorageValue<_, (u64, bool), OptionQuery>;
+
+    /// Names under which the license server sends response fields, for servers whose schema
+    /// differs from the standard one. Fields without an entry keep their default name.
+    #[pallet::storage]
+    pub type ResponseFieldNames<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        ResponseField,
+        BoundedVec<u8, ConstU32<MAX_RESPONSE_FIELD_NAME_LEN>>,
+        OptionQuery,
+    >;
+
+    /// Index of the current session, counting the sessions started since genesis.
+    #[pallet::storage]
+    pub type CurrentSession<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Reported license checks that failed since the last valid one.
+    #[pallet::storage]
+    pub type ConsecutiveCheckFailures<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Reported license checks that succeeded in a row while production is halted. Cleared by a
+    /// failed check and when production resumes.
+    #[pallet::storage]
+    pub type ConsecutiveSuccessesWhileHalted<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Block at which the current run of valid license reports began, while unhalted.
+    #[pallet::storage]
+    pub type LicensedStreakStart<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    /// Length in blocks of the current continuously licensed streak, as of the last report.
+    #[pallet::storage]
+    pub type CurrentLicensedStreak<T: Config> = StorageValue<_, BlockNumberFor<T>, ValueQuery>;
+
+    /// Length in blocks of the longest continuously licensed streak so far.
+    #[pallet::storage]
+    pub type LongestLicensedStreak<T: Config> = StorageValue<_, BlockNumberFor<T>, ValueQuery>;
+
+    #[pallet::type_value]
+    pub fn DefaultEnforcementHealth() -> u8 {
+        100
+    }
+
+    /// Enforcement health score from 0 (failing) to 100 (healthy), as of the last check report.
+    ///
+    /// See [`Pallet::compute_health_score`] for how it is derived. It takes no staleness penalty
+    /// here; [`Pallet::enforcement_health`] applies it for the blocks since the last report.
+    #[pallet::storage]
+    pub type EnforcementHealth<T: Config> =
+        StorageValue<_, u8, ValueQuery, DefaultEnforcementHealth>;
+
+    /// Events for the pallet.
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// Block production (i.e. transaction execution) was halted.
+        ProductionHalted,
+        /// Block production resumed.
+        ProductionResumed,
+        /// The given percentage of the license term has elapsed.
+        LicenseUsageThreshold { percent: u8 },
+        /// The next offchain license check will bypass the cache.
+        FreshCheckRequested,
+        /// The enforcement health score moved across a configured threshold.
+        EnforcementHealthThresholdCrossed { threshold: u8, score: u8 },
+        /// The license endpoints were replaced.
+        EndpointsConfigured { count: u32 },
+        /// The pinned certificate fingerprint was set or, with `None`, removed.
+        CertFingerprintPinned { fingerprint: Option<[u8; 32]> },
+        /// The check interval override was set, in milliseconds after clamping.
+        CheckIntervalSet { interval: u64 },
+        /// A fresh license check was requested for the next offchain worker run.
+        StatusRefreshRequested { who: T::AccountId },
+        /// An authority's own license key was set or, with `set: false`, removed.
+        AuthorityLicenseKeySet {
+            authority: T::AuthorityId,
+            set: bool,
+        },
+        /// An authority reported the outcome of checking its own license key.
+        AuthorityLicenseReported {
+            authority: T::AuthorityId,
+            valid: bool,
+        },
+        /// The server reported more features than [`Config::MaxLicensedFeatures`]; only the
+        /// first `kept` of them were stored.
+        FeaturesTruncated { reported: u32, kept: u32 },
+        /// Safe mode was entered; production is halted and only a sudo resume is accepted.
+        SafeModeEntered,
+        /// Safe mode was left by `sudo_resume_production`.
+        SafeModeExited,
+        /// The auto-recovery delay override was set or, with `None`, removed.
+        AutoRecoveryDelaySet { delay: Option<BlockNumberFor<T>> },
+        /// Auto-recovery was paused or unpaused, see [`AutoRecoveryPaused`].
+        AutoRecoveryPauseSet { paused: bool },
+        /// Production resumed on its own, [`Config::AutoRecoveryDelay`] blocks after the halt.
+        AutoRecovered,
+        /// A license check reported an invalid license. `suppressed` more failures were reported
+        /// since the previous event, within [`Config::EventThrottleBlocks`].
+        LicenseCheckFailed { suppressed: u32 },
+        /// A call was queued to be dispatched once production resumes.
+        CallQueuedForResume { index: u32 },
+        /// A queued call was dispatched after production resumed.
+        QueuedCallDispatched { index: u32, result: DispatchResult },
+        /// Production was halted because no offchain worker transaction landed since
+        /// `last_run`, see [`Config::HaltIfOcwAbsent`].
+        OffchainWorkerAbsent { last_run: Option<BlockNumberFor<T>> },
+        /// Periodic summary of enforcement, every [`Config::HeartbeatInterval`] blocks.
+        /// `last_check_age` is the number of blocks since a license check was last reported, if
+        /// ever.
+        EnforcementHeartbeat {
+            halted: bool,
+            last_check_age: Option<BlockNumberFor<T>>,
+            consecutive_failures: u32,
+        },
+        /// The reason of the ongoing halt was replaced by `set_halt_reason`.
+        HaltReasonSet,
+        /// Runtime code halted production through [`Pallet::request_halt`].
+        HaltRequested { source: HaltSource },
+        /// The license was renewed by an offchain worker; the new term ends at `expires_at`.
+        LicenseRenewed { expires_at: u64 },
+        /// [`CanaryAuthorities`] was replaced with `count` authorities.
+        CanaryAuthoritiesSet { count: u32 },
+        /// The authority set changed. Both lists are sorted by encoding, see
+        /// [`Pallet::authority_diff`].
+        AuthoritiesChanged {
+            added: Vec<T::AuthorityId>,
+            removed: Vec<T::AuthorityId>,
+        },
+        /// A simulated halt started and lasts until block `until`; the license is not involved.
+        SimulatedHaltStarted {
+            reason: Vec<u8>,
+            until: BlockNumberFor<T>,
+        },
+        /// A simulated halt ran its course and production resumed.
+        SimulatedHaltEnded,
+        /// A perpetual license signed by [`Config::LicensePublicKey`] was installed.
+        PerpetualLicenseInstalled,
+        /// The offchain worker cannot use [`LicenseKey`] because it is not valid UTF-8, so the
+        /// license is not being checked. Emitted once per key.
+        LicenseKeyUnusable,
+        /// License responses carry `field` under `name`, or under its default name if `None`.
+        ResponseFieldRenamed {
+            field: ResponseField,
+            name: Option<Vec<u8>>,
+        },
+        /// A disabled authority authored this block within [`Config::DisabledAuthorGrace`], so
+        /// it carries mandatory inherents only.
+        DisabledAuthorBlockEmptied { authority_index: u32 },
+        /// An operator reset the count of consecutive failed license checks, which stood at
+        /// `previous`.
+        FailureCounterReset { previous: u32 },
     }
 
     #[pallet::error]
@@ -232,34 +1611,98 @@ pub mod pallet {
         LicenseKeyTooLong,
         /// License key is not set.
         LicenseKeyNotSet,
+        /// The license proof claims a check made after the block's timestamp.
+        LicenseProofFromFuture,
+        /// More than [`MAX_LICENSE_ENDPOINTS`] license endpoints were given.
+        TooManyEndpoints,
+        /// A license endpoint is longer than [`MAX_LICENSE_ENDPOINT_LEN`] bytes.
+        EndpointTooLong,
+        /// A license endpoint is empty or not valid UTF-8.
+        InvalidEndpoint,
+        /// A status refresh is already pending.
+        RefreshAlreadyRequested,
+        /// The authority has no license key of its own.
+        NoAuthorityLicenseKey,
+        /// The authority already reported a check at the same or a later block.
+        StaleAuthorityLicenseReport,
+        /// Production is not halted.
+        NotHalted,
+        /// [`Config::MaxQueuedCalls`] calls are already queued.
+        TooManyQueuedCalls,
+        /// Auth token is longer than [`Config::MaxAuthTokenLen`].
+        AuthTokenTooLong,
+        /// The [`LicenseTerm`] has expired; only `sudo_force_resume` can resume production.
+        LicenseExpired,
+        /// The account is not one of the current authorities.
+        NotAnAuthority,
+        /// The renewal does not extend the current [`LicenseTerm`].
+        StaleLicenseRenewal,
+        /// More than [`Config::MaxAuthorities`] canary authorities were given.
+        TooManyCanaries,
+        /// Production is already halted.
+        AlreadyHalted,
+        /// A simulated halt must last at least one block.
+        EmptySimulatedHalt,
+        /// A perpetual license is already installed.
+        AlreadyPerpetuallyLicensed,
+        /// No [`Config::LicensePublicKey`] is configured to verify perpetual licenses with.
+        NoLicensePublicKey,
+        /// The perpetual license token is longer than [`MAX_PERPETUAL_TOKEN_LEN`] bytes.
+        PerpetualTokenTooLong,
+        /// The perpetual license token is not signed by [`Config::LicensePublicKey`].
+        BadPerpetualLicenseSignature,
+        /// A response field name is longer than [`MAX_RESPONSE_FIELD_NAME_LEN`] bytes.
+        ResponseFieldNameTooLong,
+        /// A response field name must be non-empty UTF-8 without quotes.
+        BadResponseFieldName,
+        /// An authority set must not be empty.
+        EmptyAuthoritySet,
+        /// An authority set must not exceed [`Config::MaxAuthorities`].
+        TooManyAuthorities,
+        /// An authority set must have at least [`Config::MinAuthorities`] authorities.
+        TooFewAuthorities,
+        /// An authority set must not list the same authority twice.
+        DuplicateAuthority,
+        /// The license proof reports a check the license server failed.
+        LicenseProofInvalid,
+        /// The license proof is older than [`Config::LicenseProofMaxAge`].
+        LicenseProofStale,
+        /// The license proof was made for another license key.
+        LicenseProofForAnotherKey,
     }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
-        /// Halt transaction execution (requires sudo / root).
+        /// Halt transaction execution (requires [`Config::HaltOrigin`]).
         ///
         /// Blocks will still be authored, but the runtime's BaseCallFilter
         /// must be configured to disallow all extrinsics except
         /// `sudo_resume_production` (and optionally the offchain halt extrinsic).
         #[pallet::call_index(0)]
-        #[pallet::weight(T::DbWeight::get().writes(2))]
+        #[pallet::weight(Pallet::<T>::halt_weight())]
         pub fn sudo_halt_production(
             origin: OriginFor<T>,
             reason: Option<Vec<u8>>,
         ) -> DispatchResult {
-            ensure_root(origin)?;
-            Self::halt_production_internal(reason)?;
-            Self::deposit_event(Event::ProductionHalted);
+            T::HaltOrigin::ensure_origin(origin)?;
+            Self::halt_production_internal(reason, HaltReasonKind::Manual)?;
+            Self::note_production_halted();
             Ok(())
         }
 
-        /// Resume transaction execution (requires sudo / root).
+        /// Resume transaction execution (requires [`Config::HaltOrigin`]).
+        ///
+        /// Fails with [`Error::LicenseExpired`] while the [`LicenseTerm`] has expired, so an
+        /// operator cannot simply keep running an unlicensed chain; see `sudo_force_resume`.
+        ///
+        /// Also drops the server's minimum check interval and, through [`ResetBackoff`], the
+        /// offchain workers' submission backoff.
         #[pallet::call_index(1)]
-        #[pallet::weight(T::DbWeight::get().writes(2))]
+        #[pallet::weight(T::DbWeight::get().reads_writes(2, 5))]
         pub fn sudo_resume_production(origin: OriginFor<T>) -> DispatchResult {
-            ensure_root(origin)?;
-            Self::resume_production_internal();
-            Self::deposit_event(Event::ProductionResumed);
+            T::HaltOrigin::ensure_origin(origin)?;
+            ensure!(!Self::is_license_term_expired(), Error::<T>::LicenseExpired);
+            Self::resume_by_operator();
             Ok(())
         }
 
@@ -267,308 +1710,2569 @@ pub mod pallet {
         ///
         /// This is emitted by the OCW when license validation fails.
         #[pallet::call_index(2)]
-        #[pallet::weight(T::DbWeight::get().writes(2))]
+        #[pallet::weight(
+            Pallet::<T>::halt_weight().saturating_add(T::DbWeight::get().reads_writes(1, 1))
+        )]
         pub fn offchain_worker_halt_production(
             origin: OriginFor<T>,
             reason: Option<Vec<u8>>,
         ) -> DispatchResult {
             ensure_none(origin)?;
-            Self::halt_production_internal(reason)?;
-            Self::deposit_event(Event::ProductionHalted);
+            Self::note_ocw_run();
+            if Self::is_perpetually_licensed() {
+                return Ok(());
+            }
+            let kind = HaltReasonKind::of_offchain_reason(reason.as_deref());
+            Self::halt_production_internal(reason, kind)?;
+            Self::note_production_halted();
             Ok(())
         }
 
-        /// Set the license key for API validation (requires sudo / root).
+        /// Set the license key for API validation (requires [`Config::HaltOrigin`]).
         #[pallet::call_index(3)]
-        #[pallet::weight(T::DbWeight::get().writes(1))]
+        #[pallet::weight(T::DbWeight::get().reads_writes(2, 4))]
         pub fn set_license_key(origin: OriginFor<T>, license_key: Vec<u8>) -> DispatchResult {
-            ensure_root(origin)?;
+            T::HaltOrigin::ensure_origin(origin)?;
 
             let bounded_key = BoundedVec::<u8, ConstU32<128>>::try_from(license_key)
                 .map_err(|_| Error::<T>::LicenseKeyTooLong)?;
+            if LicenseKey::<T>::get().as_ref() != Some(&bounded_key) {
+                RecheckNow::<T>::put(frame_system::Pallet::<T>::block_number());
+                LicenseKeyUnusableReported::<T>::kill();
+            }
             LicenseKey::<T>::put(bounded_key);
+            // The offchain worker is only missed from the first key on.
+            if !LastOcwRun::<T>::exists() {
+                Self::note_ocw_run();
+            }
 
             log::info!(target: LOG_TARGET, "License key updated");
             Ok(())
         }
 
+        /// Set the [`BackupLicenseKey`], or remove it with `None` (requires
+        /// [`Config::HaltOrigin`]).
+        #[pallet::call_index(25)]
+        #[pallet::weight(T::DbWeight::get().writes(1))]
+        pub fn set_backup_license_key(
+            origin: OriginFor<T>,
+            license_key: Option<Vec<u8>>,
+        ) -> DispatchResult {
+            T::HaltOrigin::ensure_origin(origin)?;
+
+            let bounded_key = license_key
+                .map(BoundedVec::<u8, ConstU32<128>>::try_from)
+                .transpose()
+                .map_err(|_| Error::<T>::LicenseKeyTooLong)?;
+            BackupLicenseKey::<T>::set(bounded_key);
+
+            log::info!(target: LOG_TARGET, "Backup license key updated");
+            Ok(())
+        }
+
         /// Resume production from offchain worker (unsigned transaction).
         ///
         /// This is emitted by the OCW when license validation succeeds and the chain is currently halted.
         #[pallet::call_index(4)]
-        #[pallet::weight(T::DbWeight::get().writes(2))]
+        #[pallet::weight(T::DbWeight::get().writes(3))]
         pub fn offchain_worker_resume_production(origin: OriginFor<T>) -> DispatchResult {
             ensure_none(origin)?;
+            Self::note_ocw_run();
+            // A simulated halt does not depend on the license, so a valid one cannot end it.
+            if SimulatedHaltUntil::<T>::exists() {
+                return Ok(());
+            }
             Self::resume_production_internal();
             Self::deposit_event(Event::ProductionResumed);
             Ok(())
         }
-    }
 
-    #[pallet::genesis_config]
-    #[derive(frame_support::DefaultNoBound)]
-    pub struct GenesisConfig<T: Config> {
-        pub authorities: Vec<T::AuthorityId>,
-        #[serde(
-            default,
-            alias = "licenseKey", // accept camelCase in chain spec JSON
-            serialize_with = "license_key_serde::serialize",
-            deserialize_with = "license_key_serde::deserialize",
-        )]
-        pub license_key: Option<Vec<u8>>,
-    }
+        /// Record the outcome of a license check from the offchain worker (unsigned transaction).
+        #[pallet::call_index(5)]
+        #[pallet::weight(T::DbWeight::get().reads_writes(15, 19))]
+        pub fn offchain_worker_report_check(
+            origin: OriginFor<T>,
+            report: LicenseCheckReport,
+        ) -> DispatchResult {
+            ensure_none(origin)?;
+            Self::note_ocw_run();
+            BypassCacheOnce::<T>::kill();
+            StatusRefreshRequestedAt::<T>::kill();
+            RecheckNow::<T>::kill();
+            if report.valid {
+                ServerMinCheckInterval::<T>::set(report.min_check_interval);
+            }
+            if report.not_before.is_some() || report.valid {
+                LicenseNotBefore::<T>::set(report.not_before);
+            }
+            if report.valid {
+                if let Some(ref features) = report.features {
+                    Self::note_licensed_features(features);
+                }
+            }
+            Self::note_licensed_streak(report.valid);
+            Self::note_check_outcome(report.valid);
+            if !report.valid {
+                Self::note_check_failed();
+            }
 
-    #[pallet::genesis_build]
-    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
-        fn build(&self) {
-            Pallet::<T>::initialize_authorities(&self.authorities);
+            let now = pallet_timestamp::Pallet::<T>::get().saturated_into::<u64>();
+            LastCheckReportedTime::<T>::put((now, report.valid));
 
-            if let Some(ref key) = self.license_key {
-                let bounded_key = BoundedVec::<u8, ConstU32<128>>::try_from(key.clone())
-                    .expect("License key too long for genesis config");
-                LicenseKey::<T>::put(bounded_key);
+            // A license that has not started yet has no term elapsing.
+            if !report.is_not_yet_active(now) {
+                Self::note_license_term(&report);
             }
+            Ok(())
         }
-    }
 
-    /// Allow the chainspec to keep the license key as a readable string.
-    mod license_key_serde {
-        use super::*;
-        use serde::{Deserialize, Deserializer, Serializer};
+        /// Force the next offchain license check to ask the server, ignoring any cached
+        /// valid result (requires sudo / root).
+        #[pallet::call_index(6)]
+        #[pallet::weight(T::DbWeight::get().writes(1))]
+        pub fn sudo_force_fresh_check(origin: OriginFor<T>) -> DispatchResult {
+            ensure_root(origin)?;
+            BypassCacheOnce::<T>::put(true);
+            Self::deposit_event(Event::FreshCheckRequested);
+            Ok(())
+        }
 
-        #[derive(Deserialize)]
-        #[serde(untagged)]
-        enum Input {
-            String(String),
-            Bytes(Vec<u8>),
+        /// Judge the block author's license proof (inherent).
+        ///
+        /// Fails, invalidating the block, if the proof is dated after the block, stale, failed or
+        /// for another key. An author without any proof, such as a node whose offchain worker
+        /// has not checked yet, still gets its block in but halts production, which the next
+        /// fresh, valid proof resumes. Under a perpetual license every proof is accepted.
+        #[pallet::call_index(7)]
+        #[pallet::weight((T::DbWeight::get().reads_writes(8, 3), DispatchClass::Mandatory))]
+        pub fn note_license_proof(
+            origin: OriginFor<T>,
+            proof: Option<LicenseProof>,
+        ) -> DispatchResult {
+            ensure_none(origin)?;
+            let now: u64 = pallet_timestamp::Pallet::<T>::get().saturated_into();
+            if let Some(ref proof) = proof {
+                ensure!(proof.checked_at <= now, Error::<T>::LicenseProofFromFuture);
+            }
+
+            // Nothing lapses under a perpetual license.
+            let perpetual = Self::is_perpetually_licensed();
+            let current_key = Self::expected_proof_license_key();
+            let lapsed = match (&proof, &current_key) {
+                (Some(proof), Some(key)) if !perpetual => {
+                    ensure!(
+                        proof.key_hash == LicenseProof::key_hash(key),
+                        Error::<T>::LicenseProofForAnotherKey
+                    );
+                    ensure!(proof.valid, Error::<T>::LicenseProofInvalid);
+                    ensure!(
+                        now - proof.checked_at <= T::LicenseProofMaxAge::get(),
+                        Error::<T>::LicenseProofStale
+                    );
+                    false
+                }
+                _ => !perpetual,
+            };
+
+            assert!(
+                !LicenseProofIncluded::<T>::exists(),
+                "License proof must be provided only once per block"
+            );
+            LicenseProofIncluded::<T>::put(true);
+
+            if lapsed && !Self::is_halted() {
+                let reason = Self::internal_halt_reason(b"License proof lapsed");
+                Self::halt_production_internal(Some(reason), HaltReasonKind::LicenseProofLapsed)?;
+                HaltedByLicenseProof::<T>::put(true);
+                Self::note_production_halted();
+            } else if !lapsed && HaltedByLicenseProof::<T>::get() {
+                Self::resume_production_internal();
+                Self::deposit_event(Event::ProductionResumed);
+            }
+            Ok(())
         }
 
-        pub fn serialize<S>(key: &Option<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error>
-        where
-            S: Serializer,
-        {
-            match key {
-                Some(bytes) => serializer.serialize_str(&String::from_utf8_lossy(bytes)),
-                None => serializer.serialize_none(),
+        /// Replace the license endpoints (requires sudo / root).
+        ///
+        /// Each entry is `(priority, base URL)`. The offchain worker tries the highest priority
+        /// first; endpoints of equal priority keep their given order. An empty list restores
+        /// [`DEFAULT_LICENSE_ENDPOINT`]. Each URL must have an [`EndpointScheme`].
+        #[pallet::call_index(8)]
+        #[pallet::weight(T::DbWeight::get().writes(1))]
+        pub fn sudo_set_license_endpoints(
+            origin: OriginFor<T>,
+            endpoints: Vec<(u8, Vec<u8>)>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+            ensure!(
+                endpoints.len() <= MAX_LICENSE_ENDPOINTS as usize,
+                Error::<T>::TooManyEndpoints
+            );
+
+            let mut bounded = Vec::with_capacity(endpoints.len());
+            for (priority, url) in endpoints {
+                let scheme = alloc::str::from_utf8(&url)
+                    .ok()
+                    .and_then(EndpointScheme::of);
+                ensure!(scheme.is_some(), Error::<T>::InvalidEndpoint);
+                let url =
+                    LicenseEndpoint::try_from(url).map_err(|_| Error::<T>::EndpointTooLong)?;
+                bounded.push((priority, url));
             }
+            bounded.sort_by(|a, b| b.0.cmp(&a.0));
+
+            let count = bounded.len() as u32;
+            LicenseEndpoints::<T>::put(
+                BoundedVec::try_from(bounded).map_err(|_| Error::<T>::TooManyEndpoints)?,
+            );
+            Self::deposit_event(Event::EndpointsConfigured { count });
+            Ok(())
         }
 
-        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Vec<u8>>, D::Error>
-        where
-            D: Deserializer<'de>,
-        {
-            let value: Option<Input> = Option::deserialize(deserializer)?;
-            Ok(value.map(|v| match v {
-                Input::String(s) => s.into_bytes(),
-                Input::Bytes(b) => b,
-            }))
+        /// Pin the SHA-256 fingerprint of the license server's TLS certificate, or unpin it with
+        /// `None` (requires sudo / root).
+        ///
+        /// Offchain HTTP does not expose the TLS session, so the pin is enforced at the
+        /// application layer: responses that do not attest the pinned fingerprint are treated as
+        /// invalid.
+        #[pallet::call_index(9)]
+        #[pallet::weight(T::DbWeight::get().writes(1))]
+        pub fn sudo_set_pinned_cert_fingerprint(
+            origin: OriginFor<T>,
+            fingerprint: Option<[u8; 32]>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+            PinnedCertFingerprint::<T>::set(fingerprint);
+            Self::deposit_event(Event::CertFingerprintPinned { fingerprint });
+            Ok(())
         }
-    }
-    #[pallet::validate_unsigned]
-    impl<T: Config> ValidateUnsigned for Pallet<T> {
-        type Call = Call<T>;
 
-        fn validate_unsigned(source: TransactionSource, call: &Self::Call) -> TransactionValidity {
-            match call {
-                Call::offchain_worker_halt_production { .. }
-                | Call::offchain_worker_resume_production { .. } => {
-                    // Only allow extrinsics created locally by the offchain worker.
-                    // This prevents malicious actors from submitting these extrinsics remotely.
-                    match source {
-                        TransactionSource::Local => {}
-                        _ => return InvalidTransaction::BadSigner.into(),
-                    }
+        /// Override [`Config::LicenseCheckInterval`] with `ms` milliseconds, clamped to
+        /// [`MIN_CHECK_INTERVAL`]..=[`MAX_CHECK_INTERVAL`] (requires sudo / root).
+        ///
+        /// A server-mandated minimum still takes precedence when it is longer; see
+        /// [`Pallet::effective_check_interval`].
+        #[pallet::call_index(10)]
+        #[pallet::weight(T::DbWeight::get().writes(1))]
+        pub fn sudo_set_check_interval(origin: OriginFor<T>, ms: u64) -> DispatchResult {
+            ensure_root(origin)?;
+            let interval = ms.clamp(MIN_CHECK_INTERVAL, MAX_CHECK_INTERVAL);
+            CheckIntervalOverride::<T>::put(interval);
+            Self::deposit_event(Event::CheckIntervalSet { interval });
+            Ok(())
+        }
+
+        /// Ask validators to check the license on their next offchain worker run, ignoring the
+        /// check interval and cache, and report the outcome on-chain.
+        ///
+        /// Meant for light clients, which cannot run offchain workers but can then read a fresh
+        /// result through the runtime API. Only one refresh can be pending at a time.
+        #[pallet::call_index(11)]
+        #[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+        pub fn request_status_refresh(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                StatusRefreshRequestedAt::<T>::get().is_none(),
+                Error::<T>::RefreshAlreadyRequested
+            );
+
+            StatusRefreshRequestedAt::<T>::put(frame_system::Pallet::<T>::block_number());
+            Self::deposit_event(Event::StatusRefreshRequested { who });
+            Ok(())
+        }
+
+        /// Set or, with `None`, remove an authority's own license key (requires sudo / root).
+        ///
+        /// Any status reported for the previous key is discarded and the quorum re-evaluated.
+        #[pallet::call_index(12)]
+        #[pallet::weight(T::DbWeight::get().reads_writes(
+            2 * T::MaxAuthorities::get() as u64 + 3,
+            5,
+        ))]
+        pub fn sudo_set_authority_license_key(
+            origin: OriginFor<T>,
+            authority: T::AuthorityId,
+            license_key: Option<Vec<u8>>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+
+            let set = license_key.is_some();
+            match license_key {
+                Some(key) => {
+                    let bounded_key = BoundedVec::<u8, ConstU32<128>>::try_from(key)
+                        .map_err(|_| Error::<T>::LicenseKeyTooLong)?;
+                    AuthorityLicenseKeys::<T>::insert(&authority, bounded_key);
+                }
+                None => AuthorityLicenseKeys::<T>::remove(&authority),
+            }
+            AuthorityLicenseStatus::<T>::remove(&authority);
+
+            Self::deposit_event(Event::AuthorityLicenseKeySet { authority, set });
+            Self::enforce_license_quorum()
+        }
+
+        /// Record an authority's check of its own license key from its offchain worker
+        /// (unsigned transaction, signed by the authority), then halt or resume production
+        /// according to [`Config::LicenseQuorum`].
+        #[pallet::call_index(13)]
+        #[pallet::weight(T::DbWeight::get().reads_writes(
+            2 * T::MaxAuthorities::get() as u64 + 8,
+            10,
+        ))]
+        pub fn offchain_worker_report_authority_check(
+            origin: OriginFor<T>,
+            report: AuthorityLicenseReport<BlockNumberFor<T>, T::AuthorityId>,
+            // The signature is checked in `validate_unsigned`.
+            _signature: <T::AuthorityId as RuntimeAppPublic>::Signature,
+        ) -> DispatchResult {
+            ensure_none(origin)?;
+            Self::check_authority_report_is_fresh(&report)?;
+            Self::note_ocw_run();
+
+            BypassCacheOnce::<T>::kill();
+            StatusRefreshRequestedAt::<T>::kill();
+            RecheckNow::<T>::kill();
+            AuthorityLicenseStatus::<T>::insert(
+                &report.authority,
+                (report.valid, report.block_number),
+            );
+            Self::note_check_outcome(report.valid);
+
+            Self::deposit_event(Event::AuthorityLicenseReported {
+                authority: report.authority,
+                valid: report.valid,
+            });
+            Self::enforce_license_quorum()
+        }
+
+        /// Halt production and enter [`SafeMode`] (requires sudo / root).
+        ///
+        /// Stricter than a normal halt: the offchain worker can no longer halt or resume, and
+        /// license proofs or quorum reports cannot lift it. Only `sudo_resume_production` or
+        /// `sudo_force_resume` can, and only `sudo_force_resume` once the [`LicenseTerm`] has
+        /// expired.
+        #[pallet::call_index(14)]
+        #[pallet::weight(Pallet::<T>::halt_weight().saturating_add(T::DbWeight::get().writes(1)))]
+        pub fn sudo_enter_safe_mode(
+            origin: OriginFor<T>,
+            reason: Option<Vec<u8>>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::halt_production_internal(reason, HaltReasonKind::Manual)?;
+            SafeMode::<T>::put(true);
+            Self::note_production_halted();
+            Self::deposit_event(Event::SafeModeEntered);
+            Ok(())
+        }
+
+        /// Override [`Config::AutoRecoveryDelay`] with `delay` blocks, or remove the override
+        /// with `None` (requires sudo / root). A delay of zero disables auto-recovery.
+        #[pallet::call_index(15)]
+        #[pallet::weight(T::DbWeight::get().writes(1))]
+        pub fn sudo_set_auto_recovery_delay(
+            origin: OriginFor<T>,
+            delay: Option<BlockNumberFor<T>>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+            AutoRecoveryDelayOverride::<T>::set(delay);
+            Self::deposit_event(Event::AutoRecoveryDelaySet { delay });
+            Ok(())
+        }
+
+        /// Queue `call` to be dispatched as root at the start of the first block after
+        /// production resumes (requires sudo / root).
+        ///
+        /// Only accepted while halted, and at most [`Config::MaxQueuedCalls`] at a time. See
+        /// [`QueuedCalls`].
+        #[pallet::call_index(16)]
+        #[pallet::weight(T::DbWeight::get().reads_writes(2, 1))]
+        pub fn sudo_queue_call_for_resume(
+            origin: OriginFor<T>,
+            call: Box<<T as Config>::RuntimeCall>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+            ensure!(Self::is_halted(), Error::<T>::NotHalted);
+
+            let index = QueuedCalls::<T>::try_mutate(|calls| {
+                calls.try_push(*call).map(|_| calls.len() as u32 - 1)
+            })
+            .map_err(|_| Error::<T>::TooManyQueuedCalls)?;
+            Self::deposit_event(Event::CallQueuedForResume { index });
+            Ok(())
+        }
+
+        /// Set the auth token sent to the license server (requires [`Config::HaltOrigin`]).
+        ///
+        /// An empty token removes it.
+        #[pallet::call_index(17)]
+        #[pallet::weight(T::DbWeight::get().writes(1))]
+        pub fn set_auth_token(origin: OriginFor<T>, auth_token: Vec<u8>) -> DispatchResult {
+            T::HaltOrigin::ensure_origin(origin)?;
+
+            if auth_token.is_empty() {
+                AuthToken::<T>::kill();
+            } else {
+                let bounded_token = BoundedVec::<u8, T::MaxAuthTokenLen>::try_from(auth_token)
+                    .map_err(|_| Error::<T>::AuthTokenTooLong)?;
+                AuthToken::<T>::put(bounded_token);
+            }
+
+            log::info!(target: LOG_TARGET, "Auth token updated");
+            Ok(())
+        }
+
+        /// Replace the reason of the ongoing halt, e.g. to point at an incident, without
+        /// otherwise touching the halt (requires sudo / root).
+        #[pallet::call_index(18)]
+        #[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+        pub fn set_halt_reason(origin: OriginFor<T>, reason: Vec<u8>) -> DispatchResult {
+            ensure_root(origin)?;
+            ensure!(Self::is_halted(), Error::<T>::NotHalted);
+
+            let bounded_reason = BoundedVec::<u8, ConstU32<256>>::try_from(reason)
+                .map_err(|_| Error::<T>::ReasonTooLong)?;
+            HaltReason::<T>::put(bounded_reason);
+            HaltReasonIsManual::<T>::put(true);
+            Self::deposit_event(Event::HaltReasonSet);
+            Ok(())
+        }
+
+        /// Resume transaction execution like `sudo_resume_production`, even though the
+        /// [`LicenseTerm`] has expired (requires sudo / root).
+        #[pallet::call_index(19)]
+        #[pallet::weight(T::DbWeight::get().writes(5))]
+        pub fn sudo_force_resume(origin: OriginFor<T>) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::resume_by_operator();
+            Ok(())
+        }
+
+        /// Replace the license key and term with a renewal obtained by an authority's offchain
+        /// worker (unsigned transaction, signed by the authority).
+        #[pallet::call_index(20)]
+        #[pallet::weight(T::DbWeight::get().reads_writes(3, 6))]
+        pub fn offchain_worker_report_renewal(
+            origin: OriginFor<T>,
+            renewal: LicenseRenewal<T::AuthorityId>,
+            // The signature is checked in `validate_unsigned`.
+            _signature: <T::AuthorityId as RuntimeAppPublic>::Signature,
+        ) -> DispatchResult {
+            ensure_none(origin)?;
+            Self::check_renewal_is_fresh(&renewal)?;
+            Self::note_ocw_run();
+
+            let bounded_key = BoundedVec::<u8, ConstU32<128>>::try_from(renewal.license_key)
+                .map_err(|_| Error::<T>::LicenseKeyTooLong)?;
+            if LicenseKey::<T>::get().as_ref() != Some(&bounded_key) {
+                RecheckNow::<T>::put(frame_system::Pallet::<T>::block_number());
+                LicenseKeyUnusableReported::<T>::kill();
+            }
+            LicenseKey::<T>::put(bounded_key);
+            LicenseTerm::<T>::put((renewal.issued_at, renewal.expires_at));
+            LicenseUsageThresholdReached::<T>::kill();
+
+            log::info!(target: LOG_TARGET, "License renewed until {}", renewal.expires_at);
+            Self::deposit_event(Event::LicenseRenewed {
+                expires_at: renewal.expires_at,
+            });
+            Ok(())
+        }
+
+        /// Replace [`CanaryAuthorities`] (requires sudo / root). An empty list disables canaries.
+        #[pallet::call_index(21)]
+        #[pallet::weight(T::DbWeight::get().writes(1))]
+        pub fn sudo_set_canary_authorities(
+            origin: OriginFor<T>,
+            canaries: Vec<T::AuthorityId>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+            let canaries = BoundedVec::<_, T::MaxAuthorities>::try_from(canaries)
+                .map_err(|_| Error::<T>::TooManyCanaries)?;
+            let count = canaries.len() as u32;
+            CanaryAuthorities::<T>::put(canaries);
+            Self::deposit_event(Event::CanaryAuthoritiesSet { count });
+            Ok(())
+        }
+
+        /// Pause or unpause auto-recovery without otherwise touching the halt (requires sudo /
+        /// root).
+        #[pallet::call_index(22)]
+        #[pallet::weight(T::DbWeight::get().writes(1))]
+        pub fn sudo_set_auto_recovery_paused(origin: OriginFor<T>, paused: bool) -> DispatchResult {
+            ensure_root(origin)?;
+            AutoRecoveryPaused::<T>::put(paused);
+            Self::deposit_event(Event::AutoRecoveryPauseSet { paused });
+            Ok(())
+        }
+
+        /// Halt for exactly `blocks` blocks, then resume on its own (requires
+        /// [`Config::HaltOrigin`]), to rehearse an incident without touching the license server.
+        ///
+        /// The halt is recorded with a [`REASON_SIMULATION_PREFIX`]ed reason and only
+        /// emits [`Event::SimulatedHaltStarted`] and [`Event::SimulatedHaltEnded`]. License
+        /// checks cannot resume it early and auto-recovery does not apply, but a real halt in
+        /// the meantime takes over and keeps the chain halted.
+        #[pallet::call_index(23)]
+        #[pallet::weight(T::DbWeight::get().reads_writes(1, 4))]
+        pub fn sudo_simulate_halt(
+            origin: OriginFor<T>,
+            reason: Vec<u8>,
+            blocks: BlockNumberFor<T>,
+        ) -> DispatchResult {
+            T::HaltOrigin::ensure_origin(origin)?;
+            ensure!(!Self::is_halted(), Error::<T>::AlreadyHalted);
+            ensure!(!blocks.is_zero(), Error::<T>::EmptySimulatedHalt);
+
+            let recorded = [REASON_SIMULATION_PREFIX, &reason[..]].concat();
+            Self::halt_production_internal(Some(recorded), HaltReasonKind::Simulated)?;
+            let until = frame_system::Pallet::<T>::block_number().saturating_add(blocks);
+            SimulatedHaltUntil::<T>::put(until);
+            Self::deposit_event(Event::SimulatedHaltStarted { reason, until });
+            Ok(())
+        }
+
+        /// Install a perpetual license: `signature` by [`Config::LicensePublicKey`] over the
+        /// [`Pallet::perpetual_license_message`] of `token`.
+        ///
+        /// Sets [`PerpetuallyLicensed`], which stops license checks and license-driven halts for
+        /// good, and resumes production if license enforcement halted it and no operator has
+        /// taken the halt over since. Any signed account may submit it; the signature is the
+        /// authorization.
+        #[pallet::call_index(24)]
+        #[pallet::weight(T::DbWeight::get().reads_writes(4, 7))]
+        pub fn install_perpetual_license(
+            origin: OriginFor<T>,
+            token: Vec<u8>,
+            signature: ed25519::Signature,
+        ) -> DispatchResult {
+            ensure_signed(origin)?;
+            ensure!(
+                !Self::is_perpetually_licensed(),
+                Error::<T>::AlreadyPerpetuallyLicensed
+            );
+            ensure!(
+                token.len() <= MAX_PERPETUAL_TOKEN_LEN as usize,
+                Error::<T>::PerpetualTokenTooLong
+            );
+            let public = T::LicensePublicKey::get().ok_or(Error::<T>::NoLicensePublicKey)?;
+            ensure!(
+                sp_io::crypto::ed25519_verify(
+                    &signature,
+                    &Self::perpetual_license_message(&token),
+                    &ed25519::Public::from_raw(public),
+                ),
+                Error::<T>::BadPerpetualLicenseSignature
+            );
+
+            PerpetuallyLicensed::<T>::put(true);
+            Self::deposit_event(Event::PerpetualLicenseInstalled);
+            // An operator may have taken over a license halt, which the logged kind, that of the
+            // halt's start, does not show.
+            let license_halt = Self::current_halt_kind()
+                .is_some_and(HaltReasonKind::is_license_enforcement)
+                && !HaltReasonIsManual::<T>::get();
+            if Self::is_halted() && !Self::is_safe_mode() && license_halt {
+                Self::resume_production_internal();
+                Self::deposit_event(Event::ProductionResumed);
+            }
+            Ok(())
+        }
+
+        /// Report from the offchain worker that [`LicenseKey`] is not valid UTF-8 (unsigned
+        /// transaction).
+        ///
+        /// Emits [`Event::LicenseKeyUnusable`] unless it was already emitted for this key.
+        #[pallet::call_index(26)]
+        #[pallet::weight(T::DbWeight::get().reads_writes(2, 3))]
+        pub fn offchain_worker_report_unusable_key(origin: OriginFor<T>) -> DispatchResult {
+            ensure_none(origin)?;
+            Self::note_ocw_run();
+            if !LicenseKeyUnusableReported::<T>::get() {
+                LicenseKeyUnusableReported::<T>::put(true);
+                Self::deposit_event(Event::LicenseKeyUnusable);
+            }
+            Ok(())
+        }
+
+        /// Read `field` of license responses under `name`, or under its default name if `None`
+        /// (requires [`Config::HaltOrigin`]). See [`ResponseFieldNames`].
+        #[pallet::call_index(28)]
+        #[pallet::weight(T::DbWeight::get().writes(1))]
+        pub fn set_response_field_name(
+            origin: OriginFor<T>,
+            field: ResponseField,
+            name: Option<Vec<u8>>,
+        ) -> DispatchResult {
+            T::HaltOrigin::ensure_origin(origin)?;
+
+            match name.clone() {
+                None => ResponseFieldNames::<T>::remove(field),
+                Some(name) => {
+                    let valid = core::str::from_utf8(&name)
+                        .is_ok_and(|name| !name.is_empty() && !name.contains('"'));
+                    ensure!(valid, Error::<T>::BadResponseFieldName);
+                    let name = BoundedVec::try_from(name)
+                        .map_err(|_| Error::<T>::ResponseFieldNameTooLong)?;
+                    ResponseFieldNames::<T>::insert(field, name);
+                }
+            }
+            Self::deposit_event(Event::ResponseFieldRenamed { field, name });
+            Ok(())
+        }
+
+        /// Replace the authorities with `new`, in this order (requires sudo / root).
+        ///
+        /// For chains that manage their validators by governance rather than a session pallet.
+        /// The set is validated as a whole, between [`Config::MinAuthorities`] and
+        /// [`Config::MaxAuthorities`] authorities without duplicates, and applied like a session
+        /// change.
+        #[pallet::call_index(27)]
+        #[pallet::weight(
+            T::DbWeight::get().reads_writes(1, 2u64.saturating_add(T::MaxAuthorities::get().into()))
+        )]
+        pub fn sudo_set_authorities(
+            origin: OriginFor<T>,
+            new: Vec<T::AuthorityId>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+            ensure!(!new.is_empty(), Error::<T>::EmptyAuthoritySet);
+            ensure!(
+                new.len() as u32 >= T::MinAuthorities::get(),
+                Error::<T>::TooFewAuthorities
+            );
+            let new = BoundedVec::<_, T::MaxAuthorities>::try_from(new)
+                .map_err(|_| Error::<T>::TooManyAuthorities)?;
+            ensure!(
+                !Self::has_duplicate_authorities(&new),
+                Error::<T>::DuplicateAuthority
+            );
+
+            Self::change_authorities(new);
+            Ok(())
+        }
+
+        /// Forget the license checks that failed since the last valid one, once the outage
+        /// behind them is known to be over (requires sudo / root).
+        #[pallet::call_index(29)]
+        #[pallet::weight(T::DbWeight::get().writes(1))]
+        pub fn sudo_reset_failure_counter(origin: OriginFor<T>) -> DispatchResult {
+            ensure_root(origin)?;
+            let previous = ConsecutiveCheckFailures::<T>::take();
+            Self::deposit_event(Event::FailureCounterReset { previous });
+            Ok(())
+        }
+    }
+
+    #[pallet::inherent]
+    impl<T: Config> ProvideInherent for Pallet<T> {
+        type Call = Call<T>;
+        type Error = inherent::InherentError;
+        const INHERENT_IDENTIFIER: InherentIdentifier = inherent::INHERENT_IDENTIFIER;
+
+        fn create_inherent(data: &InherentData) -> Option<Self::Call> {
+            if !T::EnforceLicenseInherent::get() {
+                return None;
+            }
+
+            let proof = data
+                .get_data::<inherent::InherentType>(&inherent::INHERENT_IDENTIFIER)
+                .ok()
+                .flatten()
+                .flatten();
+            Some(Call::note_license_proof { proof })
+        }
+
+        fn is_inherent_required(_: &InherentData) -> Result<Option<Self::Error>, Self::Error> {
+            if T::EnforceLicenseInherent::get() {
+                Ok(Some(inherent::InherentError::Missing))
+            } else {
+                Ok(None)
+            }
+        }
+
+        fn is_inherent(call: &Self::Call) -> bool {
+            matches!(call, Call::note_license_proof { .. })
+        }
+    }
+
+    #[pallet::genesis_config]
+    #[derive(frame_support::DefaultNoBound)]
+    pub struct GenesisConfig<T: Config> {
+        pub authorities: Vec<T::AuthorityId>,
+        #[serde(
+            default,
+            alias = "licenseKey", // accept camelCase in chain spec JSON
+            serialize_with = "license_key_serde::serialize",
+            deserialize_with = "license_key_serde::deserialize",
+        )]
+        pub license_key: Option<Vec<u8>>,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+        fn build(&self) {
+            // Fail at genesis rather than at the first timestamp of a chain that can never
+            // produce a block.
+            assert!(!T::SlotDuration::get().is_zero(), "{}", ZERO_SLOT_DURATION);
+
+            Pallet::<T>::initialize_authorities(&self.authorities);
+
+            if let Some(ref key) = self.license_key {
+                let bounded_key = BoundedVec::<u8, ConstU32<128>>::try_from(key.clone())
+                    .expect("License key too long for genesis config");
+                LicenseKey::<T>::put(bounded_key);
+            }
+        }
+    }
+
+    /// Allow the chainspec to keep the license key as a readable string.
+    mod license_key_serde {
+        use super::*;
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Input {
+            String(String),
+            Bytes(Vec<u8>),
+        }
+
+        pub fn serialize<S>(key: &Option<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match key {
+                Some(bytes) => serializer.serialize_str(&String::from_utf8_lossy(bytes)),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Vec<u8>>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let value: Option<Input> = Option::deserialize(deserializer)?;
+            Ok(value.map(|v| match v {
+                Input::String(s) => s.into_bytes(),
+                Input::Bytes(b) => b,
+            }))
+        }
+    }
+    #[pallet::validate_unsigned]
+    impl<T: Config> ValidateUnsigned for Pallet<T> {
+        type Call = Call<T>;
+
+        fn validate_unsigned(source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+            match call {
+                Call::offchain_worker_halt_production { .. }
+                | Call::offchain_worker_resume_production { .. }
+                | Call::offchain_worker_report_check { .. }
+                | Call::offchain_worker_report_unusable_key { .. } => {
+                    // Only allow extrinsics created locally by the offchain worker.
+                    // This prevents malicious actors from submitting these extrinsics remotely.
+                    match source {
+                        TransactionSource::Local => {}
+                        _ => return InvalidTransaction::BadSigner.into(),
+                    }
+
+                    // Use the call itself as the unique identifier to prevent duplicates.
+                    ValidTransaction::with_tag_prefix("AuraOCW")
+                        .priority(u64::MAX)
+                        .and_provides(call)
+                        .longevity(1)
+                        .propagate(false)
+                        .build()
+                }
+                Call::offchain_worker_report_authority_check { report, signature } => {
+                    match source {
+                        TransactionSource::Local => {}
+                        _ => return InvalidTransaction::BadSigner.into(),
+                    }
+
+                    if Self::check_authority_report_is_fresh(report).is_err() {
+                        return InvalidTransaction::Stale.into();
+                    }
+                    let signed_by_authority = report
+                        .using_encoded(|payload| report.authority.verify(&payload, signature));
+                    if !signed_by_authority {
+                        return InvalidTransaction::BadProof.into();
+                    }
+
+                    ValidTransaction::with_tag_prefix("AuraOCW")
+                        .priority(u64::MAX)
+                        .and_provides((&report.authority, report.block_number))
+                        .longevity(1)
+                        .propagate(false)
+                        .build()
+                }
+                Call::offchain_worker_report_renewal { renewal, signature } => {
+                    match source {
+                        TransactionSource::Local => {}
+                        _ => return InvalidTransaction::BadSigner.into(),
+                    }
+
+                    if Self::check_renewal_is_fresh(renewal).is_err() {
+                        return InvalidTransaction::Stale.into();
+                    }
+                    let signed_by_authority = renewal
+                        .using_encoded(|payload| renewal.authority.verify(&payload, signature));
+                    if !signed_by_authority {
+                        return InvalidTransaction::BadProof.into();
+                    }
+
+                    ValidTransaction::with_tag_prefix("AuraOCW")
+                        .priority(u64::MAX)
+                        .and_provides((&renewal.authority, renewal.expires_at))
+                        .longevity(1)
+                        .propagate(false)
+                        .build()
+                }
+                _ => InvalidTransaction::Call.into(),
+            }
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    /// Internal function to halt transaction execution.
+    ///
+    /// The reason of an ongoing manual halt is kept when anything but another manual halt comes
+    /// in, so that the operator's explanation is not replaced by a generic license message.
+    fn halt_production_internal(reason: Option<Vec<u8>>, kind: HaltReasonKind) -> DispatchResult {
+        let keep_reason = kind != HaltReasonKind::Manual && HaltReasonIsManual::<T>::get();
+        SimulatedHaltUntil::<T>::kill();
+        let started = !Self::is_halted();
+        if started {
+            let now = frame_system::Pallet::<T>::block_number();
+            HaltedAtBlock::<T>::put(now);
+            HaltLog::<T>::mutate(|log| push_evicting_oldest(log, (now, kind)));
+        }
+        HaltProduction::<T>::put(true);
+        Self::end_licensed_streak();
+
+        if let Some(r) = reason {
+            let bounded_reason = BoundedVec::<u8, ConstU32<256>>::try_from(r)
+                .map_err(|_| Error::<T>::ReasonTooLong)?;
+            if keep_reason {
+                log::info!(target: LOG_TARGET, "Keeping the reason of the ongoing manual halt");
+            } else {
+                HaltReason::<T>::put(bounded_reason);
+            }
+        }
+        if kind == HaltReasonKind::Manual {
+            HaltReasonIsManual::<T>::put(true);
+            // The halt now belongs to the operator, whatever caused it before, so neither a
+            // valid license proof nor a restored quorum lifts it.
+            HaltedByLicenseProof::<T>::kill();
+            HaltedByLicenseQuorum::<T>::kill();
+        }
+
+        if started {
+            let block = frame_system::Pallet::<T>::block_number();
+            let record = HaltRecord {
+                block,
+                reason: HaltReason::<T>::get()
+                    .map(|r| r.into_inner())
+                    .unwrap_or_default(),
+                source: kind,
+            };
+            sp_io::offchain_index::set(&Self::halt_record_key(block), &record.encode());
+        }
+
+        log::warn!(target: LOG_TARGET, "HaltProduction set to true");
+        Ok(())
+    }
+
+    /// Worst-case weight of [`Self::halt_production_internal`] followed by
+    /// [`Self::note_production_halted`], for a manual halt starting a new one.
+    ///
+    /// Reads `HaltReasonIsManual`, `HaltProduction`, `HaltLog`, `HaltReason` and
+    /// `LastHaltEventAt`. Writes `SimulatedHaltUntil`, `HaltedAtBlock`, `HaltLog`,
+    /// `HaltProduction`, both streak values, `HaltReason`, `HaltReasonIsManual`,
+    /// `HaltedByLicenseProof`, `HaltedByLicenseQuorum`, the [`HaltRecord`] offchain index and
+    /// `LastHaltEventAt`.
+    pub(crate) fn halt_weight() -> Weight {
+        T::DbWeight::get().reads_writes(5, 12)
+    }
+
+    /// The offchain index key of the [`HaltRecord`] of a halt that started in `block`.
+    pub fn halt_record_key(block: BlockNumberFor<T>) -> Vec<u8> {
+        [HALT_RECORD_INDEX_PREFIX, &block.encode()[..]].concat()
+    }
+
+    /// A halt reason the pallet generated itself, guaranteed to fit [`HaltReason`].
+    ///
+    /// Unlike a reason passed in by sudo, an oversized one here is not the caller's mistake and
+    /// must not stop the halt, so it is logged and replaced by [`REASON_FALLBACK`].
+    fn internal_halt_reason(reason: &[u8]) -> Vec<u8> {
+        if BoundedVec::<u8, ConstU32<256>>::try_from(reason.to_vec()).is_ok() {
+            return reason.to_vec();
+        }
+        log::error!(
+            target: LOG_TARGET,
+            "Halt reason of {} bytes does not fit; recording fallback reason: {}",
+            reason.len(),
+            String::from_utf8_lossy(reason)
+        );
+        REASON_FALLBACK.to_vec()
+    }
+
+    /// Halt production on behalf of trusted runtime code, such as a governance or emergency
+    /// pallet that detected a reason to stop, without going through an extrinsic.
+    ///
+    /// This bypasses every origin check and is for runtime-internal use only. Do not expose it
+    /// through a call without an origin check of your own. Nothing is changed on error.
+    pub fn request_halt(reason: Vec<u8>, source: HaltSource) -> DispatchResult {
+        frame_support::storage::with_storage_layer(|| {
+            Self::halt_production_internal(Some(reason), HaltReasonKind::Requested)?;
+            Self::note_production_halted();
+            Self::deposit_event(Event::HaltRequested { source });
+            Ok(())
+        })
+    }
+
+    /// Internal function to resume transaction execution.
+    fn resume_production_internal() {
+        if Self::is_halted() {
+            let ended = EndedHalt {
+                halted_at: HaltedAtBlock::<T>::get(),
+                resumed_at: frame_system::Pallet::<T>::block_number(),
+                kind: Self::current_halt_kind(),
+                reason: HaltReason::<T>::get(),
+            };
+            HaltHistory::<T>::mutate(|history| push_evicting_oldest(history, ended));
+        }
+        HaltProduction::<T>::put(false);
+        HaltReason::<T>::kill();
+        HaltReasonIsManual::<T>::kill();
+        HaltedAtBlock::<T>::kill();
+        ConsecutiveSuccessesWhileHalted::<T>::kill();
+        HaltedByLicenseProof::<T>::kill();
+        HaltedByLicenseQuorum::<T>::kill();
+        SimulatedHaltUntil::<T>::kill();
+        log::info!(target: LOG_TARGET, "HaltProduction set to false");
+    }
+
+    /// Resume on an operator's request and drop whatever was slowing the offchain workers down.
+    fn resume_by_operator() {
+        Self::resume_production_internal();
+        // Whatever slowed checks down is presumably what the operator just fixed.
+        ServerMinCheckInterval::<T>::kill();
+        ResetBackoff::<T>::put(frame_system::Pallet::<T>::block_number());
+        Self::deposit_event(Event::ProductionResumed);
+        if SafeMode::<T>::take() {
+            Self::deposit_event(Event::SafeModeExited);
+        }
+    }
+
+    /// Whether the [`LicenseTerm`] last reported by the license server has run out.
+    pub fn is_license_term_expired() -> bool {
+        let now = pallet_timestamp::Pallet::<T>::get().saturated_into::<u64>();
+        matches!(LicenseTerm::<T>::get(), Some((_, expires_at)) if expires_at <= now)
+    }
+
+    /// Public helper: is the chain currently halted?
+    pub fn is_halted() -> bool {
+        HaltProduction::<T>::get()
+    }
+
+    /// Public helper: does the chain hold a perpetual license, see [`PerpetuallyLicensed`]?
+    pub fn is_perpetually_licensed() -> bool {
+        PerpetuallyLicensed::<T>::get()
+    }
+
+    /// The message [`Config::LicensePublicKey`] signs to grant this chain a perpetual license
+    /// with `token`: the SCALE encoding of [`PERPETUAL_LICENSE_CONTEXT`], the genesis hash and
+    /// `token`. The genesis hash keeps a token from being replayed on another chain.
+    pub fn perpetual_license_message(token: &[u8]) -> Vec<u8> {
+        let genesis_hash = frame_system::Pallet::<T>::block_hash(BlockNumberFor::<T>::zero());
+        (PERPETUAL_LICENSE_CONTEXT, genesis_hash, token).encode()
+    }
+
+    /// The kind of the ongoing halt, from the [`HaltLog`], or `None` if not halted.
+    pub fn current_halt_kind() -> Option<HaltReasonKind> {
+        if !Self::is_halted() {
+            return None;
+        }
+        HaltLog::<T>::get().last().map(|(_, kind)| *kind)
+    }
+
+    /// Public helper: is the current block emptied, see [`DisabledAuthorBlock`]?
+    pub fn is_disabled_author_block() -> bool {
+        DisabledAuthorBlock::<T>::get()
+    }
+
+    /// Public helper: is the chain in [`SafeMode`]?
+    pub fn is_safe_mode() -> bool {
+        SafeMode::<T>::get()
+    }
+
+    /// The block at which production will resume on its own, or `None` if the chain is not
+    /// halted, is in safe mode, or auto-recovery is disabled or paused.
+    ///
+    /// Counts [`Pallet::auto_recovery_delay`] from [`HaltedAtBlock`]. Does not apply to a
+    /// simulated halt, which ends at its [`SimulatedHaltUntil`] instead.
+    pub fn auto_recovery_block() -> Option<BlockNumberFor<T>> {
+        if !Self::is_halted()
+            || Self::is_safe_mode()
+            || AutoRecoveryPaused::<T>::get()
+            || SimulatedHaltUntil::<T>::exists()
+        {
+            return None;
+        }
+
+        let delay = Self::auto_recovery_delay();
+        if delay.is_zero() {
+            return None;
+        }
+        HaltedAtBlock::<T>::get().map(|halted_at| halted_at.saturating_add(delay))
+    }
+
+    /// The auto-recovery delay in effect: [`AutoRecoveryDelayOverride`] if set, else
+    /// [`Config::AutoRecoveryDelay`], raised to [`MIN_AUTO_RECOVERY_BLOCKS`] unless zero.
+    pub fn auto_recovery_delay() -> BlockNumberFor<T> {
+        let delay = AutoRecoveryDelayOverride::<T>::get().unwrap_or_else(T::AutoRecoveryDelay::get);
+        let floor = BlockNumberFor::<T>::from(MIN_AUTO_RECOVERY_BLOCKS);
+        if delay.is_zero() || delay >= floor {
+            return delay;
+        }
+        log::warn!(
+            target: LOG_TARGET,
+            "Auto-recovery delay of {:?} blocks is below the minimum; using {}",
+            delay,
+            MIN_AUTO_RECOVERY_BLOCKS
+        );
+        floor
+    }
+
+    /// The first block from `current_block` on whose start production would resume on its own,
+    /// or `None` if the chain is not halted, is in safe mode, or auto-recovery is disabled or
+    /// paused.
+    ///
+    /// A recovery block already behind `current_block` fires at `current_block`. Reads state
+    /// only, so it can be asked about any block without advancing the chain.
+    pub fn auto_recovery_eta(current_block: BlockNumberFor<T>) -> Option<BlockNumberFor<T>> {
+        Self::auto_recovery_block().map(|recovery_block| recovery_block.max(current_block))
+    }
+
+    /// Whether production will resume on its own, and if not why.
+    ///
+    /// A simulated halt is pending at its [`SimulatedHaltUntil`] even while auto-recovery is
+    /// paused or disabled, as it ends there regardless.
+    pub fn auto_recovery_status() -> AutoRecoveryStatus<BlockNumberFor<T>> {
+        if !Self::is_halted() {
+            return AutoRecoveryStatus::NotHalted;
+        }
+        if Self::is_safe_mode() {
+            return AutoRecoveryStatus::PermanentHalt;
+        }
+        if let Some(until) = SimulatedHaltUntil::<T>::get() {
+            return AutoRecoveryStatus::PendingAtBlock(until);
+        }
+        if AutoRecoveryPaused::<T>::get() {
+            return AutoRecoveryStatus::Paused;
+        }
+        Self::auto_recovery_block().map_or(
+            AutoRecoveryStatus::Disabled,
+            AutoRecoveryStatus::PendingAtBlock,
+        )
+    }
+
+    /// What `on_initialize` of the next block would do to production, worked out from current
+    /// storage without changing it.
+    ///
+    /// The next block is assumed to be authored in the slot after [`CurrentSlot`]. Calls queued
+    /// for resume are not dispatched, so a halt they would cause is not foreseen.
+    pub fn simulate_on_initialize_decision() -> EnforcementDecision {
+        let next = frame_system::Pallet::<T>::block_number().saturating_add(One::one());
+
+        let slot = CurrentSlot::<T>::get().saturating_add(1u64);
+        if let Some(n_authorities) = Authorities::<T>::decode_len().filter(|n| *n > 0) {
+            let authority_index = (*slot % n_authorities as u64) as u32;
+            if T::DisabledValidators::is_disabled(authority_index) {
+                let since = DisabledAuthorSince::<T>::get(authority_index).unwrap_or(next);
+                let grace = T::DisabledAuthorGrace::get();
+                if grace.is_zero() || next >= since.saturating_add(grace) {
+                    return EnforcementDecision::Panic;
+                }
+            }
+        }
+
+        let resumes = SimulatedHaltUntil::<T>::get().is_some_and(|until| next >= until)
+            || Self::auto_recovery_block().is_some_and(|at| next >= at);
+        let halted = Self::is_halted() && !resumes;
+        if !halted && T::HaltIfOcwAbsent::get() && Self::ocw_absent_for(next).is_some() {
+            return EnforcementDecision::Halt(REASON_OCW_ABSENT.to_vec());
+        }
+        if resumes {
+            EnforcementDecision::Resume
+        } else {
+            EnforcementDecision::Continue
+        }
+    }
+
+    /// Empty block `n` of the disabled authority `authority_index` while within
+    /// [`Config::DisabledAuthorGrace`] of its disablement, and panic once past it.
+    fn note_disabled_author(n: BlockNumberFor<T>, authority_index: u32) {
+        let since = DisabledAuthorSince::<T>::get(authority_index).unwrap_or(n);
+        let grace = T::DisabledAuthorGrace::get();
+        if grace.is_zero() || n >= since.saturating_add(grace) {
+            panic!(
+                "Validator with index {:?} is disabled and should not be attempting to author \
+                 blocks.",
+                authority_index,
+            );
+        }
+        DisabledAuthorSince::<T>::insert(authority_index, since);
+        DisabledAuthorBlock::<T>::put(true);
+        Self::deposit_event(Event::DisabledAuthorBlockEmptied { authority_index });
+    }
+
+    /// Record that an offchain worker transaction landed in this block, see [`LastOcwRun`].
+    fn note_ocw_run() {
+        LastOcwRun::<T>::put(frame_system::Pallet::<T>::block_number());
+    }
+
+    /// How long no offchain worker transaction has landed as of `now`, if that is past
+    /// [`Config::OcwAbsenceThreshold`] on a chain that needs the worker.
+    fn ocw_absent_for(now: BlockNumberFor<T>) -> Option<BlockNumberFor<T>> {
+        if Self::is_perpetually_licensed()
+            || (!LicenseKey::<T>::exists() && !Self::has_authority_license_keys())
+        {
+            return None;
+        }
+        let last_run = LastOcwRun::<T>::get();
+        let since = now.saturating_sub(last_run.unwrap_or_else(Zero::zero));
+        (since > T::OcwAbsenceThreshold::get()).then_some(since)
+    }
+
+    /// Halt if [`Config::HaltIfOcwAbsent`] is set and no offchain worker transaction has landed
+    /// for more than [`Config::OcwAbsenceThreshold`] blocks while a license key is set.
+    fn halt_if_ocw_absent(now: BlockNumberFor<T>) -> Weight {
+        if !T::HaltIfOcwAbsent::get() {
+            return Weight::zero();
+        }
+        let weight = T::DbWeight::get().reads(5);
+        if Self::is_halted() {
+            return weight;
+        }
+        let Some(since) = Self::ocw_absent_for(now) else {
+            return weight;
+        };
+        let last_run = LastOcwRun::<T>::get();
+
+        log::error!(
+            target: LOG_TARGET,
+            "No offchain worker transaction for {:?} blocks; halting",
+            since
+        );
+        let reason = Self::internal_halt_reason(REASON_OCW_ABSENT);
+        // Cannot fail: internal reasons always fit.
+        let _ = Self::halt_production_internal(Some(reason), HaltReasonKind::OffchainWorkerAbsent);
+        Self::note_production_halted();
+        Self::deposit_event(Event::OffchainWorkerAbsent { last_run });
+        weight.saturating_add(Self::halt_weight())
+    }
+
+    /// Resume production once [`Self::auto_recovery_block`] is reached.
+    fn auto_recover_if_due(now: BlockNumberFor<T>) -> Weight {
+        match Self::auto_recovery_block() {
+            Some(recovery_block) if now >= recovery_block => {
+                log::info!(
+                    target: LOG_TARGET,
+                    "Auto-recovering from halt at block {:?}",
+                    now
+                );
+                Self::resume_production_internal();
+                Self::deposit_event(Event::ProductionResumed);
+                Self::deposit_event(Event::AutoRecovered);
+                T::DbWeight::get().reads_writes(4, 5)
+            }
+            _ => T::DbWeight::get().reads(4),
+        }
+    }
+
+    /// End a halt started by `sudo_simulate_halt` once its [`SimulatedHaltUntil`] is reached.
+    fn end_simulated_halt_if_due(now: BlockNumberFor<T>) -> Weight {
+        match SimulatedHaltUntil::<T>::get() {
+            Some(until) if now >= until => {
+                log::info!(target: LOG_TARGET, "Simulated halt ended at block {:?}", now);
+                Self::resume_production_internal();
+                Self::deposit_event(Event::SimulatedHaltEnded);
+                T::DbWeight::get().reads_writes(1, 6)
+            }
+            _ => T::DbWeight::get().reads(1),
+        }
+    }
+
+    /// Emit [`Event::EnforcementHeartbeat`] on every [`Config::HeartbeatInterval`]th block.
+    fn emit_heartbeat_if_due(now: BlockNumberFor<T>) -> Weight {
+        let interval = T::HeartbeatInterval::get();
+        if interval.is_zero() || !(now % interval).is_zero() {
+            return Weight::zero();
+        }
+
+        Self::deposit_event(Event::EnforcementHeartbeat {
+            halted: Self::is_halted(),
+            last_check_age: LastCheckReportedAt::<T>::get().map(|last| now.saturating_sub(last)),
+            consecutive_failures: ConsecutiveCheckFailures::<T>::get(),
+        });
+        T::DbWeight::get().reads(3)
+    }
+
+    /// Dispatch the [`QueuedCalls`] as root once production is no longer halted.
+    fn dispatch_queued_calls() -> Weight {
+        let mut weight = T::DbWeight::get().reads(2);
+        if Self::is_halted() || QueuedCalls::<T>::decode_len().unwrap_or(0) == 0 {
+            return weight;
+        }
+
+        for (index, call) in QueuedCalls::<T>::take().into_iter().enumerate() {
+            let info = call.get_dispatch_info();
+            let result = call.dispatch(frame_system::RawOrigin::Root.into());
+            weight = weight.saturating_add(frame_support::dispatch::extract_actual_weight(
+                &result, &info,
+            ));
+            Self::deposit_event(Event::QueuedCallDispatched {
+                index: index as u32,
+                result: result.map(|_| ()).map_err(|e| e.error),
+            });
+        }
+        weight.saturating_add(T::DbWeight::get().writes(1))
+    }
+
+    /// The interval between offchain license checks actually in effect, in milliseconds.
+    ///
+    /// This is the [`CheckIntervalOverride`] if set, else [`Config::CheckEverySlots`] slots if
+    /// set, else [`Config::LicenseCheckInterval`], raised to the [`ServerMinCheckInterval`] when
+    /// the server asks for less frequent checks.
+    pub fn effective_check_interval() -> u64 {
+        let configured = CheckIntervalOverride::<T>::get()
+            .or_else(Self::slot_check_interval)
+            .unwrap_or_else(T::LicenseCheckInterval::get);
+        configured.max(ServerMinCheckInterval::<T>::get().unwrap_or(0))
+    }
+
+    /// [`Config::CheckEverySlots`] slots in milliseconds, if set.
+    fn slot_check_interval() -> Option<u64> {
+        T::CheckEverySlots::get().map(|slots| {
+            (slots as u64).saturating_mul(Self::slot_duration().saturated_into::<u64>())
+        })
+    }
+
+    /// Whether the chain uses per-authority license keys, see [`AuthorityLicenseKeys`].
+    pub fn has_authority_license_keys() -> bool {
+        AuthorityLicenseKeys::<T>::iter_keys().next().is_some()
+    }
+
+    /// The first current authority in the local keystore that has its own license key.
+    fn local_authority_license_key() -> Option<(T::AuthorityId, BoundedVec<u8, ConstU32<128>>)> {
+        let local_keys = T::AuthorityId::all();
+        Authorities::<T>::get()
+            .into_iter()
+            .filter(|authority| local_keys.contains(authority))
+            .find_map(|authority| {
+                AuthorityLicenseKeys::<T>::get(&authority).map(|key| (authority, key))
+            })
+    }
+
+    /// Offchain worker: whether a local authority key is one of the [`CanaryAuthorities`].
+    fn is_local_canary() -> bool {
+        let canaries = CanaryAuthorities::<T>::get();
+        !canaries.is_empty()
+            && T::AuthorityId::all()
+                .iter()
+                .any(|key| canaries.contains(key))
+    }
+
+    /// The key the block author's license proof must be for: the author's own key on consortium
+    /// chains, else [`LicenseKey`].
+    fn expected_proof_license_key() -> Option<BoundedVec<u8, ConstU32<128>>> {
+        let author = Self::current_slot_from_digests().and_then(|slot| {
+            let authorities = Authorities::<T>::get();
+            match authorities.len() as u64 {
+                0 => None,
+                len => authorities.get((*slot % len) as usize).cloned(),
+            }
+        });
+        author
+            .and_then(|author| AuthorityLicenseKeys::<T>::get(&author))
+            .or_else(LicenseKey::<T>::get)
+    }
+
+    /// Ensure `report` is for an authority with its own key and newer than its last report,
+    /// and not from a future block.
+    fn check_authority_report_is_fresh(
+        report: &AuthorityLicenseReport<BlockNumberFor<T>, T::AuthorityId>,
+    ) -> DispatchResult {
+        ensure!(
+            AuthorityLicenseKeys::<T>::contains_key(&report.authority),
+            Error::<T>::NoAuthorityLicenseKey
+        );
+        let newer = AuthorityLicenseStatus::<T>::get(&report.authority)
+            .is_none_or(|(_, last)| report.block_number > last);
+        ensure!(
+            newer && report.block_number <= frame_system::Pallet::<T>::block_number(),
+            Error::<T>::StaleAuthorityLicenseReport
+        );
+        Ok(())
+    }
+
+    /// Ensure `renewal` comes from a current authority and extends the [`LicenseTerm`].
+    fn check_renewal_is_fresh(renewal: &LicenseRenewal<T::AuthorityId>) -> DispatchResult {
+        ensure!(
+            Authorities::<T>::get().contains(&renewal.authority),
+            Error::<T>::NotAnAuthority
+        );
+        let extends_term =
+            LicenseTerm::<T>::get().is_none_or(|(_, expires_at)| renewal.expires_at > expires_at);
+        ensure!(
+            extends_term && renewal.expires_at > renewal.issued_at,
+            Error::<T>::StaleLicenseRenewal
+        );
+        Ok(())
+    }
+
+    /// Whether the license should be renewed: the highest of [`Config::LicenseUsageThresholds`]
+    /// has been reached for the current [`LicenseTerm`].
+    pub fn is_renewal_due() -> bool {
+        let highest = T::LicenseUsageThresholds::get()
+            .into_iter()
+            .max()
+            .unwrap_or(0);
+        highest > 0
+            && LicenseTerm::<T>::exists()
+            && LicenseUsageThresholdReached::<T>::get() >= highest
+    }
+
+    /// Halt production once [`Config::LicenseQuorum`] of the current authorities holding their
+    /// own key last reported it invalid, and resume a quorum halt once they no longer do.
+    ///
+    /// Authorities that have not reported yet count as valid.
+    fn enforce_license_quorum() -> DispatchResult {
+        if Self::is_perpetually_licensed() {
+            return Ok(());
+        }
+        let mut holders = 0u32;
+        let mut invalid = 0u32;
+        for authority in Authorities::<T>::get().iter() {
+            if !AuthorityLicenseKeys::<T>::contains_key(authority) {
+                continue;
+            }
+            holders += 1;
+            if let Some((false, _)) = AuthorityLicenseStatus::<T>::get(authority) {
+                invalid += 1;
+            }
+        }
+
+        let quorum = T::LicenseQuorum::get().mul_ceil(holders).max(1);
+        let lost = holders > 0 && invalid >= quorum;
+        if lost && !Self::is_halted() {
+            log::error!(
+                target: LOG_TARGET,
+                "{} of {} authority licenses invalid; halting",
+                invalid,
+                holders
+            );
+            let reason = Self::internal_halt_reason(REASON_LICENSE_QUORUM);
+            Self::halt_production_internal(Some(reason), HaltReasonKind::LicenseQuorum)?;
+            HaltedByLicenseQuorum::<T>::put(true);
+            Self::note_production_halted();
+        } else if !lost && HaltedByLicenseQuorum::<T>::get() {
+            Self::resume_production_internal();
+            Self::deposit_event(Event::ProductionResumed);
+        }
+        Ok(())
+    }
+
+    /// License endpoint base URLs in the order the offchain worker tries them.
+    pub fn license_endpoints() -> Vec<String> {
+        let endpoints = LicenseEndpoints::<T>::get();
+        if endpoints.is_empty() {
+            return alloc::vec![DEFAULT_LICENSE_ENDPOINT.into()];
+        }
+
+        endpoints
+            .into_iter()
+            .filter_map(|(_, url)| String::from_utf8(url.into_inner()).ok())
+            .collect()
+    }
+
+    /// The highest priority license endpoint, the one queried first unless
+    /// [`Config::EndpointSelection`] spreads the load.
+    pub fn license_endpoint() -> Option<Vec<u8>> {
+        Self::license_endpoints()
+            .into_iter()
+            .next()
+            .map(String::into_bytes)
+    }
+
+    /// The first four bytes of the `blake2_256` of [`LicenseKey`], enough to tell keys apart
+    /// without revealing them.
+    pub fn license_key_fingerprint() -> Option<[u8; 4]> {
+        LicenseKey::<T>::get().map(|key| {
+            let hash = LicenseProof::key_hash(&key);
+            [hash[0], hash[1], hash[2], hash[3]]
+        })
+    }
+
+    /// The length of [`LicenseKey`] in bytes, if set.
+    pub fn license_key_len() -> Option<u32> {
+        LicenseKey::<T>::decode_len().map(|len| len as u32)
+    }
+
+    /// This node's instance ID, see [`INSTANCE_ID_STORAGE_KEY`].
+    fn instance_id() -> [u8; 16] {
+        let storage_instance_id = StorageValueRef::persistent(INSTANCE_ID_STORAGE_KEY);
+        let mut fresh = [0u8; 16];
+        fresh.copy_from_slice(&sp_io::offchain::random_seed()[..16]);
+
+        // Missing or unreadable: start over with a fresh one.
+        let stored = storage_instance_id.mutate(|stored: Result<Option<[u8; 16]>, _>| {
+            Ok::<_, ()>(stored.ok().flatten().unwrap_or(fresh))
+        });
+        match stored {
+            Ok(instance_id) => instance_id,
+            // Another worker stored one at the same time; use theirs.
+            Err(_) => storage_instance_id.get().ok().flatten().unwrap_or(fresh),
+        }
+    }
+
+    /// Index into [`Self::license_endpoints`] of the endpoint the next check queries first,
+    /// according to [`Config::EndpointSelection`].
+    fn first_endpoint_index(count: usize, seed: &[u8; 32]) -> usize {
+        if count <= 1 {
+            return 0;
+        }
+
+        match T::EndpointSelection::get() {
+            EndpointSelection::Failover => 0,
+            EndpointSelection::RoundRobin => {
+                let storage_cursor = StorageValueRef::persistent(b"licensed_aura::endpoint_cursor");
+                let cursor = storage_cursor.get::<u32>().unwrap_or(None).unwrap_or(0);
+                storage_cursor.set(&cursor.wrapping_add(1));
+                cursor as usize % count
+            }
+            EndpointSelection::Random => {
+                u32::from_le_bytes([seed[16], seed[17], seed[18], seed[19]]) as usize % count
+            }
+        }
+    }
+
+    /// The stored halt reason decoded as UTF-8, or `"<invalid utf8>"` if it is not valid UTF-8.
+    pub fn halt_reason_string() -> Option<String> {
+        HaltReason::<T>::get().map(|reason| match alloc::str::from_utf8(&reason) {
+            Ok(reason) => reason.into(),
+            Err(_) => "<invalid utf8>".into(),
+        })
+    }
+
+    /// Offchain worker: check license and submit halt/resume unsigned tx as needed.
+    pub(crate) fn check_license_and_halt_if_needed<C: LicenseHttpClient>(
+        client: &mut C,
+    ) -> Result<(), OffchainError> {
+        use frame_system::offchain::SubmitTransaction;
+        use sp_runtime::offchain::{
+            storage::StorageValueRef,
+            storage_lock::{StorageLock, Time},
+            Duration,
+        };
+
+        // 0) Nothing to enforce until the genesis authorities are known, which may not be the
+        //    case yet during early startup or warp sync, nor ever again under a perpetual license.
+        if Self::is_perpetually_licensed() {
+            return Ok(());
+        }
+        if Self::authorities_len() == 0 {
+            log::debug!(
+                target: LOG_TARGET,
+                "Authorities not yet set; skipping license check"
+            );
+            return Ok(());
+        }
+
+        let mut lock = StorageLock::<Time>::with_deadline(
+            CHECK_LOCK_STORAGE_KEY,
+            Duration::from_millis(CHECK_LOCK_DEADLINE_MS),
+        );
+        let _guard = lock.try_lock().map_err(|_| OffchainError::LockBusy)?;
+
+        // Everything below shares one time budget, whatever it ends up requesting.
+        let client = &mut BudgetedClient::new(client, T::OffchainTimeBudget::get());
+
+        // A manual resume drops the submission backoff, once per resume.
+        let storage_reset_served =
+            StorageValueRef::persistent(b"licensed_aura::backoff_reset_served");
+        if let Some(reset_at) = ResetBackoff::<T>::get() {
+            if storage_reset_served
+                .get::<BlockNumberFor<T>>()
+                .unwrap_or(None)
+                != Some(reset_at)
+            {
+                log::info!(target: LOG_TARGET, "Production resumed manually; resetting backoff");
+                StorageValueRef::persistent(SUBMIT_BACKOFF_STORAGE_KEY).clear();
+                storage_reset_served.set(&reset_at);
+            }
+        }
+
+        // 1) Rate-limit checks to the effective check interval, or to one per session
+        let storage_last_check = StorageValueRef::persistent(b"licensed_aura::last_check");
+        let storage_checked_session = StorageValueRef::persistent(CHECKED_SESSION_STORAGE_KEY);
+        let session = CurrentSession::<T>::get();
+        let now = sp_io::offchain::timestamp();
+        let mut last_check = storage_last_check.get::<u64>().unwrap_or(None).unwrap_or(0);
+        // A check another node reported counts too, unless each authority checks its own key.
+        if T::SharedCheckResults::get() && !Self::has_authority_license_keys() {
+            if let Some((reported_at, _)) = LastCheckReportedTime::<T>::get() {
+                last_check = last_check.max(reported_at);
+            }
+        }
+
+        // A status refresh or license key change requested on-chain skips the wait, once per
+        // request.
+        let storage_refresh_served = StorageValueRef::persistent(b"licensed_aura::refresh_served");
+        let refresh_requested = StatusRefreshRequestedAt::<T>::get()
+            .into_iter()
+            .chain(RecheckNow::<T>::get())
+            .max()
+            .filter(|requested_at| {
+                storage_refresh_served
+                    .get::<BlockNumberFor<T>>()
+                    .unwrap_or(None)
+                    .as_ref()
+                    != Some(requested_at)
+            });
+
+        // Canaries check on every block.
+        let canary = Self::is_local_canary();
+        let due = if T::CheckOncePerSession::get() {
+            storage_checked_session.get::<u32>().unwrap_or(None) != Some(session)
+        } else {
+            now.unix_millis().saturating_sub(last_check) >= Self::effective_check_interval()
+        };
+        if refresh_requested.is_none() && !canary && !due {
+            return Ok(());
+        }
+
+        // 2) Check if we have a pending action from a previous check
+        let storage_halt = StorageValueRef::persistent(b"licensed_aura::halt_requested");
+        let storage_resume = StorageValueRef::persistent(b"licensed_aura::resume_requested");
+        let storage_halt_reason = StorageValueRef::persistent(b"licensed_aura::halt_reason");
+
+        // If a previous check requested halting, try to submit the halt tx.
+        if let Some(true) = storage_halt.get::<bool>().unwrap_or(None) {
+            log::warn!(
+                target: LOG_TARGET,
+                "License invalid previously: submitting halt tx from OCW"
+            );
+
+            let reason = storage_halt_reason
+                .get::<Vec<u8>>()
+                .unwrap_or(None)
+                .unwrap_or_else(|| REASON_LICENSE_INVALID.to_vec());
+            let call: Call<T> = Call::offchain_worker_halt_production {
+                reason: Some(Self::internal_halt_reason(&reason)),
+            };
+
+            if Self::submit_with_backoff("halt", || {
+                SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
+            }) {
+                storage_halt.set(&false);
+            }
+            return Ok(());
+        }
+
+        // If a previous check requested resuming, try to submit the resume tx.
+        if let Some(true) = storage_resume.get::<bool>().unwrap_or(None) {
+            log::info!(
+                target: LOG_TARGET,
+                "License valid previously: submitting resume tx from OCW"
+            );
+
+            let call: Call<T> = Call::offchain_worker_resume_production {};
+
+            if Self::submit_with_backoff("resume", || {
+                SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
+            }) {
+                storage_resume.set(&false);
+            }
+            return Ok(());
+        }
+
+        // 3) Reuse a recent valid result unless a fresh check was forced on-chain.
+        let storage_valid_until = StorageValueRef::persistent(b"licensed_aura::valid_until");
+        if refresh_requested.is_some() && RecheckNow::<T>::exists() {
+            // Whatever was cached or last checked was for the previous key.
+            storage_valid_until.clear();
+            storage_last_check.clear();
+            storage_checked_session.clear();
+        }
+        if canary {
+            log::debug!(target: LOG_TARGET, "Canary authority; ignoring cached result");
+        } else if BypassCacheOnce::<T>::get() || refresh_requested.is_some() {
+            log::info!(
+                target: LOG_TARGET,
+                "Fresh license check requested; ignoring cached result"
+            );
+        } else if let Some(valid_until) = storage_valid_until.get::<u64>().unwrap_or(None) {
+            if now.unix_millis() < valid_until && !Self::is_halted() {
+                log::debug!(target: LOG_TARGET, "Using cached license validation result");
+                return Ok(());
+            }
+        }
+
+        // 4) Read license key from on-chain storage. On consortium chains each authority checks
+        //    only its own key, and nodes without one have nothing to check.
+        let local_authority = Self::local_authority_license_key();
+        let license_key_bytes = match local_authority {
+            Some((_, ref key)) => key.clone(),
+            None if Self::has_authority_license_keys() => {
+                log::debug!(
+                    target: LOG_TARGET,
+                    "No local authority holds a license key; skipping license check"
+                );
+                return Ok(());
+            }
+            None => LicenseKey::<T>::get().ok_or(OffchainError::KeyNotSet)?,
+        };
+        let Ok(license_key) = alloc::str::from_utf8(&license_key_bytes) else {
+            // Tell operators on-chain as well, as the check cannot run until the key is fixed.
+            if !LicenseKeyUnusableReported::<T>::get() {
+                let call: Call<T> = Call::offchain_worker_report_unusable_key {};
+                Self::submit_with_backoff("unusable license key report", || {
+                    SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
+                });
+            }
+            return Err(OffchainError::BadKeyUtf8);
+        };
+
+        let secret = sp_io::offchain::local_storage_get(
+            sp_runtime::offchain::StorageKind::PERSISTENT,
+            HMAC_SECRET_STORAGE_KEY,
+        );
+        let seed = sp_io::offchain::random_seed();
+        let nonce = http_client::to_hex(&seed[..16]);
+        // Lets the server bind the license to this chain, so it cannot be reused on a fork.
+        let genesis_hash = http_client::to_hex(
+            frame_system::Pallet::<T>::block_hash(BlockNumberFor::<T>::zero()).as_ref(),
+        );
+        let instance_id = http_client::to_hex(&Self::instance_id());
+        let auth_token =
+            AuthToken::<T>::get().and_then(|token| match String::from_utf8(token.into_inner()) {
+                Ok(token) => Some(token),
+                Err(_) => {
+                    log::error!(target: LOG_TARGET, "Invalid auth token UTF8; not sending it");
+                    None
+                }
+            });
+        let mut extra_query = if T::ReportNodeVersion::get() {
+            let version = <T as frame_system::Config>::Version::get();
+            alloc::format!(
+                "&spec_version={}&impl_version={}",
+                version.spec_version,
+                version.impl_version
+            )
+        } else {
+            String::new()
+        };
+        if T::ReportBlockNumber::get() {
+            let block_number = frame_system::Pallet::<T>::block_number().saturated_into::<u64>();
+            extra_query.push_str(&alloc::format!("&block={}", block_number));
+        }
+
+        let mut endpoints = Self::license_endpoints();
+        let first = Self::first_endpoint_index(endpoints.len(), &seed);
+        endpoints.rotate_left(first);
+
+        let requests_for = |license_key: &str, nonce: &str| -> Vec<LicenseRequest> {
+            endpoints
+                .iter()
+                .filter(|endpoint| {
+                    let local = EndpointScheme::of(endpoint) == Some(EndpointScheme::Unix);
+                    if local && secret.is_none() {
+                        log::warn!(
+                            target: LOG_TARGET,
+                            "Skipping license endpoint {}: local agents need an HMAC secret",
+                            endpoint
+                        );
+                    }
+                    !local || secret.is_some()
+                })
+                .map(|endpoint| {
+                    let url = T::RequestUrlTransformer::transform(
+                        &T::LicenseUrlBuilder::build_url(
+                            http_client::route_endpoint(endpoint),
+                            license_key.as_bytes(),
+                            frame_system::Pallet::<T>::block_number(),
+                        ),
+                        license_key,
+                    );
+                    let separator = if url.contains('?') { '&' } else { '?' };
+                    let api_url = alloc::format!(
+                        "{}{}genesis={}&instance={}{}",
+                        url,
+                        separator,
+                        genesis_hash,
+                        instance_id,
+                        extra_query
+                    );
+                    let mut request = LicenseRequest::new(api_url);
+                    if let Some(ref auth_token) = auth_token {
+                        request.authorize(auth_token);
+                    }
+                    if let Some(ref secret) = secret {
+                        request.sign(secret, license_key.as_bytes(), nonce, now.unix_millis());
+                    }
+                    request
+                })
+                .collect()
+        };
+
+        let policy = RetryPolicy {
+            max_attempts: T::LicenseRequestAttempts::get(),
+            initial_backoff_ms: T::LicenseRetryBackoff::get(),
+        };
+        let offline = Self::offline_key_verdict(&license_key_bytes);
+        let response = match offline {
+            Some(_) => {
+                log::debug!(target: LOG_TARGET, "Checked the license key offline");
+                None
+            }
+            None => Self::fetch_license(client, &requests_for(license_key, &nonce), policy)?,
+        };
+        let unreachable = offline.is_none() && response.is_none();
+
+        // Only update last_check after we've heard back, or given up in fail-closed mode.
+        storage_last_check.set(&now.unix_millis());
+        storage_checked_session.set(&session);
+        if let Some(requested_at) = refresh_requested {
+            storage_refresh_served.set(&requested_at);
+        }
+
+        let mut report = match offline {
+            Some(valid) => LicenseCheckReport {
+                valid,
+                ..LicenseCheckReport::invalid()
+            },
+            None => Self::report_from_response(response, secret.as_deref(), &nonce)?,
+        };
+
+        // A chain-wide key the server turned down gets a second chance with the backup key.
+        let backup_key = BackupLicenseKey::<T>::get().filter(|_| {
+            !report.valid && !unreachable && offline.is_none() && local_authority.is_none()
+        });
+        if let Some(backup_key) = backup_key {
+            match alloc::str::from_utf8(&backup_key) {
+                Ok(backup_key) => {
+                    log::warn!(target: LOG_TARGET, "License key invalid; checking the backup key");
+                    let backup_nonce = http_client::to_hex(&seed[16..]);
+                    let requests = requests_for(backup_key, &backup_nonce);
+                    let backup_report = match fetch_with_failover(client, &requests, policy) {
+                        Err(HttpFailure::BudgetExhausted) => {
+                            // Without the backup's answer the check is not over yet.
+                            storage_last_check.clear();
+                            storage_checked_session.clear();
+                            return Err(OffchainError::TimeBudgetExhausted);
+                        }
+                        outcome => outcome.ok().and_then(|response| {
+                            Self::report_from_response(
+                                Some(response),
+                                secret.as_deref(),
+                                &backup_nonce,
+                            )
+                            .ok()
+                        }),
+                    };
+                    match backup_report {
+                        Some(backup_report) if backup_report.valid => report = backup_report,
+                        _ => log::error!(target: LOG_TARGET, "Backup license key invalid too"),
+                    }
+                }
+                Err(_) => log::error!(target: LOG_TARGET, "Invalid backup license key UTF8"),
+            }
+        }
+
+        // The activation time is judged by the local clock, so only when that clock is sane.
+        let not_yet_active = Self::offchain_clock_trusted(now.unix_millis())
+            && report.is_not_yet_active(now.unix_millis());
+        if not_yet_active {
+            log::warn!(
+                target: LOG_TARGET,
+                "License not active until {:?}",
+                report.not_before
+            );
+            report.valid = false;
+        }
+
+        // A past expiry takes precedence over the `valid` flag, so that a server bug cannot keep
+        // an expired chain running. Judged by the local clock too, so only when that is sane.
+        let expired =
+            Self::offchain_clock_trusted(now.unix_millis()) && report.is_expired(now.unix_millis());
+        if expired && report.valid {
+            log::warn!(
+                target: LOG_TARGET,
+                "License server reported a valid license that expired at {:?}; treating it as \
+                 invalid",
+                report.expires_at
+            );
+            report.valid = false;
+        }
+
+        let is_valid = report.valid;
+        match local_authority {
+            Some((ref authority, _)) => Self::submit_authority_report(authority, is_valid)?,
+            None => Self::submit_check_report(report),
+        }
+
+        // Keep the proof handed to the license proof inherent in step with this check.
+        StorageValueRef::persistent(LICENSE_PROOF_STORAGE_KEY).set(&LicenseProof {
+            key_hash: LicenseProof::key_hash(&license_key_bytes),
+            valid: is_valid,
+            checked_at: now.unix_millis(),
+        });
+
+        if is_valid {
+            storage_valid_until.set(&now.unix_millis().saturating_add(T::LicenseCacheTtl::get()));
+        } else {
+            storage_valid_until.clear();
+        }
+
+        if is_valid && offline.is_none() && local_authority.is_none() && Self::is_renewal_due() {
+            if let Some(url) = T::RenewalUrl::get() {
+                let mut request = LicenseRequest::new(String::from(url));
+                if let Some(ref auth_token) = auth_token {
+                    request.authorize(auth_token);
+                }
+                let nonce = http_client::to_hex(&sp_io::offchain::random_seed()[..16]);
+                if let Some(ref secret) = secret {
+                    request.sign(secret, license_key.as_bytes(), &nonce, now.unix_millis());
+                }
+                if let Err(e) = Self::renew_license(
+                    client,
+                    &request,
+                    license_key,
+                    secret.as_deref(),
+                    &nonce,
+                    now.unix_millis(),
+                ) {
+                    log::warn!(target: LOG_TARGET, "License renewal failed: {}", e.message());
+                }
+            }
+        }
+
+        // 5) Determine action based on license validity and current halt state. With
+        //    per-authority keys the chain decides from the quorum of reports instead.
+        if local_authority.is_some() {
+            return Ok(());
+        }
+        let currently_halted = Self::is_halted();
+
+        // The first checks after a node start only log failures, see
+        // [`Config::OffchainWarmupChecks`].
+        let storage_warmup = StorageValueRef::persistent(WARMUP_CHECKS_STORAGE_KEY);
+        let warmup_checks = storage_warmup.get::<u32>().unwrap_or(None).unwrap_or(0);
+        let warming_up = warmup_checks < T::OffchainWarmupChecks::get();
+        if warming_up {
+            storage_warmup.set(&warmup_checks.saturating_add(1));
+        }
+
+        if !is_valid && !currently_halted && warming_up {
+            // License is invalid but the node just started -> only log
+            log::warn!(
+                target: LOG_TARGET,
+                "License validation failed during warm-up check {} of {}; not requesting halt",
+                warmup_checks + 1,
+                T::OffchainWarmupChecks::get()
+            );
+        } else if !is_valid && !currently_halted {
+            // License is invalid and we're not halted yet -> request halt
+            log::error!(
+                target: LOG_TARGET,
+                "License validation failed; will request halt via unsigned tx"
+            );
+            let reason = if not_yet_active {
+                REASON_LICENSE_NOT_YET_ACTIVE
+            } else if expired {
+                REASON_LICENSE_EXPIRED
+            } else if unreachable {
+                REASON_LICENSE_UNREACHABLE
+            } else {
+                REASON_LICENSE_INVALID
+            };
+            storage_halt_reason.set(&reason.to_vec());
+            storage_halt.set(&true);
+        } else if is_valid && currently_halted {
+            // License is valid and we're currently halted -> request resume
+            log::info!(
+                target: LOG_TARGET,
+                "License validation successful and chain is halted; will request resume via unsigned tx"
+            );
+            storage_resume.set(&true);
+        } else if is_valid && !currently_halted {
+            // License is valid and we're not halted -> all good
+            log::info!(target: LOG_TARGET, "License validation successful");
+        } else {
+            // License is invalid and we're already halted -> no action needed
+            log::warn!(target: LOG_TARGET, "License still invalid, chain remains halted");
+        }
+
+        Ok(())
+    }
+
+    /// Offchain worker: parse a license response with the configured verdict path and
+    /// [`ResponseFieldNames`].
+    fn parse_license_response(body: &[u8]) -> Result<LicenseResponse, ParseError> {
+        let names: Vec<(ResponseField, String)> = ResponseFieldNames::<T>::iter()
+            .filter_map(|(field, name)| Some((field, String::from_utf8(name.into_inner()).ok()?)))
+            .collect();
+        let renamed: Vec<(ResponseField, &str)> = names
+            .iter()
+            .map(|(field, name)| (*field, name.as_str()))
+            .collect();
+        LicenseResponse::parse_with_field_names(
+            body,
+            &renamed,
+            T::ValidityFieldPath::get(),
+            T::AlternativeValidField::get(),
+        )
+    }
+
+    /// Offchain worker: the check report for the body of a `200` license response.
+    pub(crate) fn report_from_body(
+        body: &[u8],
+        secret: Option<&[u8]>,
+        nonce: &str,
+    ) -> Result<LicenseCheckReport, OffchainError> {
+        if body.iter().all(u8::is_ascii_whitespace) {
+            let valid = T::EmptyBodyMeansValid::get()
+                && Self::verify_cert_fingerprint(&LicenseResponse::default(), secret, nonce);
+            return Ok(LicenseCheckReport {
+                valid,
+                ..LicenseCheckReport::invalid()
+            });
+        }
+        let response =
+            Self::parse_license_response(body).map_err(|_| OffchainError::BadResponseUtf8)?;
+        if !Self::verify_cert_fingerprint(&response, secret, nonce) {
+            log::error!(
+                target: LOG_TARGET,
+                "License response does not attest the pinned certificate fingerprint"
+            );
+            return Ok(LicenseCheckReport::invalid());
+        }
+
+        let valid = match response.valid {
+            Some(valid) => valid,
+            None => match T::MissingValidField::get() {
+                MissingValidFieldPolicy::Invalid => false,
+                MissingValidFieldPolicy::Unparseable => {
+                    return Err(OffchainError::MissingValidField)
+                }
+                MissingValidFieldPolicy::Valid => true,
+            },
+        };
+
+        Ok(LicenseCheckReport {
+            valid,
+            issued_at: response.issued_at,
+            expires_at: response.expires_at,
+            min_check_interval: response.min_check_interval,
+            not_before: response.not_before,
+            features: response.features,
+        })
+    }
+
+    /// Offchain worker: the license server's response to the first of `requests` that answers,
+    /// see [`fetch_with_failover`].
+    ///
+    /// `None` if the server could not be reached and [`Config::FailClosed`] counts that as an
+    /// invalid license.
+    fn fetch_license<C: LicenseHttpClient>(
+        client: &mut C,
+        requests: &[LicenseRequest],
+        policy: RetryPolicy,
+    ) -> Result<Option<HttpResponse>, OffchainError> {
+        match fetch_with_failover(client, requests, policy) {
+            Ok(response) => Ok(Some(response)),
+            // Running out of time says nothing about the license: try again later.
+            Err(HttpFailure::BudgetExhausted) => Err(OffchainError::TimeBudgetExhausted),
+            Err(e) => {
+                let error = OffchainError::from(e);
+                if !T::FailClosed::get() {
+                    return Err(error);
+                }
+                log::error!(
+                    target: LOG_TARGET,
+                    "License server unreachable ({}); failing closed",
+                    error.message()
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// Whether `license_key` is among the [`Config::OfflineKeyHashes`], or `None` if there are
+    /// none and the license server decides.
+    pub fn offline_key_verdict(license_key: &[u8]) -> Option<bool> {
+        let hashes = T::OfflineKeyHashes::get();
+        if hashes.is_empty() {
+            return None;
+        }
+        Some(hashes.contains(&LicenseProof::key_hash(license_key)))
+    }
+
+    /// The outcome of a license check from the server's `response`, which is `None` if the server
+    /// could not be reached.
+    ///
+    /// Anything but a readable `200` counts as an invalid license, except a response without a
+    /// verdict outside [`Config::FailClosed`], which is [`OffchainError::MissingValidField`].
+    fn report_from_response(
+        response: Option<HttpResponse>,
+        secret: Option<&[u8]>,
+        nonce: &str,
+    ) -> Result<LicenseCheckReport, OffchainError> {
+        // Undo any content encoding; an unreadable body counts as an invalid license.
+        let response =
+            response.and_then(
+                |response| match response.decoded_body(T::MaxResponseBytes::get()) {
+                    Ok(body) => Some(HttpResponse {
+                        body,
+                        content_encoding: None,
+                        ..response
+                    }),
+                    Err(e) => {
+                        log::error!(target: LOG_TARGET, "Unreadable license response: {:?}", e);
+                        None
+                    }
+                },
+            );
 
-                    // Use the call itself as the unique identifier to prevent duplicates.
-                    ValidTransaction::with_tag_prefix("AuraOCW")
-                        .priority(u64::MAX)
-                        .and_provides(call)
-                        .longevity(1)
-                        .propagate(false)
-                        .build()
+        let report = match response {
+            Some(response) if response.code == 200 => {
+                match Self::report_from_body(&response.body, secret, nonce) {
+                    Ok(report) => report,
+                    // Without a verdict the answer is as good as none.
+                    Err(e @ OffchainError::MissingValidField) if !T::FailClosed::get() => {
+                        return Err(e)
+                    }
+                    Err(e) => {
+                        // An unreadable response counts as an invalid license, whatever
+                        // `FailClosed` says.
+                        log::error!(target: LOG_TARGET, "{}", e.message());
+                        LicenseCheckReport::invalid()
+                    }
                 }
-                _ => InvalidTransaction::Call.into(),
             }
-        }
+            Some(response) => {
+                log::error!(
+                    target: LOG_TARGET,
+                    "License check failed with HTTP {:?}",
+                    response.code
+                );
+                LicenseCheckReport::invalid()
+            }
+            None => LicenseCheckReport::invalid(),
+        };
+        Ok(report)
     }
-}
 
-impl<T: Config> Pallet<T> {
-    /// Internal function to halt transaction execution.
-    fn halt_production_internal(reason: Option<Vec<u8>>) -> DispatchResult {
-        HaltProduction::<T>::put(true);
+    /// Check a license response against the [`PinnedCertFingerprint`], if one is pinned.
+    ///
+    /// The response must report the pinned fingerprint, hex-encoded, as `cert_fingerprint`. With
+    /// an HMAC secret configured it must also carry `cert_fingerprint_sig`, the hex-encoded
+    /// `HMAC-SHA256(secret, nonce || cert_fingerprint)` for the request's nonce, so that an
+    /// intermediary cannot simply echo the expected fingerprint. Without a secret the response
+    /// is rejected under [`Config::RequireSignedCertFingerprint`].
+    pub(crate) fn verify_cert_fingerprint(
+        response: &LicenseResponse,
+        secret: Option<&[u8]>,
+        nonce: &str,
+    ) -> bool {
+        let Some(pinned) = PinnedCertFingerprint::<T>::get() else {
+            return true;
+        };
 
-        if let Some(r) = reason {
-            let bounded_reason = BoundedVec::<u8, ConstU32<256>>::try_from(r)
-                .map_err(|_| Error::<T>::ReasonTooLong)?;
-            HaltReason::<T>::put(bounded_reason);
+        let Some(reported) = &response.cert_fingerprint else {
+            return false;
+        };
+        if !reported.eq_ignore_ascii_case(http_client::to_hex(&pinned).as_bytes()) {
+            return false;
         }
 
-        log::warn!(target: LOG_TARGET, "HaltProduction set to true");
-        Ok(())
+        match secret {
+            Some(secret) => {
+                let mut message = Vec::with_capacity(nonce.len() + reported.len());
+                message.extend_from_slice(nonce.as_bytes());
+                message.extend_from_slice(reported);
+                let expected = http_client::to_hex(&http_client::hmac_sha256(secret, &message));
+                response
+                    .cert_fingerprint_sig
+                    .as_ref()
+                    .is_some_and(|sig| sig.eq_ignore_ascii_case(expected.as_bytes()))
+            }
+            None if T::RequireSignedCertFingerprint::get() => {
+                log::error!(
+                    target: LOG_TARGET,
+                    "Pinned certificate fingerprint cannot be verified without an HMAC secret"
+                );
+                false
+            }
+            None => true,
+        }
     }
 
-    /// Internal function to resume transaction execution.
-    fn resume_production_internal() {
-        HaltProduction::<T>::put(false);
-        HaltReason::<T>::kill();
-        log::info!(target: LOG_TARGET, "HaltProduction set to false");
+    /// Offchain worker: submit the outcome of a license check as an unsigned transaction.
+    fn submit_check_report(report: LicenseCheckReport) {
+        use frame_system::offchain::SubmitTransaction;
+
+        let call: Call<T> = Call::offchain_worker_report_check { report };
+        Self::submit_with_backoff("license check report", || {
+            SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
+        });
     }
 
-    /// Public helper: is the chain currently halted?
-    pub fn is_halted() -> bool {
-        HaltProduction::<T>::get()
+    /// Offchain worker: whether the offchain clock reading `now` is within
+    /// [`Config::MaxClockDrift`] of the on-chain timestamp.
+    ///
+    /// Before the first timestamp is set there is nothing to compare with, so the clock is
+    /// trusted.
+    pub(crate) fn offchain_clock_trusted(now: u64) -> bool {
+        let on_chain = pallet_timestamp::Pallet::<T>::get().saturated_into::<u64>();
+        if on_chain == 0 {
+            return true;
+        }
+
+        let drift = now.abs_diff(on_chain);
+        if drift > T::MaxClockDrift::get() {
+            log::warn!(
+                target: LOG_TARGET,
+                "Offchain clock is {} ms off the on-chain timestamp; ignoring it for license timing",
+                drift
+            );
+            return false;
+        }
+        true
     }
 
-    /// Offchain worker: check license and submit halt/resume unsigned tx as needed.
-    fn check_license_and_halt_if_needed() -> Result<(), &'static str> {
-        use sp_runtime::offchain::{http, storage::StorageValueRef, Duration};
+    /// Offchain worker: sign and submit `authority`'s check of its own license key.
+    fn submit_authority_report(
+        authority: &T::AuthorityId,
+        valid: bool,
+    ) -> Result<(), OffchainError> {
+        use frame_system::offchain::SubmitTransaction;
 
-        // 1) Rate-limit checks: once every 30s
-        let storage_last_check = StorageValueRef::persistent(b"licensed_aura::last_check");
-        let now = sp_io::offchain::timestamp();
-        let last_check = storage_last_check.get::<u64>().unwrap_or(None).unwrap_or(0);
+        let report = AuthorityLicenseReport {
+            authority: authority.clone(),
+            valid,
+            block_number: frame_system::Pallet::<T>::block_number(),
+        };
+        let signature = report
+            .using_encoded(|payload| authority.sign(&payload))
+            .ok_or(OffchainError::SigningFailed)?;
+
+        let call: Call<T> = Call::offchain_worker_report_authority_check { report, signature };
+        Self::submit_with_backoff("authority license report", || {
+            SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
+        });
+        Ok(())
+    }
+
+    /// Offchain worker: `POST` `license_key` to [`Config::RenewalUrl`] and submit the renewal
+    /// the server answers with, signed by the first local authority key.
+    ///
+    /// The renewal replaces the chain's license, so the response must say `"valid":true` and
+    /// pass [`Pallet::verify_cert_fingerprint`] for `secret` and the request's `nonce`.
+    pub(crate) fn renew_license<C: LicenseHttpClient>(
+        client: &mut C,
+        request: &LicenseRequest,
+        license_key: &str,
+        secret: Option<&[u8]>,
+        nonce: &str,
+        now: u64,
+    ) -> Result<(), OffchainError> {
+        use frame_system::offchain::SubmitTransaction;
 
-        if now.unix_millis().saturating_sub(last_check) < 30_000 {
+        let local_keys = T::AuthorityId::all();
+        let Some(authority) = Authorities::<T>::get()
+            .into_iter()
+            .find(|authority| local_keys.contains(authority))
+        else {
+            log::debug!(target: LOG_TARGET, "No local authority key; not renewing the license");
             return Ok(());
+        };
+
+        // The key is sent as a JSON string; rather than escaping it, refuse keys needing it.
+        if license_key
+            .chars()
+            .any(|c| c == '"' || c == '\\' || c.is_control())
+        {
+            return Err(OffchainError::UnsafeRenewalKey);
+        }
+        // Unlike a check, a renewal may change state on the server, so it is not retried.
+        let body = alloc::format!("{{\"key\":\"{}\"}}", license_key);
+        let response = client.post(request, body.as_bytes())?;
+        if response.code != 200 {
+            return Err(OffchainError::RenewalRejected);
+        }
+        let body = response
+            .decoded_body(T::MaxResponseBytes::get())
+            .map_err(|_| OffchainError::BadRenewalResponse)?;
+        let response =
+            Self::parse_license_response(&body).map_err(|_| OffchainError::BadResponseUtf8)?;
+        if response.valid != Some(true) || !Self::verify_cert_fingerprint(&response, secret, nonce)
+        {
+            return Err(OffchainError::UnverifiedRenewal);
         }
 
-        // 2) Check if we have a pending action from a previous check
-        let storage_halt = StorageValueRef::persistent(b"licensed_aura::halt_requested");
-        let storage_resume = StorageValueRef::persistent(b"licensed_aura::resume_requested");
+        let (Some(key), Some(expires_at)) = (response.key, response.expires_at) else {
+            return Err(OffchainError::BadRenewalResponse);
+        };
+        let renewal = LicenseRenewal {
+            authority: authority.clone(),
+            license_key: key,
+            issued_at: response.issued_at.unwrap_or(now),
+            expires_at,
+        };
+        let signature = renewal
+            .using_encoded(|payload| authority.sign(&payload))
+            .ok_or(OffchainError::SigningFailed)?;
 
-        // If a previous check requested halting, try to submit the halt tx.
-        if let Some(true) = storage_halt.get::<bool>().unwrap_or(None) {
-            log::warn!(
-                target: LOG_TARGET,
-                "License invalid previously: submitting halt tx from OCW"
-            );
+        let call: Call<T> = Call::offchain_worker_report_renewal { renewal, signature };
+        Self::submit_with_backoff("license renewal", || {
+            SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
+        });
+        Ok(())
+    }
 
-            let call: Call<T> = Call::offchain_worker_halt_production {
-                reason: Some(b"License validation failed".to_vec()),
-            };
+    /// Offchain worker: run `submit` unless a submission failed within the last
+    /// [`Config::SubmissionRetryBackoff`] blocks. Returns whether the transaction was submitted.
+    pub(crate) fn submit_with_backoff(what: &str, submit: impl FnOnce() -> Result<(), ()>) -> bool {
+        use sp_runtime::offchain::storage::StorageValueRef;
 
-            use frame_system::offchain::SubmitTransaction;
-            if let Err(e) =
-                SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
-            {
-                log::error!(
+        let storage_backoff = StorageValueRef::persistent(SUBMIT_BACKOFF_STORAGE_KEY);
+        let now = frame_system::Pallet::<T>::block_number();
+
+        if let Some(until) = storage_backoff.get::<BlockNumberFor<T>>().unwrap_or(None) {
+            if now < until {
+                log::debug!(
                     target: LOG_TARGET,
-                    "Failed to submit halt unsigned tx: {:?}",
-                    e
+                    "Deferring {} tx submission until block {:?}",
+                    what,
+                    until
                 );
-            } else {
-                log::info!(target: LOG_TARGET, "Halt unsigned tx submitted");
-                storage_halt.set(&false);
+                return false;
             }
-            return Ok(());
         }
 
-        // If a previous check requested resuming, try to submit the resume tx.
-        if let Some(true) = storage_resume.get::<bool>().unwrap_or(None) {
-            log::info!(
-                target: LOG_TARGET,
-                "License valid previously: submitting resume tx from OCW"
-            );
-
-            let call: Call<T> = Call::offchain_worker_resume_production {};
-
-            use frame_system::offchain::SubmitTransaction;
-            if let Err(e) =
-                SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
-            {
+        match submit() {
+            Ok(()) => {
+                log::info!(target: LOG_TARGET, "Submitted {} unsigned tx", what);
+                storage_backoff.clear();
+                true
+            }
+            Err(()) => {
+                let until = now.saturating_add(T::SubmissionRetryBackoff::get());
                 log::error!(
                     target: LOG_TARGET,
-                    "Failed to submit resume unsigned tx: {:?}",
-                    e
+                    "Failed to submit {} unsigned tx; not retrying before block {:?}",
+                    what,
+                    until
                 );
-            } else {
-                log::info!(target: LOG_TARGET, "Resume unsigned tx submitted");
-                storage_resume.set(&false);
+                storage_backoff.set(&until);
+                false
             }
-            return Ok(());
         }
+    }
 
-        // 3) Read license key from on-chain storage
-        let license_key_bytes = LicenseKey::<T>::get().ok_or("License key not set")?;
-        let license_key =
-            alloc::str::from_utf8(&license_key_bytes).map_err(|_| "Invalid license key UTF8")?;
+    /// Emit [`Event::ProductionHalted`], unless the previous one is less than
+    /// [`Config::HaltEventDebounce`] old.
+    fn note_production_halted() {
+        let now = frame_system::Pallet::<T>::block_number();
+        let debounced = LastHaltEventAt::<T>::get()
+            .is_some_and(|last| now < last.saturating_add(T::HaltEventDebounce::get()));
+        if !debounced {
+            LastHaltEventAt::<T>::put(now);
+            Self::deposit_event(Event::ProductionHalted);
+        }
+    }
 
-        let api_url = alloc::format!("http://localhost:3000/license?key={}", license_key);
+    /// Emit [`Event::LicenseCheckFailed`], or only count the failure while the previous event is
+    /// less than [`Config::EventThrottleBlocks`] old.
+    fn note_check_failed() {
+        let now = frame_system::Pallet::<T>::block_number();
+        let throttled = LastCheckFailedEventAt::<T>::get()
+            .is_some_and(|last| now < last.saturating_add(T::EventThrottleBlocks::get()));
 
-        let deadline = now.add(Duration::from_millis(5_000));
-        let request = http::Request::get(&api_url);
+        if throttled {
+            SuppressedCheckFailedEvents::<T>::mutate(|n| *n = n.saturating_add(1));
+        } else {
+            LastCheckFailedEventAt::<T>::put(now);
+            let suppressed = SuppressedCheckFailedEvents::<T>::take();
+            Self::deposit_event(Event::LicenseCheckFailed { suppressed });
+        }
+    }
 
-        let pending = request
-            .deadline(deadline)
-            .send()
-            .map_err(|_| "send failed")?;
+    /// Record a check outcome and refresh [`EnforcementHealth`].
+    fn note_check_outcome(valid: bool) {
+        let old_score = Self::enforcement_health();
+        LastCheckReportedAt::<T>::put(frame_system::Pallet::<T>::block_number());
+        if valid {
+            ConsecutiveCheckFailures::<T>::kill();
+            if Self::is_halted() {
+                ConsecutiveSuccessesWhileHalted::<T>::mutate(|successes| {
+                    *successes = successes.saturating_add(1)
+                });
+            }
+        } else {
+            ConsecutiveCheckFailures::<T>::mutate(|failures| {
+                *failures = failures.saturating_add(1)
+            });
+            ConsecutiveSuccessesWhileHalted::<T>::kill();
+        }
 
-        let response = pending
-            .try_wait(deadline)
-            .map_err(|_| "wait failed")?
-            .map_err(|_| "http error")?;
+        CheckHistory::<T>::mutate(|history| push_evicting_oldest(history, valid));
 
-        // Only update last_check after we've heard back.
-        storage_last_check.set(&now.unix_millis());
+        let new_score = Self::enforcement_health();
+        EnforcementHealth::<T>::put(new_score);
 
-        let is_valid = if response.code == 200 {
-            let body = response.body().collect::<Vec<u8>>();
-            match alloc::str::from_utf8(&body) {
-                Ok(body_str) => Self::parse_license_response(body_str),
-                Err(_) => {
-                    log::error!(target: LOG_TARGET, "Invalid UTF8 in license response");
-                    false
-                }
+        for threshold in T::HealthScoreThresholds::get() {
+            if (old_score >= threshold) != (new_score >= threshold) {
+                Self::deposit_event(Event::EnforcementHealthThresholdCrossed {
+                    threshold,
+                    score: new_score,
+                });
             }
+        }
+    }
+
+    /// Extend the current licensed streak on a valid report, or end it on an invalid one.
+    ///
+    /// Valid reports while halted do not start a streak.
+    fn note_licensed_streak(valid: bool) {
+        if !valid {
+            Self::end_licensed_streak();
+            return;
+        }
+        if Self::is_halted() {
+            return;
+        }
+
+        let now = frame_system::Pallet::<T>::block_number();
+        let start = LicensedStreakStart::<T>::get().unwrap_or_else(|| {
+            LicensedStreakStart::<T>::put(now);
+            now
+        });
+        let current = now.saturating_sub(start);
+        CurrentLicensedStreak::<T>::put(current);
+        LongestLicensedStreak::<T>::mutate(|longest| *longest = (*longest).max(current));
+    }
+
+    /// End the current licensed streak. The longest streak is kept.
+    fn end_licensed_streak() {
+        LicensedStreakStart::<T>::kill();
+        CurrentLicensedStreak::<T>::kill();
+    }
+
+    /// The current and longest continuously licensed streaks, in blocks.
+    pub fn licensed_streaks() -> (BlockNumberFor<T>, BlockNumberFor<T>) {
+        (
+            CurrentLicensedStreak::<T>::get(),
+            LongestLicensedStreak::<T>::get(),
+        )
+    }
+
+    /// Score enforcement health from 0 (failing) to 100 (healthy).
+    ///
+    /// The base score is the percentage of valid checks in `history`. It is reduced by up to
+    /// half as the gap since the last report approaches `HealthStalenessBlocks`. A halted
+    /// chain has no grace left and scores 0.
+    pub fn compute_health_score(
+        history: &[bool],
+        blocks_since_last_check: u64,
+        halted: bool,
+    ) -> u8 {
+        if halted {
+            return 0;
+        }
+        if history.is_empty() {
+            return DefaultEnforcementHealth::get();
+        }
+
+        let valid = history.iter().filter(|v| **v).count() as u64;
+        let ratio = valid * 100 / history.len() as u64;
+
+        let stale_after = T::HealthStalenessBlocks::get().saturated_into::<u64>();
+        let penalty = if stale_after.is_zero() {
+            0
         } else {
-            log::error!(
-                target: LOG_TARGET,
-                "License check failed with HTTP {:?}",
-                response.code
-            );
-            false
+            (blocks_since_last_check.saturating_mul(50) / stale_after).min(50)
         };
 
-        // 4) Determine action based on license validity and current halt state
-        let currently_halted = Self::is_halted();
+        (ratio * (100 - penalty) / 100) as u8
+    }
 
-        if !is_valid && !currently_halted {
-            // License is invalid and we're not halted yet -> request halt
-            log::error!(
-                target: LOG_TARGET,
-                "License validation failed; will request halt via unsigned tx"
-            );
-            storage_halt.set(&true);
-        } else if is_valid && currently_halted {
-            // License is valid and we're currently halted -> request resume
+    /// The current enforcement health score, penalized for the blocks elapsed since
+    /// [`LastCheckReportedAt`] so that a chain no longer receiving reports loses health.
+    pub fn enforcement_health() -> u8 {
+        let now = frame_system::Pallet::<T>::block_number();
+        let blocks_since_last_check = LastCheckReportedAt::<T>::get()
+            .map(|last| now.saturating_sub(last).saturated_into::<u64>())
+            .unwrap_or(0);
+        Self::compute_health_score(
+            &CheckHistory::<T>::get(),
+            blocks_since_last_check,
+            Self::is_halted(),
+        )
+    }
+
+    /// Snapshot all license and halt state; see [`LicensedAuraDebug`].
+    pub fn debug_dump() -> LicensedAuraDebug<BlockNumberFor<T>> {
+        let history = CheckHistory::<T>::get();
+
+        LicensedAuraDebug {
+            halted: HaltProduction::<T>::get(),
+            halt_reason: HaltReason::<T>::get().map(Into::into),
+            halted_by_license_proof: HaltedByLicenseProof::<T>::get(),
+            license_key_fingerprint: LicenseKey::<T>::get().map(|key| LicenseProof::key_hash(&key)),
+            license_term: LicenseTerm::<T>::get(),
+            license_usage_threshold_reached: LicenseUsageThresholdReached::<T>::get(),
+            bypass_cache_once: BypassCacheOnce::<T>::get(),
+            current_slot: *CurrentSlot::<T>::get(),
+            authorities_count: Self::authorities_len() as u32,
+            recent_checks: history.len() as u32,
+            recent_valid_checks: history.iter().filter(|valid| **valid).count() as u32,
+            last_check_reported_at: LastCheckReportedAt::<T>::get(),
+            enforcement_health: Self::enforcement_health(),
+            halted_at_block: HaltedAtBlock::<T>::get(),
+            safe_mode: SafeMode::<T>::get(),
+            halted_by_license_quorum: HaltedByLicenseQuorum::<T>::get(),
+            simulated_halt_until: SimulatedHaltUntil::<T>::get(),
+            auto_recovery_paused: AutoRecoveryPaused::<T>::get(),
+            perpetually_licensed: PerpetuallyLicensed::<T>::get(),
+            consecutive_check_failures: ConsecutiveCheckFailures::<T>::get(),
+        }
+    }
+
+    /// The license enforcement policy in effect; see [`EnforcementConfig`].
+    pub fn enforcement_config() -> EnforcementConfig<BlockNumberFor<T>> {
+        EnforcementConfig {
+            license_endpoints: Self::license_endpoints()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            endpoint_selection: T::EndpointSelection::get(),
+            check_interval: Self::effective_check_interval(),
+            check_interval_override: CheckIntervalOverride::<T>::get(),
+            check_every_slots: T::CheckEverySlots::get(),
+            server_min_check_interval: ServerMinCheckInterval::<T>::get(),
+            license_cache_ttl: T::LicenseCacheTtl::get(),
+            offchain_warmup_checks: T::OffchainWarmupChecks::get(),
+            license_proof_max_age: T::LicenseProofMaxAge::get(),
+            max_clock_drift: T::MaxClockDrift::get(),
+            auto_recovery_delay: Self::auto_recovery_delay(),
+            fail_closed: T::FailClosed::get(),
+            enforce_license_inherent: T::EnforceLicenseInherent::get(),
+            halt_if_ocw_absent: T::HaltIfOcwAbsent::get(),
+            ocw_absence_threshold: T::OcwAbsenceThreshold::get(),
+            per_authority_keys: Self::has_authority_license_keys(),
+            license_quorum: T::LicenseQuorum::get(),
+            safe_mode: SafeMode::<T>::get(),
+        }
+    }
+
+    /// How many halts of each kind the [`HaltLog`] holds, by kind. Kinds without halts are left
+    /// out.
+    pub fn halt_reason_counts() -> Vec<(HaltReasonKind, u32)> {
+        let mut counts = alloc::collections::BTreeMap::<HaltReasonKind, u32>::new();
+        for (_, kind) in HaltLog::<T>::get() {
+            *counts.entry(kind).or_default() += 1;
+        }
+        counts.into_iter().collect()
+    }
+
+    /// Replace [`LicensedFeatures`] with the reported `features` once
+    /// [`Config::FeatureChangeConfirmations`] consecutive checks reported them.
+    ///
+    /// Only checks that report features count; one reporting any other set starts over. Names
+    /// longer than [`MAX_LICENSE_FEATURE_LEN`] are skipped. Beyond
+    /// [`Config::MaxLicensedFeatures`], the first features are kept and
+    /// [`Event::FeaturesTruncated`] is emitted when they are stored.
+    fn note_licensed_features(features: &[Vec<u8>]) {
+        let mut kept = BoundedVec::<LicenseFeature, T::MaxLicensedFeatures>::new();
+        let mut truncated = false;
+        for feature in features {
+            let Ok(feature) = LicenseFeature::try_from(feature.clone()) else {
+                log::warn!(target: LOG_TARGET, "Skipping over-long licensed feature name");
+                continue;
+            };
+            truncated |= kept.try_push(feature).is_err();
+        }
+
+        if kept == LicensedFeatures::<T>::get() {
+            PendingFeatures::<T>::kill();
+            return;
+        }
+        let confirmations = match PendingFeatures::<T>::get() {
+            Some((pending, confirmations)) if pending == kept => confirmations.saturating_add(1),
+            _ => 1,
+        };
+        if confirmations < T::FeatureChangeConfirmations::get() {
             log::info!(
                 target: LOG_TARGET,
-                "License validation successful and chain is halted; will request resume via unsigned tx"
+                "New licensed features reported {} of {} times; not applied yet",
+                confirmations,
+                T::FeatureChangeConfirmations::get()
             );
-            storage_resume.set(&true);
-        } else if is_valid && !currently_halted {
-            // License is valid and we're not halted -> all good
-            log::info!(target: LOG_TARGET, "License validation successful");
-        } else {
-            // License is invalid and we're already halted -> no action needed
-            log::warn!(target: LOG_TARGET, "License still invalid, chain remains halted");
+            PendingFeatures::<T>::put((kept, confirmations));
+            return;
         }
+        PendingFeatures::<T>::kill();
 
-        Ok(())
+        if truncated {
+            log::warn!(
+                target: LOG_TARGET,
+                "License reports {} features; keeping the first {}",
+                features.len(),
+                kept.len()
+            );
+            Self::deposit_event(Event::FeaturesTruncated {
+                reported: features.len() as u32,
+                kept: kept.len() as u32,
+            });
+        }
+        LicensedFeatures::<T>::put(kept);
     }
 
-    /// Parse a JSON body that contains `"valid": true` or `"valid": false`.
-    fn parse_license_response(response_str: &str) -> bool {
-        if let Some(start) = response_str.find("\"valid\"") {
-            let after_valid = &response_str[start + 7..];
-            let trimmed = after_valid.trim_start();
-            if let Some(colon_trimmed) = trimmed.strip_prefix(':') {
-                let value_part = colon_trimmed.trim_start();
-                return value_part.starts_with("true");
+    /// Track the reported license term and emit any usage thresholds crossed for the first time.
+    fn note_license_term(report: &LicenseCheckReport) {
+        let (issued_at, expires_at) = match (report.issued_at, report.expires_at) {
+            (Some(issued_at), Some(expires_at)) if expires_at > issued_at => {
+                (issued_at, expires_at)
+            }
+            _ => return,
+        };
+
+        if LicenseTerm::<T>::get() != Some((issued_at, expires_at)) {
+            // A new term (e.g. after a renewal) re-arms every threshold.
+            LicenseTerm::<T>::put((issued_at, expires_at));
+            LicenseUsageThresholdReached::<T>::kill();
+        }
+
+        let now = pallet_timestamp::Pallet::<T>::get().saturated_into::<u64>();
+        let elapsed = now.saturating_sub(issued_at) as u128;
+        let term = (expires_at - issued_at) as u128;
+        let percent = (elapsed.saturating_mul(100) / term).min(100) as u8;
+
+        let mut reached = LicenseUsageThresholdReached::<T>::get();
+        let mut thresholds = T::LicenseUsageThresholds::get();
+        thresholds.sort_unstable();
+        for threshold in thresholds {
+            if threshold > reached && threshold <= percent {
+                Self::deposit_event(Event::LicenseUsageThreshold { percent: threshold });
+                reached = threshold;
             }
         }
-        false
+        LicenseUsageThresholdReached::<T>::put(reached);
     }
 
     /// Change authorities.
@@ -576,15 +4280,26 @@ impl<T: Config> Pallet<T> {
     /// The storage will be applied immediately.
     /// And aura consensus log will be appended to block's log.
     ///
-    /// This is a no-op if `new` is empty.
+    /// This is a no-op if `new` is empty, or has duplicates that
+    /// [`Config::DuplicateAuthorityPolicy`] rejects.
     pub fn change_authorities(new: BoundedVec<T::AuthorityId, T::MaxAuthorities>) {
         if new.is_empty() {
             log::warn!(target: LOG_TARGET, "Ignoring empty authority change.");
 
             return;
         }
+        let Some(new) = Self::without_duplicate_authorities(new.into_inner()) else {
+            return;
+        };
+        let new = BoundedVec::<_, T::MaxAuthorities>::truncate_from(new);
 
+        let (added, removed) = Self::authority_diff(&Authorities::<T>::get(), &new);
         <Authorities<T>>::put(&new);
+        // Authority indices may now refer to other authorities.
+        let _ = DisabledAuthorSince::<T>::clear(u32::MAX, None);
+        if !added.is_empty() || !removed.is_empty() {
+            Self::deposit_event(Event::AuthoritiesChanged { added, removed });
+        }
 
         let log = DigestItem::Consensus(
             AURA_ENGINE_ID,
@@ -593,6 +4308,51 @@ impl<T: Config> Pallet<T> {
         <frame_system::Pallet<T>>::deposit_log(log);
     }
 
+    /// The authorities `next` adds to `last` and the ones it removes.
+    ///
+    /// Both lists are sorted by the authorities' encoding and free of duplicates, so that the
+    /// resulting [`Event::AuthoritiesChanged`] does not depend on the order of the session.
+    pub fn authority_diff(
+        last: &[T::AuthorityId],
+        next: &[T::AuthorityId],
+    ) -> (Vec<T::AuthorityId>, Vec<T::AuthorityId>) {
+        let canonical = |mut authorities: Vec<T::AuthorityId>| {
+            authorities.sort_by_cached_key(|authority| authority.encode());
+            authorities.dedup();
+            authorities
+        };
+        let added = next
+            .iter()
+            .filter(|authority| !last.contains(authority))
+            .cloned()
+            .collect();
+        let removed = last
+            .iter()
+            .filter(|authority| !next.contains(authority))
+            .cloned()
+            .collect();
+        (canonical(added), canonical(removed))
+    }
+
+    /// `next` reordered to disturb `last` as little as possible: the authorities in both keep
+    /// their relative order from `last`, followed by the newcomers in their order from `next`.
+    pub fn preserve_authority_indices(
+        last: &[T::AuthorityId],
+        next: Vec<T::AuthorityId>,
+    ) -> Vec<T::AuthorityId> {
+        let mut merged: Vec<_> = last
+            .iter()
+            .filter(|authority| next.contains(authority))
+            .cloned()
+            .collect();
+        let newcomers: Vec<_> = next
+            .into_iter()
+            .filter(|authority| !last.contains(authority))
+            .collect();
+        merged.extend(newcomers);
+        merged
+    }
+
     /// Initial authorities.
     ///
     /// The storage will be applied immediately.
@@ -604,17 +4364,140 @@ impl<T: Config> Pallet<T> {
                 <Authorities<T>>::get().is_empty(),
                 "Authorities are already initialized!"
             );
-            let bounded = <BoundedSlice<'_, _, T::MaxAuthorities>>::try_from(authorities)
+            let authorities = Self::without_duplicate_authorities(authorities.to_vec())
+                .expect("Initial authority set must not contain duplicates");
+            let bounded = <BoundedVec<_, T::MaxAuthorities>>::try_from(authorities)
                 .expect("Initial authority set must be less than T::MaxAuthorities");
             <Authorities<T>>::put(bounded);
         }
     }
 
+    /// Whether `authorities` lists any authority more than once.
+    pub fn has_duplicate_authorities(authorities: &[T::AuthorityId]) -> bool {
+        let mut encoded: Vec<_> = authorities.iter().map(Encode::encode).collect();
+        encoded.sort();
+        encoded.windows(2).any(|pair| pair[0] == pair[1])
+    }
+
+    /// `authorities` as [`Config::DuplicateAuthorityPolicy`] has it: unchanged without
+    /// duplicates, else with only the first occurrence of each authority, or `None` to reject
+    /// it.
+    fn without_duplicate_authorities(
+        authorities: Vec<T::AuthorityId>,
+    ) -> Option<Vec<T::AuthorityId>> {
+        if !Self::has_duplicate_authorities(&authorities) {
+            return Some(authorities);
+        }
+        match T::DuplicateAuthorityPolicy::get() {
+            DuplicateAuthorityPolicy::Dedup => {
+                log::warn!(target: LOG_TARGET, "Authority set has duplicates; dropping them");
+                let mut unique = Vec::with_capacity(authorities.len());
+                for authority in authorities {
+                    if !unique.contains(&authority) {
+                        unique.push(authority);
+                    }
+                }
+                Some(unique)
+            }
+            DuplicateAuthorityPolicy::Reject => {
+                log::error!(target: LOG_TARGET, "Authority set has duplicates; rejecting it");
+                None
+            }
+        }
+    }
+
     /// Return current authorities length.
     pub fn authorities_len() -> usize {
         Authorities::<T>::decode_len().unwrap_or(0)
     }
 
+    /// The current slot together with the index of the authority assigned to it.
+    ///
+    /// Both come from a single read of [`CurrentSlot`] and use the same round-robin as
+    /// [`FindAuthor`], so they can't disagree. The index is `None` without authorities.
+    pub fn current_slot_and_author() -> (Slot, Option<u32>) {
+        let slot = CurrentSlot::<T>::get();
+        let authorities = Self::authorities_len() as u64;
+        let author = (authorities > 0).then(|| (*slot % authorities) as u32);
+        (slot, author)
+    }
+
+    /// How many reported license checks succeeded in a row during the ongoing halt, see
+    /// [`ConsecutiveSuccessesWhileHalted`].
+    pub fn consecutive_successes_while_halted() -> u32 {
+        ConsecutiveSuccessesWhileHalted::<T>::get()
+    }
+
+    /// The authority assigned to the slot after [`CurrentSlot`], by the same round-robin as
+    /// [`FindAuthor`]. `None` without authorities.
+    pub fn next_slot_author() -> Option<T::AuthorityId> {
+        let next_slot = CurrentSlot::<T>::get().saturating_add(1u64);
+        let authorities = Authorities::<T>::get();
+        let len = authorities.len() as u64;
+        (len > 0).then(|| authorities[(*next_slot % len) as usize].clone())
+    }
+
+    /// The author of the block at `slot` that Aura assigned to `assigned`, as refined by
+    /// [`Config::SameSlotAuthor`].
+    ///
+    /// The block's position within its slot comes from [`SlotBlockPosition`]. A slot not noted
+    /// there yet is taken to be at its first block, so the refinement of a block's author is only
+    /// complete once this pallet's `on_initialize` has run for it.
+    fn same_slot_author(slot: Slot, assigned: u32, authorities: u32) -> u32 {
+        if !T::AllowMultipleBlocksPerSlot::get() {
+            return assigned;
+        }
+        let position = match SlotBlockPosition::<T>::get() {
+            Some((noted, position)) if noted == slot => position,
+            _ => 0,
+        };
+        let author = T::SameSlotAuthor::author_index(slot, position, assigned, authorities);
+        if author < authorities {
+            author
+        } else {
+            log::warn!(
+                target: LOG_TARGET,
+                "Same-slot author index {} is out of range; keeping {}",
+                author,
+                assigned
+            );
+            assigned
+        }
+    }
+
+    /// Record `authority_index` as the author of the current block in [`RecentAuthors`].
+    fn note_block_author(authority_index: u32) {
+        RecentAuthors::<T>::mutate(|authors| push_evicting_oldest(authors, authority_index));
+    }
+
+    /// How many of the last `window` recorded blocks, at most [`AUTHOR_HISTORY_LEN`], each
+    /// authority index authored, by index. Indices without blocks are left out.
+    ///
+    /// Indices are those of the authority set at the time, so a window spanning an authority
+    /// change mixes two sets.
+    pub fn author_distribution(window: u32) -> Vec<(u32, u32)> {
+        let authors = RecentAuthors::<T>::get();
+        let start = authors.len().saturating_sub(window as usize);
+        let mut counts = alloc::collections::BTreeMap::<u32, u32>::new();
+        for index in &authors[start..] {
+            *counts.entry(*index).or_default() += 1;
+        }
+        counts.into_iter().collect()
+    }
+
+    /// Record `slot` as the slot of block `n` in [`SlotHistory`].
+    fn note_block_slot(n: BlockNumberFor<T>, slot: Slot) {
+        SlotHistory::<T>::mutate(|history| push_evicting_oldest(history, (n, slot)));
+    }
+
+    /// The `(block, slot)` pairs of the last `window` recorded blocks, at most
+    /// [`SLOT_HISTORY_LEN`], oldest first.
+    pub fn slot_history(window: u32) -> Vec<(BlockNumberFor<T>, Slot)> {
+        let history = SlotHistory::<T>::get();
+        let start = history.len().saturating_sub(window as usize);
+        history[start..].to_vec()
+    }
+
     /// Get the current slot from the pre-runtime digests.
     fn current_slot_from_digests() -> Option<Slot> {
         let digest = frame_system::Pallet::<T>::digest();
@@ -639,11 +4522,17 @@ impl<T: Config> Pallet<T> {
     ///
     /// # Invariants
     ///
+    /// ## `SlotDuration`
+    ///
+    /// The configured slot duration must be non-zero.
+    ///
     /// ## `CurrentSlot`
     ///
     /// If we don't allow for multiple blocks per slot, then the current slot must be less than the
     /// maximal slot number. Otherwise, it can be arbitrary.
     ///
+    /// The slot in the pre-runtime digest must not be behind the stored slot.
+    ///
     /// ## `Authorities`
     ///
     /// * The authorities must be non-empty.
@@ -652,11 +4541,29 @@ impl<T: Config> Pallet<T> {
     ///   is guarded by the type system.
     #[cfg(any(test, feature = "try-runtime"))]
     pub fn do_try_state() -> Result<(), sp_runtime::TryRuntimeError> {
+        frame_support::ensure!(!Self::slot_duration().is_zero(), ZERO_SLOT_DURATION);
+
         // We don't have any guarantee that we are already after `on_initialize` and thus we have to
         // check the current slot from the digest or take the last known slot.
         let current_slot =
             Self::current_slot_from_digests().unwrap_or_else(|| CurrentSlot::<T>::get());
 
+        // A digest behind the stored slot would make `on_initialize` panic; report it instead.
+        if let Some(digest_slot) = Self::current_slot_from_digests() {
+            let stored_slot = CurrentSlot::<T>::get();
+            if digest_slot < stored_slot {
+                log::error!(
+                    target: LOG_TARGET,
+                    "Slot must not decrease: current slot {}, new slot {} \
+                     (AllowMultipleBlocksPerSlot = {})",
+                    *stored_slot,
+                    *digest_slot,
+                    T::AllowMultipleBlocksPerSlot::get(),
+                );
+                return Err("Slot must not decrease".into());
+            }
+        }
+
         // Check that the current slot is less than the maximal slot number, unless we allow for
         // multiple blocks per slot.
         if !T::AllowMultipleBlocksPerSlot::get() {
@@ -672,6 +4579,12 @@ impl<T: Config> Pallet<T> {
         // Check that the authorities are non-empty.
         frame_support::ensure!(!authorities_len.is_zero(), "Authorities must be non-empty.");
 
+        // Check that no authority takes several turns of the round-robin.
+        frame_support::ensure!(
+            !Self::has_duplicate_authorities(&Authorities::<T>::get()),
+            "Authorities must be unique."
+        );
+
         // Check that the current authority is not disabled.
         let authority_index = *current_slot % authorities_len as u64;
         frame_support::ensure!(
@@ -702,17 +4615,37 @@ impl<T: Config> OneSessionHandler<T::AccountId> for Pallet<T> {
     where
         I: Iterator<Item = (&'a T::AccountId, T::AuthorityId)>,
     {
+        CurrentSession::<T>::mutate(|session| *session = session.wrapping_add(1));
+
         // instant changes
         if changed {
             let next_authorities = validators.map(|(_, k)| k).collect::<Vec<_>>();
             let last_authorities = Authorities::<T>::get();
+            let next_authorities = match T::AuthorityUpdatePolicy::get() {
+                AuthorityUpdatePolicy::Replace => next_authorities,
+                AuthorityUpdatePolicy::PreserveIndices => {
+                    Self::preserve_authority_indices(&last_authorities, next_authorities)
+                }
+            };
             if last_authorities != next_authorities {
                 if next_authorities.len() as u32 > T::MaxAuthorities::get() {
-                    log::warn!(
-                        target: LOG_TARGET,
-                        "next authorities list larger than {}, truncating",
-                        T::MaxAuthorities::get(),
-                    );
+                    match T::OversizedAuthoritySetPolicy::get() {
+                        OversizedAuthoritySetPolicy::Truncate => {
+                            log::warn!(
+                                target: LOG_TARGET,
+                                "next authorities list larger than {}, truncating",
+                                T::MaxAuthorities::get(),
+                            );
+                        }
+                        OversizedAuthoritySetPolicy::Reject => {
+                            log::error!(
+                                target: LOG_TARGET,
+                                "next authorities list larger than {}, keeping previous set",
+                                T::MaxAuthorities::get(),
+                            );
+                            return;
+                        }
+                    }
                 }
                 let bounded = <BoundedVec<_, T::MaxAuthorities>>::truncate_from(next_authorities);
                 Self::change_authorities(bounded);
@@ -721,6 +4654,10 @@ impl<T: Config> OneSessionHandler<T::AccountId> for Pallet<T> {
     }
 
     fn on_disabled(i: u32) {
+        if !DisabledAuthorSince::<T>::contains_key(i) {
+            DisabledAuthorSince::<T>::insert(i, <frame_system::Pallet<T>>::block_number());
+        }
+
         let log = DigestItem::Consensus(
             AURA_ENGINE_ID,
             ConsensusLog::<T::AuthorityId>::OnDisabled(i as AuthorityIndex).encode(),
@@ -738,8 +4675,13 @@ impl<T: Config> FindAuthor<u32> for Pallet<T> {
         for (id, mut data) in digests.into_iter() {
             if id == AURA_ENGINE_ID {
                 let slot = Slot::decode(&mut data).ok()?;
-                let author_index = *slot % Self::authorities_len() as u64;
-                return Some(author_index as u32);
+                let authorities = Self::authorities_len() as u64;
+                let author_index = *slot % authorities;
+                return Some(Self::same_slot_author(
+                    slot,
+                    author_index as u32,
+                    authorities as u32,
+                ));
             }
         }
 
@@ -17,7 +17,19 @@
 
 //! Migrations for the AURA pallet.
 
-use frame_support::{pallet_prelude::*, traits::Get, weights::Weight};
+use frame_support::{
+    pallet_prelude::*,
+    traits::{Get, OnRuntimeUpgrade},
+    weights::Weight,
+};
+use sp_consensus_aura::Slot;
+
+#[cfg(feature = "try-runtime")]
+use alloc::vec::Vec;
+#[cfg(feature = "try-runtime")]
+use sp_runtime::traits::SaturatedConversion;
+
+const LOG_TARGET: &str = "runtime::aura::migrations";
 
 struct __LastTimestamp<T>(core::marker::PhantomData<T>);
 impl<T: RemoveLastTimestamp> frame_support::traits::StorageInstance for __LastTimestamp<T> {
@@ -43,3 +55,95 @@ pub fn remove_last_timestamp<T: RemoveLastTimestamp>() -> Weight {
     LastTimestamp::<T>::kill();
     T::DbWeight::get().writes(1)
 }
+
+/// Rescale `CurrentSlot` for a runtime upgrade that changes the slot duration.
+///
+/// `CurrentSlot` counts slots of the old duration. Left alone, the first timestamp after the
+/// upgrade would derive a slot of the new duration and `on_timestamp_set` would panic on the
+/// mismatch. This migration converts the current slot to the new duration, rounding down so the
+/// slot never appears to advance beyond the time that actually passed.
+///
+/// `OldSlotDuration` and `NewSlotDuration` are in milliseconds; `NewSlotDuration` should match
+/// the upgraded [`crate::Config::SlotDuration`]. Include this migration only in the upgrade that
+/// changes the duration, as it is not idempotent.
+pub struct RescaleCurrentSlot<T, OldSlotDuration, NewSlotDuration>(
+    core::marker::PhantomData<(T, OldSlotDuration, NewSlotDuration)>,
+);
+
+impl<T, OldSlotDuration, NewSlotDuration> RescaleCurrentSlot<T, OldSlotDuration, NewSlotDuration>
+where
+    OldSlotDuration: Get<u64>,
+    NewSlotDuration: Get<u64>,
+{
+    /// The slot of `NewSlotDuration` containing the start of `slot` of `OldSlotDuration`.
+    pub fn rescale(slot: Slot) -> Slot {
+        let started_at = u128::from(*slot) * u128::from(OldSlotDuration::get());
+        let rescaled = started_at / u128::from(NewSlotDuration::get());
+        Slot::from(u64::try_from(rescaled).unwrap_or(u64::MAX))
+    }
+}
+
+impl<T, OldSlotDuration, NewSlotDuration> OnRuntimeUpgrade
+    for RescaleCurrentSlot<T, OldSlotDuration, NewSlotDuration>
+where
+    T: super::Config,
+    OldSlotDuration: Get<u64>,
+    NewSlotDuration: Get<u64>,
+{
+    fn on_runtime_upgrade() -> Weight {
+        let (old, new) = (OldSlotDuration::get(), NewSlotDuration::get());
+        if old == new || new == 0 {
+            log::warn!(
+                target: LOG_TARGET,
+                "Slot duration {}ms -> {}ms needs no rescaling; skipping",
+                old,
+                new
+            );
+            return Weight::zero();
+        }
+
+        let slot = super::CurrentSlot::<T>::get();
+        let rescaled = Self::rescale(slot);
+        super::CurrentSlot::<T>::put(rescaled);
+
+        log::info!(
+            target: LOG_TARGET,
+            "Rescaled CurrentSlot {} -> {} for slot duration {}ms -> {}ms",
+            *slot,
+            *rescaled,
+            old,
+            new
+        );
+        T::DbWeight::get().reads_writes(1, 1)
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn pre_upgrade() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
+        ensure!(
+            NewSlotDuration::get() != 0,
+            "New slot duration must not be zero"
+        );
+        Ok(super::CurrentSlot::<T>::get().encode())
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn post_upgrade(state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+        let old_slot =
+            Slot::decode(&mut &state[..]).map_err(|_| "Failed to decode the pre-upgrade slot")?;
+        let expected = if OldSlotDuration::get() == NewSlotDuration::get() {
+            old_slot
+        } else {
+            Self::rescale(old_slot)
+        };
+
+        ensure!(
+            super::CurrentSlot::<T>::get() == expected,
+            "CurrentSlot was not rescaled to the new slot duration"
+        );
+        ensure!(
+            NewSlotDuration::get() == T::SlotDuration::get().saturated_into::<u64>(),
+            "NewSlotDuration does not match the configured SlotDuration"
+        );
+        Ok(())
+    }
+}
@@ -20,12 +20,24 @@
 #![cfg(test)]
 
 use crate as pallet_aura;
+use crate::http_client::{HttpFailure, HttpResponse, LicenseHttpClient, LicenseRequest};
+use codec::Decode;
 use frame_support::{
-    derive_impl, parameter_types,
-    traits::{ConstU32, ConstU64, DisabledValidators},
+    derive_impl, ord_parameter_types, parameter_types,
+    traits::{ConstU32, ConstU64, DisabledValidators, EitherOfDiverse, PalletInfoAccess},
 };
-use sp_consensus_aura::{ed25519::AuthorityId, AuthorityIndex};
-use sp_runtime::{testing::UintAuthorityId, BuildStorage};
+use frame_system::{EnsureRoot, EnsureSignedBy};
+use parking_lot::RwLock;
+use sp_consensus_aura::{ed25519::AuthorityId, AuthorityIndex, Slot};
+use sp_core::offchain::{
+    testing::{OffchainState, PendingRequest, PoolState, TestOffchainExt, TestTransactionPoolExt},
+    OffchainDbExt, OffchainWorkerExt, Timestamp as OffchainTimestamp, TransactionPoolExt,
+};
+use sp_keystore::{testing::MemoryKeystore, KeystoreExt};
+use sp_runtime::{
+    offchain::storage::StorageValueRef, testing::UintAuthorityId, BuildStorage, Percent,
+};
+use std::{collections::VecDeque, sync::Arc};
 
 type Block = frame_system::mocking::MockBlock<Test>;
 
@@ -43,6 +55,7 @@ frame_support::construct_runtime!(
 #[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
 impl frame_system::Config for Test {
     type Block = Block;
+    type Version = MockVersion;
 }
 
 impl pallet_timestamp::Config for Test {
@@ -52,11 +65,191 @@ impl pallet_timestamp::Config for Test {
     type WeightInfo = ();
 }
 
+ord_parameter_types! {
+    pub const HaltAdmin: u64 = 42;
+}
+
 parameter_types! {
     static DisabledValidatorTestValue: Vec<AuthorityIndex> = Default::default();
     pub static AllowMultipleBlocksPerSlot: bool = false;
+    pub static SameSlotAuthorStep: Option<u32> = None;
+    pub static OversizedPolicy: pallet_aura::OversizedAuthoritySetPolicy =
+        pallet_aura::OversizedAuthoritySetPolicy::Truncate;
+    pub static AuthorityUpdate: pallet_aura::AuthorityUpdatePolicy =
+        pallet_aura::AuthorityUpdatePolicy::Replace;
+    pub static DuplicateAuthorities: pallet_aura::DuplicateAuthorityPolicy =
+        pallet_aura::DuplicateAuthorityPolicy::Dedup;
+    pub LicenseUsageThresholds: Vec<u8> = vec![50, 80];
+    pub HealthScoreThresholds: Vec<u8> = vec![50, 90];
+    pub static FailClosed: bool = false;
+    pub static MockSlotDuration: u64 = SLOT_DURATION;
+    pub static EnforceLicenseInherent: bool = false;
+    pub static OffchainWarmupChecks: u32 = 0;
+    pub static AutoRecoveryDelay: u64 = 0;
+    pub static ReportNodeVersion: bool = false;
+    pub static ReportBlockNumber: bool = false;
+    pub MockVersion: sp_version::RuntimeVersion = sp_version::RuntimeVersion {
+        spec_version: 3,
+        impl_version: 7,
+        ..Default::default()
+    };
+    pub static EventThrottleBlocks: u64 = 0;
+    pub static HaltEventDebounce: u64 = 0;
+    pub static HaltIfOcwAbsent: bool = false;
+    pub static CheckEverySlots: Option<u32> = None;
+    pub static CheckOncePerSession: bool = false;
+    pub static OcwAbsenceThreshold: u64 = 10;
+    pub static EndpointSelectionMode: pallet_aura::EndpointSelection =
+        pallet_aura::EndpointSelection::Failover;
+    pub static LicenseQuorum: Percent = Percent::from_percent(51);
+    pub static RenewalUrl: Option<&'static str> = None;
+    pub static HaltAllowedPallets: Vec<u8> = vec![];
+    pub static HeartbeatInterval: u64 = 0;
+    pub static OffchainTimeBudget: u64 = 0;
+    pub static FeatureChangeConfirmations: u32 = 1;
+    pub static SharedCheckResults: bool = false;
+    pub static LicensePublicKey: Option<[u8; 32]> = None;
+    pub static OfflineKeyHashes: &'static [[u8; 32]] = &[];
+    pub static PathStyleLicenseUrls: bool = false;
+    pub static TimestampedLicenseUrls: bool = false;
+    pub static MinAuthorities: u32 = 1;
+    pub static DisabledAuthorGrace: u64 = 0;
+    pub static MissingValidField: pallet_aura::MissingValidFieldPolicy =
+        pallet_aura::MissingValidFieldPolicy::Invalid;
+    pub static AlternativeValidField: Option<&'static str> = None;
+    pub static ValidityFieldPath: &'static [&'static str] = &[];
+    pub static EmptyBodyMeansValid: bool = false;
+    pub static RequireSignedCertFingerprint: bool = false;
 }
 
+impl pallet_aura::filter::IsLicensedAuraCall for RuntimeCall {
+    fn is_sudo_resume_production(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_aura::Call::sudo_resume_production { .. })
+        )
+    }
+
+    fn is_sudo_force_resume(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_aura::Call::sudo_force_resume { .. })
+        )
+    }
+
+    fn is_offchain_worker_halt(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_aura::Call::offchain_worker_halt_production { .. })
+        )
+    }
+
+    fn is_offchain_worker_resume(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_aura::Call::offchain_worker_resume_production { .. })
+        )
+    }
+
+    fn is_offchain_worker_report(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_aura::Call::offchain_worker_report_check { .. })
+        )
+    }
+
+    fn is_offchain_worker_authority_report(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_aura::Call::offchain_worker_report_authority_check { .. })
+        )
+    }
+
+    fn is_offchain_worker_unusable_key_report(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_aura::Call::offchain_worker_report_unusable_key { .. })
+        )
+    }
+
+    fn is_queue_call_for_resume(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_aura::Call::sudo_queue_call_for_resume { .. })
+        )
+    }
+
+    fn is_set_halt_reason(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_aura::Call::set_halt_reason { .. })
+        )
+    }
+
+    fn is_set_auto_recovery_paused(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_aura::Call::sudo_set_auto_recovery_paused { .. })
+        )
+    }
+
+    fn is_install_perpetual_license(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_aura::Call::install_perpetual_license { .. })
+        )
+    }
+
+    fn pallet_index(&self) -> u8 {
+        let index = match self {
+            RuntimeCall::System(_) => System::index(),
+            RuntimeCall::Timestamp(_) => Timestamp::index(),
+            RuntimeCall::Aura(_) => Aura::index(),
+        };
+        index as u8
+    }
+}
+
+impl pallet_aura::filter::IsDefaultInherentExstrinsicCall for RuntimeCall {
+    fn is_timestamp_set(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Timestamp(pallet_timestamp::Call::set { .. })
+        )
+    }
+
+    fn is_license_proof(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_aura::Call::note_license_proof { .. })
+        )
+    }
+}
+
+// The mock runtime has no sudo pallet.
+impl pallet_aura::filter::IsSudoCall<RuntimeCall> for RuntimeCall {
+    fn is_sudo_wrapping_allowed(&self) -> bool {
+        false
+    }
+
+    fn is_sudo_wrapping_resume(&self) -> bool {
+        false
+    }
+}
+
+impl pallet_aura::filter::HaltWhitelistCallIndices for RuntimeCall {
+    fn inherent_call_indices() -> Vec<(u8, u8)> {
+        pallet_aura::filter::call_indices::<Timestamp, pallet_timestamp::Call<Test>>(&["set"])
+    }
+
+    fn sudo_call_indices() -> Vec<(u8, u8)> {
+        vec![]
+    }
+}
+
+/// The runtime's call filter, as a runtime using Licensed Aura would configure it.
+pub type HaltFilter = pallet_aura::filter::AuraHaltFilter<RuntimeCall, Test>;
+
 pub struct MockDisabledValidators;
 
 impl MockDisabledValidators {
@@ -81,6 +274,72 @@ impl DisabledValidators for MockDisabledValidators {
     }
 }
 
+/// Attributes block `position` of a slot to the authority `position * step` after the slot's
+/// own, once a [`SameSlotAuthorStep`] is set. Indices past the authority set are left as they are.
+pub struct MockSameSlotAuthor;
+
+impl pallet_aura::SameSlotAuthor for MockSameSlotAuthor {
+    fn author_index(_slot: Slot, position: u32, assigned: u32, _authorities: u32) -> u32 {
+        match SameSlotAuthorStep::get() {
+            Some(step) => assigned + position * step,
+            None => assigned,
+        }
+    }
+}
+
+/// Queries `<base>/<key>/<block>` once [`PathStyleLicenseUrls`] is set, and `<base>` before.
+pub struct MockLicenseUrlBuilder;
+
+impl pallet_aura::LicenseUrlBuilder<u64> for MockLicenseUrlBuilder {
+    fn build_url(base: &str, key: &[u8], block: u64) -> String {
+        if PathStyleLicenseUrls::get() {
+            format!("{}/{}/{}", base, String::from_utf8_lossy(key), block)
+        } else {
+            <() as pallet_aura::LicenseUrlBuilder<u64>>::build_url(base, key, block)
+        }
+    }
+}
+
+/// Appends `?key=<key>` unless [`PathStyleLicenseUrls`] already put the key in the path. Adds
+/// the request time as `ts` while [`TimestampedLicenseUrls`] is set.
+pub struct MockUrlTransformer;
+
+impl pallet_aura::UrlTransformer for MockUrlTransformer {
+    fn transform(base: &str, key: &str) -> String {
+        let url = if PathStyleLicenseUrls::get() {
+            String::from(base)
+        } else {
+            <() as pallet_aura::UrlTransformer>::transform(base, key)
+        };
+        if !TimestampedLicenseUrls::get() {
+            return url;
+        }
+        let separator = if url.contains('?') { '&' } else { '?' };
+        format!(
+            "{}{}ts={}",
+            url,
+            separator,
+            sp_io::offchain::timestamp().unix_millis()
+        )
+    }
+}
+
+/// Leaves calls unwrapped, as the mock runtime has no sudo pallet, and allows the system pallet
+/// while halted.
+#[cfg(feature = "runtime-benchmarks")]
+pub struct MockBenchmarkHelper;
+
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet_aura::BenchmarkHelper<RuntimeCall> for MockBenchmarkHelper {
+    fn sudo(call: RuntimeCall) -> RuntimeCall {
+        call
+    }
+
+    fn allow_another_pallet_while_halted() {
+        HaltAllowedPallets::set(vec![System::index() as u8]);
+    }
+}
+
 impl frame_system::offchain::SigningTypes for Test {
     type Public = sp_runtime::testing::UintAuthorityId;
     type Signature = sp_runtime::testing::TestSignature;
@@ -97,13 +356,211 @@ where
 impl pallet_aura::Config for Test {
     type AuthorityId = AuthorityId;
     type DisabledValidators = MockDisabledValidators;
+    type DisabledAuthorGrace = DisabledAuthorGrace;
     type MaxAuthorities = ConstU32<10>;
+    type MinAuthorities = MinAuthorities;
+    type OversizedAuthoritySetPolicy = OversizedPolicy;
+    type DuplicateAuthorityPolicy = DuplicateAuthorities;
+    type AuthorityUpdatePolicy = AuthorityUpdate;
     type AllowMultipleBlocksPerSlot = AllowMultipleBlocksPerSlot;
-    type SlotDuration = ConstU64<SLOT_DURATION>;
+    type SameSlotAuthor = MockSameSlotAuthor;
+    type SlotDuration = MockSlotDuration;
     type RuntimeEvent = RuntimeEvent;
+    type LicenseUsageThresholds = LicenseUsageThresholds;
+    type LicenseCacheTtl = ConstU64<LICENSE_CACHE_TTL>;
+    type SharedCheckResults = SharedCheckResults;
+    type LicenseCheckInterval = ConstU64<LICENSE_CHECK_INTERVAL>;
+    type CheckEverySlots = CheckEverySlots;
+    type CheckOncePerSession = CheckOncePerSession;
+    type LicenseRequestAttempts = ConstU32<3>;
+    type LicenseRetryBackoff = ConstU64<100>;
+    type OffchainTimeBudget = OffchainTimeBudget;
+    type HealthStalenessBlocks = ConstU64<100>;
+    type HealthScoreThresholds = HealthScoreThresholds;
+    type FailClosed = FailClosed;
+    type EnforceLicenseInherent = EnforceLicenseInherent;
+    type LicenseProofMaxAge = ConstU64<LICENSE_PROOF_MAX_AGE>;
+    type SubmissionRetryBackoff = ConstU64<5>;
+    type LicenseQuorum = LicenseQuorum;
+    type MaxLicensedFeatures = ConstU32<3>;
+    type FeatureChangeConfirmations = FeatureChangeConfirmations;
+    type MaxClockDrift = ConstU64<MAX_CLOCK_DRIFT>;
+    type OffchainWarmupChecks = OffchainWarmupChecks;
+    type EndpointSelection = EndpointSelectionMode;
+    type LicenseUrlBuilder = MockLicenseUrlBuilder;
+    type RequestUrlTransformer = MockUrlTransformer;
+    type RenewalUrl = RenewalUrl;
+    type HaltAllowedPallets = HaltAllowedPallets;
+    type LicensePublicKey = LicensePublicKey;
+    type RequireSignedCertFingerprint = RequireSignedCertFingerprint;
+    type OfflineKeyHashes = OfflineKeyHashes;
+    type MissingValidField = MissingValidField;
+    type AlternativeValidField = AlternativeValidField;
+    type ValidityFieldPath = ValidityFieldPath;
+    type EmptyBodyMeansValid = EmptyBodyMeansValid;
+    type AutoRecoveryDelay = AutoRecoveryDelay;
+    type MaxResponseBytes = ConstU32<MAX_RESPONSE_BYTES>;
+    type EventThrottleBlocks = EventThrottleBlocks;
+    type HaltEventDebounce = HaltEventDebounce;
+    type ReportNodeVersion = ReportNodeVersion;
+    type ReportBlockNumber = ReportBlockNumber;
+    type RuntimeCall = RuntimeCall;
+    #[cfg(feature = "runtime-benchmarks")]
+    type BenchmarkHelper = MockBenchmarkHelper;
+    type MaxQueuedCalls = ConstU32<2>;
+    type MaxAuthTokenLen = ConstU32<MAX_AUTH_TOKEN_LEN>;
+    type HaltIfOcwAbsent = HaltIfOcwAbsent;
+    type OcwAbsenceThreshold = OcwAbsenceThreshold;
+    type HeartbeatInterval = HeartbeatInterval;
+    type HaltOrigin = EitherOfDiverse<EnsureRoot<u64>, EnsureSignedBy<HaltAdmin, u64>>;
 }
 
-fn build_ext(authorities: Vec<u64>) -> sp_io::TestExternalities {
+/// How long a valid license check is cached by the offchain worker in tests.
+pub const LICENSE_CACHE_TTL: u64 = 300_000;
+
+/// Default interval between offchain license checks in tests.
+pub const LICENSE_CHECK_INTERVAL: u64 = 30_000;
+
+/// How far the offchain clock may drift from the on-chain timestamp in tests.
+pub const MAX_CLOCK_DRIFT: u64 = 60_000;
+
+/// Longest auth token accepted in tests, in bytes.
+pub const MAX_AUTH_TOKEN_LEN: u32 = 64;
+
+/// Largest license response body accepted in tests, in bytes.
+pub const MAX_RESPONSE_BYTES: u32 = 4096;
+
+/// How old a license proof may be in tests.
+pub const LICENSE_PROOF_MAX_AGE: u64 = 600_000;
+
+/// Hex of the genesis hash `frame_system` stores in test externalities.
+pub const GENESIS_HASH_HEX: &str =
+    "4545454545454545454545454545454545454545454545454545454545454545";
+
+/// The instance ID of the test node.
+pub const INSTANCE_ID: [u8; 16] = [0x1d; 16];
+
+/// Hex encoding of [`INSTANCE_ID`].
+pub const INSTANCE_ID_HEX: &str = "1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d";
+
+/// The URL the offchain worker queries for the genesis license key.
+pub const LICENSE_URL: &str = concat!(
+    "http://localhost:3000/license?key=test-license-key",
+    "&genesis=4545454545454545454545454545454545454545454545454545454545454545",
+    "&instance=1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d",
+);
+
+/// Handles on the offchain state of an externalities built by [`build_offchain_ext`].
+pub struct OffchainHandles {
+    pub offchain: Arc<RwLock<OffchainState>>,
+    pub pool: Arc<RwLock<PoolState>>,
+}
+
+impl OffchainHandles {
+    /// Set the offchain worker's notion of "now".
+    pub fn set_timestamp(&self, unix_millis: u64) {
+        self.offchain.write().timestamp = OffchainTimestamp::from_unix_millis(unix_millis);
+    }
+
+    /// Expect a single license request to `uri`, answered with `body`.
+    pub fn expect_request(&self, uri: &str, body: &[u8]) {
+        self.expect_request_with_headers(uri, vec![], body);
+    }
+
+    /// Expect a single license request to `uri` carrying `headers`, answered with `body`.
+    pub fn expect_request_with_headers(
+        &self,
+        uri: &str,
+        headers: Vec<(String, String)>,
+        body: &[u8],
+    ) {
+        self.offchain.write().expect_request(PendingRequest {
+            method: "GET".into(),
+            uri: uri.into(),
+            headers,
+            response: Some(body.to_vec()),
+            sent: true,
+            ..Default::default()
+        });
+    }
+
+    /// Expect a single `POST` of `body` to `uri`, answered with `response`.
+    pub fn expect_post(&self, uri: &str, body: &[u8], response: &[u8]) {
+        self.offchain.write().expect_request(PendingRequest {
+            method: "POST".into(),
+            uri: uri.into(),
+            body: body.to_vec(),
+            response: Some(response.to_vec()),
+            sent: true,
+            ..Default::default()
+        });
+    }
+
+    /// Expect a single request for the genesis license key, answered with `body`.
+    pub fn expect_license_request(&self, body: &[u8]) {
+        self.expect_request(LICENSE_URL, body);
+    }
+
+    /// Calls submitted to the transaction pool so far.
+    pub fn submitted_calls(&self) -> Vec<RuntimeCall> {
+        self.pool
+            .read()
+            .transactions
+            .iter()
+            .map(|tx| Extrinsic::decode(&mut &tx[..]).unwrap().call)
+            .collect()
+    }
+}
+
+type Extrinsic = sp_runtime::testing::TestXt<RuntimeCall, ()>;
+
+/// A [`LicenseHttpClient`] answering from a script and recording what it was asked to do.
+#[derive(Default)]
+pub struct ScriptedHttpClient {
+    pub script: VecDeque<Result<HttpResponse, HttpFailure>>,
+    pub requests: Vec<LicenseRequest>,
+    pub posted_bodies: Vec<Vec<u8>>,
+    pub sleeps: Vec<u64>,
+    /// Milliseconds every request takes on [`LicenseHttpClient::now_millis`].
+    pub latency_ms: u64,
+    /// Milliseconds spent in requests and sleeps so far.
+    pub elapsed_ms: u64,
+}
+
+impl ScriptedHttpClient {
+    pub fn new(script: Vec<Result<HttpResponse, HttpFailure>>) -> Self {
+        Self {
+            script: script.into(),
+            ..Default::default()
+        }
+    }
+}
+
+impl LicenseHttpClient for ScriptedHttpClient {
+    fn get(&mut self, request: &LicenseRequest) -> Result<HttpResponse, HttpFailure> {
+        self.requests.push(request.clone());
+        self.elapsed_ms += self.latency_ms;
+        self.script
+            .pop_front()
+            .expect("ScriptedHttpClient ran out of responses")
+    }
+
+    fn post(&mut self, request: &LicenseRequest, body: &[u8]) -> Result<HttpResponse, HttpFailure> {
+        self.posted_bodies.push(body.to_vec());
+        self.get(request)
+    }
+
+    fn sleep(&mut self, millis: u64) {
+        self.sleeps.push(millis);
+        self.elapsed_ms += millis;
+    }
+
+    fn now_millis(&self) -> u64 {
+        self.elapsed_ms
+    }
+}
+
+pub fn build_ext(authorities: Vec<u64>) -> sp_io::TestExternalities {
     let mut storage = frame_system::GenesisConfig::<Test>::default()
         .build_storage()
         .unwrap();
@@ -126,3 +583,74 @@ pub fn build_ext_and_execute_test(authorities: Vec<u64>, test: impl FnOnce() ->
         Aura::do_try_state().expect("Storage invariants should hold")
     });
 }
+
+/// Like [`build_ext`], with offchain worker, offchain DB, keystore and transaction pool
+/// extensions.
+pub fn build_offchain_ext(authorities: Vec<u64>) -> (sp_io::TestExternalities, OffchainHandles) {
+    let mut ext = build_ext(authorities);
+    let (offchain, offchain_state) = TestOffchainExt::new();
+    let (pool, pool_state) = TestTransactionPoolExt::new();
+    ext.register_extension(OffchainDbExt::new(offchain.clone()));
+    ext.register_extension(OffchainWorkerExt::new(offchain));
+    ext.register_extension(TransactionPoolExt::new(pool));
+    ext.register_extension(KeystoreExt::new(MemoryKeystore::new()));
+    ext.execute_with(|| {
+        StorageValueRef::persistent(pallet_aura::INSTANCE_ID_STORAGE_KEY).set(&INSTANCE_ID)
+    });
+
+    (
+        ext,
+        OffchainHandles {
+            offchain: offchain_state,
+            pool: pool_state,
+        },
+    )
+}
+
+pub fn build_offchain_ext_and_execute_test(
+    authorities: Vec<u64>,
+    test: impl FnOnce(&OffchainHandles) -> (),
+) {
+    let (mut ext, handles) = build_offchain_ext(authorities);
+    ext.execute_with(|| {
+        test(&handles);
+        Aura::do_try_state().expect("Storage invariants should hold")
+    });
+}
+
+std::thread_local! {
+    static CAPTURED_LOGS: core::cell::RefCell<Vec<(log::Level, String)>> = Default::default();
+}
+
+/// Records every log line into the emitting thread's [`CAPTURED_LOGS`].
+struct CaptureLogger;
+
+impl log::Log for CaptureLogger {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        CAPTURED_LOGS.with(|logs| {
+            logs.borrow_mut()
+                .push((record.level(), record.args().to_string()))
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+/// Start capturing log lines emitted by the current test, dropping any captured so far.
+pub fn capture_logs() {
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+    INSTALL.call_once(|| {
+        log::set_logger(&CaptureLogger).expect("no other logger is installed in tests");
+        log::set_max_level(log::LevelFilter::Trace);
+    });
+    CAPTURED_LOGS.with(|logs| logs.borrow_mut().clear());
+}
+
+/// The log lines captured on this thread since [`capture_logs`].
+pub fn captured_logs() -> Vec<(log::Level, String)> {
+    CAPTURED_LOGS.with(|logs| logs.borrow().clone())
+}
@@ -0,0 +1,290 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The body of a license server response.
+//!
+//! License servers answer with a flat JSON object. [`LicenseResponse::parse`] reads every field
+//! the pallet understands in one go, so the license check, certificate pinning and renewal all
+//! work from the same typed value instead of each scanning the body for their own fields.
+//!
+//! The runtime carries no JSON library, so the scanner only handles what license servers send:
+//! a single object of booleans, unsigned integers, strings and arrays of strings, without string
+//! escapes. Unknown fields are ignored, as are known fields with a value of the wrong type. Only
+//! the verdict may sit in nested objects, see [`LicenseResponse::parse_with_valid_path`].
+//!
+//! Servers with another schema can be read by renaming fields, see
+//! [`LicenseResponse::parse_with_field_names`].
+
+use alloc::vec::Vec;
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+
+/// Name of the field carrying the license server's verdict.
+pub const VALID_FIELD: &str = "valid";
+
+/// A field of a license response, by the concept it carries rather than by name.
+#[derive(
+    Encode,
+    Decode,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    RuntimeDebug,
+    TypeInfo,
+    MaxEncodedLen,
+)]
+pub enum ResponseField {
+    /// [`LicenseResponse::valid`].
+    Valid,
+    /// [`LicenseResponse::issued_at`].
+    IssuedAt,
+    /// [`LicenseResponse::expires_at`].
+    ExpiresAt,
+    /// [`LicenseResponse::min_check_interval`].
+    MinCheckInterval,
+    /// [`LicenseResponse::not_before`].
+    NotBefore,
+    /// [`LicenseResponse::features`].
+    Features,
+    /// [`LicenseResponse::key`].
+    Key,
+    /// [`LicenseResponse::cert_fingerprint`].
+    CertFingerprint,
+    /// [`LicenseResponse::cert_fingerprint_sig`].
+    CertFingerprintSig,
+}
+
+impl ResponseField {
+    /// The name of the field in a standard response.
+    pub fn default_name(self) -> &'static str {
+        match self {
+            Self::Valid => VALID_FIELD,
+            Self::IssuedAt => "issued_at",
+            Self::ExpiresAt => "expires_at",
+            Self::MinCheckInterval => "min_check_interval",
+            Self::NotBefore => "not_before",
+            Self::Features => "features",
+            Self::Key => "key",
+            Self::CertFingerprint => "cert_fingerprint",
+            Self::CertFingerprintSig => "cert_fingerprint_sig",
+        }
+    }
+}
+
+/// The fields of a license server response. Timestamps are unix milliseconds.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug, TypeInfo)]
+pub struct LicenseResponse {
+    /// Whether the server considers the license valid, from `valid` or the configured
+    /// alternative field.
+    pub valid: Option<bool>,
+    /// Start of the license term, from `issued_at`.
+    pub issued_at: Option<u64>,
+    /// End of the license term, from `expires_at`.
+    pub expires_at: Option<u64>,
+    /// Minimum milliseconds between checks, from `min_check_interval`.
+    pub min_check_interval: Option<u64>,
+    /// Activation time of the license, from `not_before`.
+    pub not_before: Option<u64>,
+    /// Names of the licensed features, from `features`.
+    pub features: Option<Vec<Vec<u8>>>,
+    /// A renewed license key, from `key`.
+    pub key: Option<Vec<u8>>,
+    /// Hex-encoded fingerprint of the server's TLS certificate, from `cert_fingerprint`.
+    pub cert_fingerprint: Option<Vec<u8>>,
+    /// Hex-encoded HMAC over the request nonce and `cert_fingerprint`, from
+    /// `cert_fingerprint_sig`.
+    pub cert_fingerprint_sig: Option<Vec<u8>>,
+}
+
+/// Why a response body could not be parsed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The body is not valid UTF-8.
+    BadUtf8,
+}
+
+impl LicenseResponse {
+    /// Parse a response body, reading the verdict from `valid`.
+    pub fn parse(body: &[u8]) -> Result<Self, ParseError> {
+        Self::parse_with_valid_field(body, None)
+    }
+
+    /// Parse a response body, reading the verdict from `alternative_valid_field` when the body has
+    /// no `valid` field.
+    pub fn parse_with_valid_field(
+        body: &[u8],
+        alternative_valid_field: Option<&str>,
+    ) -> Result<Self, ParseError> {
+        Self::parse_with_valid_path(body, &[], alternative_valid_field)
+    }
+
+    /// Parse a response body, reading the verdict from the field at `valid_path` through nested
+    /// objects, such as `["data", "valid"]` for `{"data": {"valid": true}}`, or from
+    /// `alternative_valid_field` when there is none.
+    ///
+    /// An empty `valid_path` reads `valid`. A body missing any object on the path has no verdict.
+    pub fn parse_with_valid_path(
+        body: &[u8],
+        valid_path: &[&str],
+        alternative_valid_field: Option<&str>,
+    ) -> Result<Self, ParseError> {
+        Self::parse_with_field_names(body, &[], valid_path, alternative_valid_field)
+    }
+
+    /// Parse a response body like [`Self::parse_with_valid_path`], reading each field of
+    /// `renamed` under its given name instead of its [`ResponseField::default_name`].
+    ///
+    /// A renamed [`ResponseField::Valid`] only applies with an empty `valid_path`.
+    pub fn parse_with_field_names(
+        body: &[u8],
+        renamed: &[(ResponseField, &str)],
+        valid_path: &[&str],
+        alternative_valid_field: Option<&str>,
+    ) -> Result<Self, ParseError> {
+        let body = core::str::from_utf8(body).map_err(|_| ParseError::BadUtf8)?;
+        let name = |field: ResponseField| {
+            renamed
+                .iter()
+                .find(|(renamed_field, _)| *renamed_field == field)
+                .map_or(field.default_name(), |(_, name)| *name)
+        };
+        let str_field = |field| str_field(body, name(field)).map(|value| value.as_bytes().to_vec());
+        let valid_name = [name(ResponseField::Valid)];
+        let valid_path = if valid_path.is_empty() {
+            &valid_name[..]
+        } else {
+            valid_path
+        };
+
+        Ok(Self {
+            valid: nested_bool_field(body, valid_path)
+                .or_else(|| bool_field(body, alternative_valid_field?)),
+            issued_at: u64_field(body, name(ResponseField::IssuedAt)),
+            expires_at: u64_field(body, name(ResponseField::ExpiresAt)),
+            min_check_interval: u64_field(body, name(ResponseField::MinCheckInterval)),
+            not_before: u64_field(body, name(ResponseField::NotBefore)),
+            features: str_array_field(body, name(ResponseField::Features)).map(|features| {
+                features
+                    .into_iter()
+                    .map(|f| f.as_bytes().to_vec())
+                    .collect()
+            }),
+            key: str_field(ResponseField::Key),
+            cert_fingerprint: str_field(ResponseField::CertFingerprint),
+            cert_fingerprint_sig: str_field(ResponseField::CertFingerprintSig),
+        })
+    }
+}
+
+/// The text following the key `"field":` of the outermost object, with leading whitespace
+/// removed. Keys of nested objects and strings that are not keys, such as array items or values,
+/// never match.
+fn field_value<'a>(body: &'a str, field: &str) -> Option<&'a str> {
+    let bytes = body.as_bytes();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            b'"' => {
+                let end = i + 1 + body[i + 1..].find('"')?;
+                let after_string = body[end + 1..].trim_start();
+                if depth == 1 && &body[i + 1..end] == field {
+                    if let Some(value) = after_string.strip_prefix(':') {
+                        return Some(value.trim_start());
+                    }
+                }
+                i = end;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// A boolean field such as `"valid": true`. Any value but `true` reads as `false`.
+fn bool_field(body: &str, field: &str) -> Option<bool> {
+    Some(field_value(body, field)?.starts_with("true"))
+}
+
+/// A boolean field inside nested objects, such as `["data", "valid"]` for
+/// `"data": {"valid": true}`.
+fn nested_bool_field(body: &str, path: &[&str]) -> Option<bool> {
+    let (field, objects) = path.split_last()?;
+    let object = objects
+        .iter()
+        .try_fold(body, |object, name| object_field(object, name))?;
+    bool_field(object, field)
+}
+
+/// An object field such as `"data": {"valid": true}`, braces included.
+fn object_field<'a>(body: &'a str, field: &str) -> Option<&'a str> {
+    let value = field_value(body, field)?;
+    if !value.starts_with('{') {
+        return None;
+    }
+    let mut depth = 0usize;
+    let mut in_string = false;
+    for (i, c) in value.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '{' if !in_string => depth += 1,
+            '}' if !in_string => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&value[..=i]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// An unsigned integer field such as `"expires_at": 1700000000000`.
+fn u64_field(body: &str, field: &str) -> Option<u64> {
+    let value = field_value(body, field)?;
+    let digits = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    value[..digits].parse().ok()
+}
+
+/// A string field such as `"cert_fingerprint": "ab12..."`.
+fn str_field<'a>(body: &'a str, field: &str) -> Option<&'a str> {
+    let value = field_value(body, field)?.strip_prefix('"')?;
+    let end = value.find('"')?;
+    Some(&value[..end])
+}
+
+/// An array of strings such as `"features": ["a", "b"]`.
+fn str_array_field<'a>(body: &'a str, field: &str) -> Option<Vec<&'a str>> {
+    let value = field_value(body, field)?.strip_prefix('[')?;
+    let end = value.find(']')?;
+    value[..end]
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| item.strip_prefix('"')?.strip_suffix('"'))
+        .collect()
+}
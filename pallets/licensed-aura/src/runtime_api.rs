@@ -0,0 +1,91 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime API definition for the Licensed Aura pallet.
+//!
+//! These calls are read-only views over the pallet's license enforcement state, meant for
+//! dashboards and operator tooling.
+
+use crate::{
+    filter::HaltFilterConfig, AutoRecoveryStatus, EnforcementConfig, EnforcementDecision,
+    HaltReasonKind, LicensedAuraDebug,
+};
+use alloc::vec::Vec;
+use codec::Codec;
+use sp_consensus_aura::Slot;
+
+sp_api::decl_runtime_apis! {
+    /// Queries about license enforcement.
+    pub trait LicensedAuraApi<BlockNumber> where BlockNumber: Codec {
+        /// The enforcement health score, from 0 (failing) to 100 (healthy).
+        fn enforcement_health() -> u8;
+
+        /// A snapshot of all license and halt state. Never contains the raw license key.
+        fn debug_dump() -> LicensedAuraDebug<BlockNumber>;
+
+        /// The interval between offchain license checks actually in effect, in milliseconds.
+        fn effective_check_interval() -> u64;
+
+        /// The current and longest continuously licensed streaks, in blocks.
+        fn licensed_streaks() -> (BlockNumber, BlockNumber);
+
+        /// The `(pallet index, call index)` pairs of the calls that remain usable while halted.
+        fn halt_whitelist() -> Vec<(u8, u8)>;
+
+        /// The halt filter's current stance together with its allowed pallets and calls.
+        fn halt_filter_config() -> HaltFilterConfig;
+
+        /// The block at which a halted chain resumes on its own, if it will.
+        fn auto_recovery_block() -> Option<BlockNumber>;
+
+        /// The block at which auto-recovery would fire if the chain were at `current_block`.
+        fn auto_recovery_eta(current_block: BlockNumber) -> Option<BlockNumber>;
+
+        /// Whether a halted chain resumes on its own, and if not why.
+        fn auto_recovery_status() -> AutoRecoveryStatus<BlockNumber>;
+
+        /// What the next block's `on_initialize` would do to production. Changes nothing.
+        fn simulate_on_initialize_decision() -> EnforcementDecision;
+
+        /// How many reported license checks succeeded in a row during the ongoing halt.
+        fn consecutive_successes_while_halted() -> u32;
+
+        /// The current slot and the index of the authority expected to author it.
+        fn current_slot_and_author() -> (Slot, Option<u32>);
+
+        /// The license endpoint the offchain worker queries first.
+        fn license_endpoint() -> Option<Vec<u8>>;
+
+        /// A short fingerprint of the license key, if set. Never the key itself.
+        fn license_key_fingerprint() -> Option<[u8; 4]>;
+
+        /// The length of the license key in bytes, if set. Never the key itself.
+        fn license_key_len() -> Option<u32>;
+
+        /// The license enforcement policy in effect, overrides included. Never contains secrets.
+        fn enforcement_config() -> EnforcementConfig<BlockNumber>;
+
+        /// How many of the recently logged halts had each cause, by kind.
+        fn halt_reason_counts() -> Vec<(HaltReasonKind, u32)>;
+
+        /// How many of the last `window` recorded blocks each authority index authored, by index.
+        fn author_distribution(window: u32) -> Vec<(u32, u32)>;
+
+        /// The `(block, slot)` pairs of the last `window` recorded blocks, oldest first.
+        fn slot_history(window: u32) -> Vec<(BlockNumber, Slot)>;
+    }
+}
@@ -20,11 +20,77 @@
 #![cfg(test)]
 
 use super::pallet;
-use crate::mock::{build_ext_and_execute_test, Aura, MockDisabledValidators, System, Test};
-use codec::Encode;
-use frame_support::traits::OnInitialize;
-use sp_consensus_aura::{Slot, AURA_ENGINE_ID};
-use sp_runtime::{Digest, DigestItem};
+use crate::{
+    filter::{CheckNotHalted, HaltFilterStance, INVALID_TX_HALTED},
+    http_client::{
+        crc32, fetch_with_failover, fetch_with_retry, gunzip, hmac_sha256, request_signature,
+        route_endpoint, to_hex, BodyError, EndpointScheme, HttpFailure, HttpResponse,
+        LicenseRequest, RetryPolicy, HMAC_SECRET_STORAGE_KEY, LOCAL_AGENT_URL,
+    },
+    inherent::{self, LicenseProof, LICENSE_PROOF_STORAGE_KEY},
+    mock::{
+        build_ext, build_ext_and_execute_test, build_offchain_ext,
+        build_offchain_ext_and_execute_test, capture_logs, captured_logs, AlternativeValidField,
+        Aura, AuthorityUpdate, AutoRecoveryDelay, CheckEverySlots, CheckOncePerSession,
+        DisabledAuthorGrace, DuplicateAuthorities, EmptyBodyMeansValid, EndpointSelectionMode,
+        EnforceLicenseInherent, EventThrottleBlocks, FailClosed, FeatureChangeConfirmations,
+        HaltAdmin, HaltAllowedPallets, HaltEventDebounce, HaltFilter, HaltIfOcwAbsent,
+        HeartbeatInterval, LicensePublicKey, MinAuthorities, MissingValidField,
+        MockDisabledValidators, MockSlotDuration, OffchainHandles, OffchainTimeBudget,
+        OffchainWarmupChecks, OfflineKeyHashes, PathStyleLicenseUrls, RenewalUrl,
+        ReportBlockNumber, ReportNodeVersion, RequireSignedCertFingerprint, RuntimeCall,
+        RuntimeEvent, RuntimeOrigin, SameSlotAuthorStep, ScriptedHttpClient, SharedCheckResults,
+        System, Test, TimestampedLicenseUrls, ValidityFieldPath, GENESIS_HASH_HEX, INSTANCE_ID_HEX,
+        LICENSE_CACHE_TTL, LICENSE_CHECK_INTERVAL, LICENSE_PROOF_MAX_AGE, LICENSE_URL,
+        MAX_AUTH_TOKEN_LEN, MAX_CLOCK_DRIFT, MAX_RESPONSE_BYTES,
+    },
+    response::{LicenseResponse, ParseError, ResponseField},
+    AuthorityLicenseReport, AuthorityUpdatePolicy, AutoRecoveryStatus, DuplicateAuthorityPolicy,
+    EndedHalt, EndpointSelection, EnforcementConfig, EnforcementDecision, HaltReasonKind,
+    HaltRecord, HaltSource, LicenseCheckReport, LicenseRenewal, LicensedAuraDebug,
+    MissingValidFieldPolicy, OffchainError, OversizedAuthoritySetPolicy, AUTHOR_HISTORY_LEN,
+    CHECK_LOCK_DEADLINE_MS, CHECK_LOCK_STORAGE_KEY, DEFAULT_LICENSE_ENDPOINT, HALT_HISTORY_LEN,
+    HALT_LOG_LEN, INSTANCE_ID_STORAGE_KEY, MAX_CHECK_INTERVAL, MAX_LICENSE_ENDPOINTS,
+    MAX_LICENSE_ENDPOINT_LEN, MAX_LICENSE_FEATURE_LEN, MAX_RESPONSE_FIELD_NAME_LEN,
+    MIN_AUTO_RECOVERY_BLOCKS, MIN_CHECK_INTERVAL, REASON_FALLBACK, REASON_LICENSE_EXPIRED,
+    REASON_LICENSE_INVALID, REASON_LICENSE_NOT_YET_ACTIVE, REASON_LICENSE_QUORUM,
+    REASON_LICENSE_UNREACHABLE, REASON_OCW_ABSENT, REASON_SIMULATION_PREFIX, SLOT_HISTORY_LEN,
+    WARMUP_CHECKS_STORAGE_KEY, ZERO_SLOT_DURATION,
+};
+use codec::{Decode, DecodeLimit, Encode};
+use frame_support::{
+    assert_noop, assert_ok, assert_storage_noop,
+    dispatch::DispatchInfo,
+    inherent::{InherentData, ProvideInherent},
+    traits::{
+        Contains, FindAuthor, Get, OffchainWorker, OnFinalize, OnInitialize, OneSessionHandler,
+    },
+    BoundedVec,
+};
+use sp_consensus_aura::{ed25519::AuthorityId, ConsensusLog, Slot, AURA_ENGINE_ID};
+use sp_runtime::{
+    testing::UintAuthorityId,
+    traits::{SignedExtension, ValidateUnsigned},
+    transaction_validity::{InvalidTransaction, TransactionSource, TransactionValidityError},
+    Digest, DigestItem, DispatchError, DispatchResult, Percent, RuntimeAppPublic,
+};
+
+fn aura_events() -> Vec<pallet::Event<Test>> {
+    System::events()
+        .into_iter()
+        .filter_map(|record| match record.event {
+            RuntimeEvent::Aura(event) => Some(event),
+            _ => None,
+        })
+        .collect()
+}
+
+fn session_validators(accounts: &[u64]) -> Vec<(&u64, AuthorityId)> {
+    accounts
+        .iter()
+        .map(|a| (a, UintAuthorityId(*a).to_public_key()))
+        .collect()
+}
 
 #[test]
 fn initial_values() {
@@ -61,6 +127,51 @@ fn disabled_validators_cannot_author_blocks() {
     });
 }
 
+#[test]
+fn disabled_validators_author_empty_blocks_within_the_grace() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        DisabledAuthorGrace::set(3);
+        MockDisabledValidators::disable_validator(1);
+        let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![1] });
+        let timestamp = RuntimeCall::Timestamp(pallet_timestamp::Call::set { now: 1 });
+
+        // Slots 1 and 5 belong to the disabled validator at index 1.
+        for (block, slot) in [(42, 1), (44, 5)] {
+            System::reset_events();
+            initialize_block_at_slot(block, slot);
+            assert!(Aura::is_disabled_author_block());
+            assert!(!HaltFilter::contains(&remark));
+            assert!(HaltFilter::contains(&timestamp));
+            assert_eq!(
+                aura_events(),
+                vec![pallet::Event::DisabledAuthorBlockEmptied { authority_index: 1 }]
+            );
+            Aura::on_finalize(block);
+        }
+        assert_eq!(pallet::DisabledAuthorSince::<Test>::get(1), Some(42));
+
+        // The next author's block is not affected.
+        initialize_block_at_slot(45, 6);
+        assert!(!Aura::is_disabled_author_block());
+        assert!(HaltFilter::contains(&remark));
+    });
+}
+
+#[test]
+#[should_panic(
+    expected = "Validator with index 1 is disabled and should not be attempting to author blocks."
+)]
+fn disabled_validators_cannot_author_blocks_past_the_grace() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        DisabledAuthorGrace::set(3);
+        MockDisabledValidators::disable_validator(1);
+        initialize_block_at_slot(42, 1);
+        Aura::on_finalize(42);
+
+        initialize_block_at_slot(45, 5);
+    });
+}
+
 #[test]
 #[should_panic(expected = "Slot must increase")]
 fn pallet_requires_slot_to_increase_unless_allowed() {
@@ -81,6 +192,63 @@ fn pallet_requires_slot_to_increase_unless_allowed() {
     });
 }
 
+#[test]
+#[should_panic(
+    expected = "Slot must increase: current slot 1, new slot 1 (AllowMultipleBlocksPerSlot = false)"
+)]
+fn repeated_slot_panic_names_both_slots_and_the_setting() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let pre_digest = Digest {
+            logs: vec![DigestItem::PreRuntime(
+                AURA_ENGINE_ID,
+                Slot::from(1).encode(),
+            )],
+        };
+        System::initialize(&42, &System::parent_hash(), &pre_digest);
+
+        Aura::on_initialize(42);
+        Aura::on_initialize(42);
+    });
+}
+
+#[test]
+#[should_panic(expected = "current slot 5, new slot 3 (AllowMultipleBlocksPerSlot = true)")]
+fn decreasing_slot_panic_names_both_slots_and_the_setting() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        crate::mock::AllowMultipleBlocksPerSlot::set(true);
+        pallet::CurrentSlot::<Test>::put(Slot::from(5));
+        let pre_digest = Digest {
+            logs: vec![DigestItem::PreRuntime(
+                AURA_ENGINE_ID,
+                Slot::from(3).encode(),
+            )],
+        };
+        System::initialize(&42, &System::parent_hash(), &pre_digest);
+
+        Aura::on_initialize(42);
+    });
+}
+
+#[test]
+fn try_state_reports_a_decreasing_slot_without_panicking() {
+    build_ext(vec![0, 1, 2, 3]).execute_with(|| {
+        pallet::CurrentSlot::<Test>::put(Slot::from(5));
+        let pre_digest = Digest {
+            logs: vec![DigestItem::PreRuntime(
+                AURA_ENGINE_ID,
+                Slot::from(3).encode(),
+            )],
+        };
+        System::initialize(&42, &System::parent_hash(), &pre_digest);
+
+        capture_logs();
+        assert_eq!(Aura::do_try_state(), Err("Slot must not decrease".into()));
+        assert!(captured_logs().iter().any(|(_, line)| {
+            line.contains("current slot 5, new slot 3 (AllowMultipleBlocksPerSlot = false)")
+        }));
+    });
+}
+
 #[test]
 fn pallet_can_allow_unchanged_slot() {
     build_ext_and_execute_test(vec![0, 1, 2, 3], || {
@@ -128,3 +296,5964 @@ fn pallet_always_rejects_decreasing_slot() {
         Aura::on_initialize(43);
     });
 }
+
+#[test]
+fn oversized_session_set_is_truncated_under_truncate_policy() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        crate::mock::OversizedPolicy::set(OversizedAuthoritySetPolicy::Truncate);
+
+        let accounts: Vec<u64> = (0..12).collect();
+        let validators = session_validators(&accounts);
+        let session = validators.iter().map(|(a, k)| (*a, k.clone()));
+        Aura::on_new_session(true, session.clone(), session);
+
+        let authorities = pallet::Authorities::<Test>::get();
+        assert_eq!(authorities.len(), 10);
+        assert_eq!(
+            authorities.into_inner(),
+            validators[..10]
+                .iter()
+                .map(|(_, k)| k.clone())
+                .collect::<Vec<_>>()
+        );
+    });
+}
+
+#[test]
+fn oversized_session_set_keeps_previous_set_under_reject_policy() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        crate::mock::OversizedPolicy::set(OversizedAuthoritySetPolicy::Reject);
+        let previous = pallet::Authorities::<Test>::get();
+
+        let accounts: Vec<u64> = (0..12).collect();
+        let validators = session_validators(&accounts);
+        let session = validators.iter().map(|(a, k)| (*a, k.clone()));
+        Aura::on_new_session(true, session.clone(), session);
+
+        assert_eq!(pallet::Authorities::<Test>::get(), previous);
+        assert_eq!(Aura::authorities_len(), 4);
+    });
+}
+
+#[test]
+fn duplicate_session_authorities_are_dropped_under_dedup_policy() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let validators = session_validators(&[4, 5, 4, 6, 5]);
+        let session = validators.iter().map(|(a, k)| (*a, k.clone()));
+        Aura::on_new_session(true, session.clone(), session);
+
+        assert_eq!(
+            pallet::Authorities::<Test>::get().into_inner(),
+            authority_keys(&[4, 5, 6])
+        );
+    });
+}
+
+#[test]
+fn duplicate_session_authorities_keep_previous_set_under_reject_policy() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        DuplicateAuthorities::set(DuplicateAuthorityPolicy::Reject);
+        let previous = pallet::Authorities::<Test>::get();
+
+        let validators = session_validators(&[4, 5, 4]);
+        let session = validators.iter().map(|(a, k)| (*a, k.clone()));
+        Aura::on_new_session(true, session.clone(), session);
+
+        assert_eq!(pallet::Authorities::<Test>::get(), previous);
+    });
+}
+
+#[test]
+fn duplicate_genesis_authorities_are_dropped_under_dedup_policy() {
+    build_ext(vec![]).execute_with(|| {
+        Aura::initialize_authorities(&authority_keys(&[0, 1, 0, 2]));
+        assert_eq!(
+            pallet::Authorities::<Test>::get().into_inner(),
+            authority_keys(&[0, 1, 2])
+        );
+    });
+}
+
+#[test]
+#[should_panic(expected = "Initial authority set must not contain duplicates")]
+fn duplicate_genesis_authorities_are_refused_under_reject_policy() {
+    build_ext(vec![]).execute_with(|| {
+        DuplicateAuthorities::set(DuplicateAuthorityPolicy::Reject);
+        Aura::initialize_authorities(&authority_keys(&[0, 1, 0]));
+    });
+}
+
+#[test]
+fn try_state_rejects_duplicate_authorities() {
+    build_ext(vec![0, 1, 2, 3]).execute_with(|| {
+        assert_ok!(Aura::do_try_state());
+
+        let duplicated = BoundedVec::truncate_from(authority_keys(&[0, 1, 0, 3]));
+        pallet::Authorities::<Test>::put(duplicated);
+        assert_eq!(
+            Aura::do_try_state(),
+            Err("Authorities must be unique.".into())
+        );
+    });
+}
+
+fn authority_keys(accounts: &[u64]) -> Vec<AuthorityId> {
+    accounts
+        .iter()
+        .map(|a| UintAuthorityId(*a).to_public_key())
+        .collect()
+}
+
+#[test]
+fn sudo_set_authorities_swaps_the_whole_set_in_order() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        let new = authority_keys(&[5, 2, 4]);
+        assert_noop!(
+            Aura::sudo_set_authorities(RuntimeOrigin::signed(1), new.clone()),
+            DispatchError::BadOrigin
+        );
+
+        assert_ok!(Aura::sudo_set_authorities(
+            RuntimeOrigin::root(),
+            new.clone()
+        ));
+        assert_eq!(pallet::Authorities::<Test>::get().into_inner(), new);
+        assert!(matches!(
+            aura_events().last(),
+            Some(pallet::Event::AuthoritiesChanged { added, removed })
+                if added.len() == 2 && removed.len() == 3
+        ));
+        assert!(System::digest().logs.contains(&DigestItem::Consensus(
+            AURA_ENGINE_ID,
+            ConsensusLog::AuthoritiesChange(new).encode(),
+        )));
+    });
+}
+
+#[test]
+fn sudo_set_authorities_rejects_invalid_sets() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let set = |accounts: &[u64]| {
+            Aura::sudo_set_authorities(RuntimeOrigin::root(), authority_keys(accounts))
+        };
+        assert_noop!(set(&[]), pallet::Error::<Test>::EmptyAuthoritySet);
+        let too_many: Vec<u64> = (0..11).collect();
+        assert_noop!(set(&too_many), pallet::Error::<Test>::TooManyAuthorities);
+        assert_noop!(set(&[0, 1, 0]), pallet::Error::<Test>::DuplicateAuthority);
+
+        assert_eq!(
+            pallet::Authorities::<Test>::get().into_inner(),
+            authority_keys(&[0, 1, 2, 3])
+        );
+    });
+}
+
+#[test]
+fn sudo_set_authorities_honours_the_minimum_set_size() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        MinAuthorities::set(3);
+        let set = |accounts: &[u64]| {
+            Aura::sudo_set_authorities(RuntimeOrigin::root(), authority_keys(accounts))
+        };
+        assert_noop!(set(&[4, 5]), pallet::Error::<Test>::TooFewAuthorities);
+
+        // A governance-driven rotation to a set of the minimum size.
+        assert_ok!(set(&[4, 5, 6]));
+        assert_eq!(
+            pallet::Authorities::<Test>::get().into_inner(),
+            authority_keys(&[4, 5, 6])
+        );
+        assert_ok!(set(&[6, 5, 4, 7]));
+        assert_eq!(Aura::authorities_len(), 4);
+    });
+}
+
+#[test]
+fn preserving_indices_keeps_the_author_schedule_of_remaining_authorities() {
+    // Authority 3 leaves, 4 joins and the session shuffles the rest.
+    for (policy, expected, unchanged_slots) in [
+        (AuthorityUpdatePolicy::Replace, [2, 4, 0, 1], 0),
+        (AuthorityUpdatePolicy::PreserveIndices, [0, 1, 2, 4], 3),
+    ] {
+        build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+            AuthorityUpdate::set(policy);
+            let before = pallet::Authorities::<Test>::get();
+
+            let validators = session_validators(&[2, 4, 0, 1]);
+            let session = validators.iter().map(|(a, k)| (*a, k.clone()));
+            Aura::on_new_session(true, session.clone(), session);
+
+            let after = pallet::Authorities::<Test>::get();
+            assert_eq!(after.to_vec(), authority_keys(&expected));
+
+            // Slot `s` is authored by authority `s % 4` under both sets.
+            let unchanged = (0..4)
+                .filter(|&index| before[index] == after[index])
+                .count();
+            assert_eq!(unchanged, unchanged_slots, "{:?}", policy);
+        });
+    }
+}
+
+#[test]
+fn authority_diff_is_sorted_and_deduplicated() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let sorted = |accounts: &[u64]| {
+            let mut keys = authority_keys(accounts);
+            keys.sort_by_key(|key| key.encode());
+            keys
+        };
+        let diff = Aura::authority_diff(
+            &authority_keys(&[0, 1, 2, 3]),
+            &authority_keys(&[5, 4, 4, 1, 0]),
+        );
+
+        assert_eq!(diff, (sorted(&[4, 5]), sorted(&[2, 3])));
+        assert_eq!(
+            Aura::authority_diff(
+                &authority_keys(&[3, 2, 1, 0]),
+                &authority_keys(&[0, 4, 1, 5, 5])
+            ),
+            diff
+        );
+    });
+}
+
+#[test]
+fn authority_change_event_does_not_depend_on_session_order() {
+    let changed_event = |order: &[u64]| {
+        let mut event = None;
+        build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+            System::set_block_number(1);
+            let validators = session_validators(order);
+            let session = validators.iter().map(|(a, k)| (*a, k.clone()));
+            Aura::on_new_session(true, session.clone(), session);
+            event = aura_events().pop();
+        });
+        event.unwrap()
+    };
+
+    let event = changed_event(&[5, 0, 4, 1]);
+    assert_eq!(event, changed_event(&[1, 4, 0, 5]));
+    let pallet::Event::AuthoritiesChanged { added, removed } = event else {
+        panic!("expected an authority change, got {:?}", event);
+    };
+    assert_eq!(added.len(), 2);
+    assert_eq!(removed.len(), 2);
+}
+
+#[test]
+fn preserving_indices_drops_gone_authorities_and_appends_new_ones() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let last = authority_keys(&[0, 1, 2, 3]);
+        assert_eq!(
+            Aura::preserve_authority_indices(&last, authority_keys(&[5, 3, 1, 4])),
+            authority_keys(&[1, 3, 5, 4])
+        );
+        // Authorities after a removed one move up, as they must.
+        assert_eq!(
+            Aura::preserve_authority_indices(&last, authority_keys(&[3, 2, 1])),
+            authority_keys(&[1, 2, 3])
+        );
+        assert_eq!(Aura::preserve_authority_indices(&last, last.clone()), last);
+    });
+}
+
+#[test]
+fn license_usage_thresholds_are_emitted_once_each() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        let report = LicenseCheckReport {
+            valid: true,
+            issued_at: Some(1_000),
+            expires_at: Some(11_000),
+            min_check_interval: None,
+            not_before: None,
+            features: None,
+        };
+
+        // 40% elapsed: nothing yet.
+        pallet_timestamp::Now::<Test>::put(5_000);
+        assert_ok!(Aura::offchain_worker_report_check(
+            RuntimeOrigin::none(),
+            report.clone()
+        ));
+        assert!(aura_events().is_empty());
+
+        // 50% elapsed, reported twice.
+        pallet_timestamp::Now::<Test>::put(6_000);
+        assert_ok!(Aura::offchain_worker_report_check(
+            RuntimeOrigin::none(),
+            report.clone()
+        ));
+        assert_ok!(Aura::offchain_worker_report_check(
+            RuntimeOrigin::none(),
+            report.clone()
+        ));
+
+        // 85% elapsed, reported twice.
+        pallet_timestamp::Now::<Test>::put(9_500);
+        assert_ok!(Aura::offchain_worker_report_check(
+            RuntimeOrigin::none(),
+            report.clone()
+        ));
+        assert_ok!(Aura::offchain_worker_report_check(
+            RuntimeOrigin::none(),
+            report
+        ));
+
+        assert_eq!(
+            aura_events(),
+            vec![
+                pallet::Event::LicenseUsageThreshold { percent: 50 },
+                pallet::Event::LicenseUsageThreshold { percent: 80 },
+            ]
+        );
+        assert_eq!(pallet::LicenseUsageThresholdReached::<Test>::get(), 80);
+    });
+}
+
+#[test]
+fn license_usage_thresholds_rearm_for_a_new_term() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        pallet_timestamp::Now::<Test>::put(9_500);
+        assert_ok!(Aura::offchain_worker_report_check(
+            RuntimeOrigin::none(),
+            LicenseCheckReport {
+                valid: true,
+                issued_at: Some(1_000),
+                expires_at: Some(11_000),
+                min_check_interval: None,
+                not_before: None,
+                features: None,
+            }
+        ));
+        assert_eq!(aura_events().len(), 2);
+
+        // Renewed: the new term is barely started.
+        System::reset_events();
+        assert_ok!(Aura::offchain_worker_report_check(
+            RuntimeOrigin::none(),
+            LicenseCheckReport {
+                valid: true,
+                issued_at: Some(9_000),
+                expires_at: Some(109_000),
+                min_check_interval: None,
+                not_before: None,
+                features: None,
+            }
+        ));
+        assert!(aura_events().is_empty());
+        assert_eq!(pallet::LicenseUsageThresholdReached::<Test>::get(), 0);
+        assert_eq!(pallet::LicenseTerm::<Test>::get(), Some((9_000, 109_000)));
+    });
+}
+
+const RENEWAL_URL: &str = "http://localhost:3000/renew";
+
+/// Make a keystore key the only authority and put the license term past its last usage
+/// threshold, so that the next valid check renews it.
+fn setup_renewal_due() -> AuthorityId {
+    RenewalUrl::set(Some(RENEWAL_URL));
+    let authority = AuthorityId::generate_pair(None);
+    pallet::Authorities::<Test>::put(BoundedVec::truncate_from(vec![authority.clone()]));
+    pallet::LicenseTerm::<Test>::put((1_000, 101_000));
+    pallet::LicenseUsageThresholdReached::<Test>::put(80);
+    assert!(Aura::is_renewal_due());
+    authority
+}
+
+fn submitted_renewals(
+    handles: &OffchainHandles,
+) -> Vec<(
+    LicenseRenewal<AuthorityId>,
+    <AuthorityId as RuntimeAppPublic>::Signature,
+)> {
+    handles
+        .submitted_calls()
+        .into_iter()
+        .filter_map(|call| match call {
+            RuntimeCall::Aura(pallet::Call::offchain_worker_report_renewal {
+                renewal,
+                signature,
+            }) => Some((renewal, signature)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn offchain_worker_renews_a_license_near_expiry() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        System::set_block_number(1);
+        let authority = setup_renewal_due();
+
+        handles.set_timestamp(90_000);
+        handles.expect_license_request(br#"{"valid":true}"#);
+        handles.expect_post(
+            RENEWAL_URL,
+            br#"{"key":"test-license-key"}"#,
+            br#"{"valid":true,"key":"renewed-key","issued_at":95000,"expires_at":200000}"#,
+        );
+        Aura::offchain_worker(1);
+
+        let renewals = submitted_renewals(&handles);
+        assert_eq!(renewals.len(), 1);
+        let (renewal, signature) = renewals[0].clone();
+        assert_eq!(
+            renewal,
+            LicenseRenewal {
+                authority,
+                license_key: b"renewed-key".to_vec(),
+                issued_at: 95_000,
+                expires_at: 200_000,
+            }
+        );
+        let call = pallet::Call::offchain_worker_report_renewal {
+            renewal: renewal.clone(),
+            signature: signature.clone(),
+        };
+        assert!(Aura::validate_unsigned(TransactionSource::Local, &call).is_ok());
+
+        assert_ok!(Aura::offchain_worker_report_renewal(
+            RuntimeOrigin::none(),
+            renewal,
+            signature
+        ));
+        assert_eq!(
+            pallet::LicenseKey::<Test>::get().unwrap().to_vec(),
+            b"renewed-key".to_vec()
+        );
+        assert_eq!(pallet::LicenseTerm::<Test>::get(), Some((95_000, 200_000)));
+        assert_eq!(pallet::LicenseUsageThresholdReached::<Test>::get(), 0);
+        assert_eq!(pallet::RecheckNow::<Test>::get(), Some(1));
+        assert!(!Aura::is_renewal_due());
+        assert_eq!(
+            aura_events(),
+            vec![pallet::Event::LicenseRenewed {
+                expires_at: 200_000
+            }]
+        );
+    });
+}
+
+#[test]
+fn failed_license_renewal_leaves_the_license_unchanged() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        System::set_block_number(1);
+        setup_renewal_due();
+
+        handles.set_timestamp(90_000);
+        handles.expect_license_request(br#"{"valid":true}"#);
+        handles.expect_post(
+            RENEWAL_URL,
+            br#"{"key":"test-license-key"}"#,
+            br#"{"error":"not renewable"}"#,
+        );
+        Aura::offchain_worker(1);
+
+        assert!(submitted_renewals(&handles).is_empty());
+        assert_eq!(
+            pallet::LicenseKey::<Test>::get().unwrap().to_vec(),
+            b"test-license-key".to_vec()
+        );
+        assert_eq!(pallet::LicenseTerm::<Test>::get(), Some((1_000, 101_000)));
+
+        // A refusal is not retried within the same check either.
+        let mut client = ScriptedHttpClient::new(vec![Ok(HttpResponse {
+            code: 403,
+            body: vec![],
+            content_encoding: None,
+        })]);
+        let request = LicenseRequest::new(RENEWAL_URL.into());
+        assert_eq!(
+            Aura::renew_license(
+                &mut client,
+                &request,
+                "test-license-key",
+                None,
+                "nonce",
+                90_000
+            ),
+            Err(OffchainError::RenewalRejected)
+        );
+        assert_eq!(
+            client.posted_bodies,
+            vec![br#"{"key":"test-license-key"}"#.to_vec()]
+        );
+        assert!(submitted_renewals(&handles).is_empty());
+    });
+}
+
+#[test]
+fn license_renewal_must_be_valid_and_attested() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        System::set_block_number(1);
+        setup_renewal_due();
+        let request = LicenseRequest::new(RENEWAL_URL.into());
+        let renew = |body: &[u8]| {
+            let mut client = ScriptedHttpClient::new(vec![http_ok(200, body)]);
+            Aura::renew_license(
+                &mut client,
+                &request,
+                "test-license-key",
+                None,
+                "nonce",
+                90_000,
+            )
+        };
+
+        // A bare key and expiry, as an intermediary could answer, is not a renewal.
+        assert_eq!(
+            renew(br#"{"key":"swapped-key","expires_at":200000}"#),
+            Err(OffchainError::UnverifiedRenewal)
+        );
+        assert_eq!(
+            renew(br#"{"valid":false,"key":"swapped-key","expires_at":200000}"#),
+            Err(OffchainError::UnverifiedRenewal)
+        );
+
+        // With a pinned certificate, the renewal must attest it like a check.
+        pin_fingerprint();
+        assert_eq!(
+            renew(br#"{"valid":true,"key":"swapped-key","expires_at":200000}"#),
+            Err(OffchainError::UnverifiedRenewal)
+        );
+        let attested = format!(
+            r#"{{"valid":true,"key":"renewed-key","expires_at":200000,"cert_fingerprint":"{}"}}"#,
+            to_hex(&PINNED_FINGERPRINT)
+        );
+        assert_ok!(renew(attested.as_bytes()));
+        assert_eq!(submitted_renewals(&handles).len(), 1);
+    });
+}
+
+#[test]
+fn license_keys_needing_json_escapes_are_not_sent_for_renewal() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |_| {
+        setup_renewal_due();
+        let request = LicenseRequest::new(RENEWAL_URL.into());
+        for key in ["key\",\"admin\":true", "back\\slash", "line\nbreak"] {
+            let mut client = ScriptedHttpClient::new(vec![]);
+            assert_eq!(
+                Aura::renew_license(&mut client, &request, key, None, "nonce", 90_000),
+                Err(OffchainError::UnsafeRenewalKey)
+            );
+            assert!(client.posted_bodies.is_empty());
+        }
+    });
+}
+
+#[test]
+fn license_renewals_must_extend_the_term_and_come_from_an_authority() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |_| {
+        System::set_block_number(1);
+        let authority = setup_renewal_due();
+        let renew = |authority: &AuthorityId, expires_at: u64| {
+            let renewal = LicenseRenewal {
+                authority: authority.clone(),
+                license_key: b"renewed-key".to_vec(),
+                issued_at: 95_000,
+                expires_at,
+            };
+            let signature = renewal
+                .using_encoded(|payload| authority.sign(&payload))
+                .unwrap();
+            Aura::offchain_worker_report_renewal(RuntimeOrigin::none(), renewal, signature)
+        };
+
+        assert_noop!(
+            renew(&authority, 101_000),
+            pallet::Error::<Test>::StaleLicenseRenewal
+        );
+        assert_noop!(
+            renew(&AuthorityId::generate_pair(None), 200_000),
+            pallet::Error::<Test>::NotAnAuthority
+        );
+        assert_ok!(renew(&authority, 200_000));
+    });
+}
+
+fn reported_checks(handles: &OffchainHandles) -> usize {
+    handles
+        .submitted_calls()
+        .into_iter()
+        .filter(|call| {
+            matches!(
+                call,
+                RuntimeCall::Aura(pallet::Call::offchain_worker_report_check { .. })
+            )
+        })
+        .count()
+}
+
+#[test]
+fn forced_fresh_check_bypasses_the_cache_exactly_once() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        // A valid check populates the cache.
+        handles.set_timestamp(60_000);
+        handles.expect_license_request(br#"{"valid":true}"#);
+        Aura::offchain_worker(1);
+        assert_eq!(reported_checks(handles), 1);
+
+        // Within the TTL the cached result is used and the server is not contacted.
+        handles.set_timestamp(100_000);
+        Aura::offchain_worker(2);
+        assert_eq!(reported_checks(handles), 1);
+
+        // A forced fresh check ignores the cache.
+        assert_ok!(Aura::sudo_force_fresh_check(RuntimeOrigin::root()));
+        handles.set_timestamp(140_000);
+        handles.expect_license_request(br#"{"valid":true}"#);
+        Aura::offchain_worker(3);
+        assert_eq!(reported_checks(handles), 2);
+
+        // Once the report lands the flag is cleared and caching resumes.
+        assert_ok!(Aura::offchain_worker_report_check(
+            RuntimeOrigin::none(),
+            LicenseCheckReport {
+                valid: true,
+                issued_at: None,
+                expires_at: None,
+                min_check_interval: None,
+                not_before: None,
+                features: None,
+            }
+        ));
+        assert!(!pallet::BypassCacheOnce::<Test>::get());
+        handles.set_timestamp(180_000);
+        Aura::offchain_worker(4);
+        assert_eq!(reported_checks(handles), 2);
+    });
+}
+
+#[test]
+fn force_fresh_check_requires_root() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_noop!(
+            Aura::sudo_force_fresh_check(RuntimeOrigin::signed(1)),
+            DispatchError::BadOrigin
+        );
+    });
+}
+
+fn report_check(valid: bool) {
+    assert_ok!(Aura::offchain_worker_report_check(
+        RuntimeOrigin::none(),
+        LicenseCheckReport {
+            valid,
+            issued_at: None,
+            expires_at: None,
+            min_check_interval: None,
+            not_before: None,
+            features: None,
+        }
+    ));
+}
+
+#[test]
+fn health_score_is_full_when_all_checks_pass() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        for block in 1..=5 {
+            System::set_block_number(block);
+            report_check(true);
+        }
+        assert_eq!(Aura::enforcement_health(), 100);
+        assert!(aura_events().is_empty());
+    });
+}
+
+#[test]
+fn health_score_is_zero_when_all_checks_fail() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        for block in 1..=5 {
+            System::set_block_number(block);
+            report_check(false);
+        }
+        assert_eq!(Aura::enforcement_health(), 0);
+        let mut expected = vec![
+            pallet::Event::EnforcementHealthThresholdCrossed {
+                threshold: 50,
+                score: 0,
+            },
+            pallet::Event::EnforcementHealthThresholdCrossed {
+                threshold: 90,
+                score: 0,
+            },
+        ];
+        expected.extend(vec![pallet::Event::LicenseCheckFailed { suppressed: 0 }; 5]);
+        assert_eq!(aura_events(), expected);
+    });
+}
+
+#[test]
+fn health_score_reflects_mixed_history() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        report_check(true);
+        System::set_block_number(2);
+        report_check(false);
+        assert_eq!(Aura::enforcement_health(), 50);
+
+        System::set_block_number(3);
+        report_check(true);
+        System::set_block_number(4);
+        report_check(true);
+        assert_eq!(Aura::enforcement_health(), 75);
+
+        assert_eq!(
+            aura_events(),
+            vec![
+                pallet::Event::EnforcementHealthThresholdCrossed {
+                    threshold: 90,
+                    score: 50
+                },
+                pallet::Event::LicenseCheckFailed { suppressed: 0 },
+            ]
+        );
+    });
+}
+
+#[test]
+fn health_score_penalizes_staleness_and_halts() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let history = [true, true, true, true];
+        assert_eq!(Aura::compute_health_score(&history, 0, false), 100);
+        // Half of `HealthStalenessBlocks` costs a quarter of the score.
+        assert_eq!(Aura::compute_health_score(&history, 50, false), 75);
+        // The staleness penalty is capped at half.
+        assert_eq!(Aura::compute_health_score(&history, 1_000, false), 50);
+        assert_eq!(Aura::compute_health_score(&history, 0, true), 0);
+        assert_eq!(Aura::compute_health_score(&[], 0, false), 100);
+    });
+}
+
+#[test]
+fn health_score_decays_while_reports_stop() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        report_check(true);
+        assert_eq!(Aura::enforcement_health(), 100);
+
+        // No report for half of `HealthStalenessBlocks`.
+        System::set_block_number(51);
+        assert_eq!(Aura::enforcement_health(), 75);
+        System::set_block_number(1_000);
+        assert_eq!(Aura::enforcement_health(), 50);
+        assert_eq!(Aura::debug_dump().enforcement_health, 50);
+
+        // A fresh report lifts the penalty and crosses back above the threshold it fell under.
+        report_check(true);
+        assert_eq!(Aura::enforcement_health(), 100);
+        assert_eq!(
+            aura_events(),
+            vec![pallet::Event::EnforcementHealthThresholdCrossed {
+                threshold: 90,
+                score: 100
+            }]
+        );
+
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert_eq!(Aura::enforcement_health(), 0);
+    });
+}
+
+#[test]
+fn offchain_worker_is_a_noop_without_authorities() {
+    // No `do_try_state` here: an empty authority set is exactly what it rejects.
+    let (mut ext, handles) = build_offchain_ext(vec![]);
+    ext.execute_with(|| {
+        assert_eq!(Aura::authorities_len(), 0);
+
+        // No request is expected, so any HTTP call would panic.
+        handles.set_timestamp(60_000);
+        Aura::offchain_worker(1);
+
+        assert!(handles.submitted_calls().is_empty());
+        assert!(!Aura::is_halted());
+    });
+}
+
+const RETRY_POLICY: RetryPolicy = RetryPolicy {
+    max_attempts: 3,
+    initial_backoff_ms: 100,
+};
+
+fn license_request() -> LicenseRequest {
+    LicenseRequest::new(LICENSE_URL.into())
+}
+
+fn http_ok(code: u16, body: &[u8]) -> Result<HttpResponse, HttpFailure> {
+    Ok(HttpResponse {
+        code,
+        body: body.to_vec(),
+        content_encoding: None,
+    })
+}
+
+#[test]
+fn fetch_succeeds_after_two_failures() {
+    let mut client = ScriptedHttpClient::new(vec![
+        Err(HttpFailure::DeadlineReached),
+        http_ok(503, b""),
+        http_ok(200, br#"{"valid":true}"#),
+    ]);
+
+    let outcome = fetch_with_retry(&mut client, &license_request(), RETRY_POLICY);
+
+    assert_eq!(outcome, http_ok(200, br#"{"valid":true}"#));
+    assert_eq!(client.requests.len(), 3);
+    assert_eq!(client.sleeps, vec![100, 200]);
+}
+
+#[test]
+fn fetch_gives_up_after_all_attempts_fail() {
+    let mut client = ScriptedHttpClient::new(vec![
+        Err(HttpFailure::SendFailed),
+        Err(HttpFailure::RequestFailed),
+        Err(HttpFailure::DeadlineReached),
+    ]);
+
+    let outcome = fetch_with_retry(&mut client, &license_request(), RETRY_POLICY);
+
+    assert_eq!(outcome, Err(HttpFailure::DeadlineReached));
+    assert_eq!(client.requests.len(), 3);
+    assert_eq!(client.sleeps, vec![100, 200]);
+    assert!(client.script.is_empty());
+}
+
+#[test]
+fn failing_over_does_not_log_the_license_key() {
+    capture_logs();
+    let mut client = ScriptedHttpClient::new(vec![
+        Err(HttpFailure::SendFailed),
+        Err(HttpFailure::SendFailed),
+        Err(HttpFailure::SendFailed),
+        http_ok(200, br#"{"valid":true}"#),
+    ]);
+    let requests = [license_request(), license_request()];
+
+    let outcome = fetch_with_failover(&mut client, &requests, RETRY_POLICY);
+
+    assert_eq!(outcome, http_ok(200, br#"{"valid":true}"#));
+    let logs = captured_logs();
+    assert!(logs
+        .iter()
+        .any(|(_, line)| line.contains("License endpoint #0 unavailable")));
+    assert!(logs
+        .iter()
+        .all(|(_, line)| !line.contains("test-license-key")));
+}
+
+#[test]
+fn fetch_returns_immediate_success_without_retrying() {
+    let mut client = ScriptedHttpClient::new(vec![http_ok(200, br#"{"valid":false}"#)]);
+
+    let outcome = fetch_with_retry(&mut client, &license_request(), RETRY_POLICY);
+
+    assert_eq!(outcome, http_ok(200, br#"{"valid":false}"#));
+    assert_eq!(client.requests, vec![license_request()]);
+    assert!(client.sleeps.is_empty());
+}
+
+#[test]
+fn fetch_does_not_retry_client_errors() {
+    let mut client = ScriptedHttpClient::new(vec![http_ok(404, b"")]);
+
+    let outcome = fetch_with_retry(&mut client, &license_request(), RETRY_POLICY);
+
+    assert_eq!(outcome, http_ok(404, b""));
+    assert_eq!(client.requests.len(), 1);
+    assert!(client.sleeps.is_empty());
+}
+
+#[test]
+fn hmac_matches_rfc_4231_vectors() {
+    assert_eq!(
+        to_hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+        "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+    // Keys longer than the block size are hashed first.
+    assert_eq!(
+        to_hex(&hmac_sha256(
+            &[0xaa; 131],
+            b"Test Using Larger Than Block-Size Key - Hash Key First"
+        )),
+        "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+    );
+}
+
+#[test]
+fn request_signature_is_stable_for_fixed_inputs() {
+    let signature = request_signature(b"secret", b"test-license-key", "00ff", "1700000000000");
+
+    assert_eq!(
+        signature,
+        hmac_sha256(b"secret", b"test-license-key00ff1700000000000")
+    );
+    assert_eq!(
+        signature,
+        request_signature(b"secret", b"test-license-key", "00ff", "1700000000000")
+    );
+    assert_ne!(
+        signature,
+        request_signature(b"secret", b"test-license-key", "00ff", "1700000000001")
+    );
+}
+
+#[test]
+fn license_request_carries_hmac_headers_when_secret_is_set() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        handles.set_timestamp(60_000);
+        handles.offchain.write().seed = [0x5a; 32];
+        sp_io::offchain::local_storage_set(
+            sp_core::offchain::StorageKind::PERSISTENT,
+            HMAC_SECRET_STORAGE_KEY,
+            b"shared-secret",
+        );
+
+        let nonce = to_hex(&[0x5a; 16]);
+        let mut expected = license_request();
+        expected.sign(b"shared-secret", b"test-license-key", &nonce, 60_000);
+        assert_eq!(
+            expected.headers[2].1,
+            to_hex(&request_signature(
+                b"shared-secret",
+                b"test-license-key",
+                &nonce,
+                "60000"
+            ))
+        );
+
+        handles.expect_request_with_headers(LICENSE_URL, expected.headers, br#"{"valid":true}"#);
+        Aura::offchain_worker(1);
+
+        assert_eq!(reported_checks(handles), 1);
+    });
+}
+
+fn unreachable_server() -> ScriptedHttpClient {
+    ScriptedHttpClient::new(vec![Err(HttpFailure::SendFailed); 3])
+}
+
+fn allow_offline_keys(keys: &[&[u8]]) {
+    let hashes: Vec<_> = keys.iter().map(|key| LicenseProof::key_hash(key)).collect();
+    OfflineKeyHashes::set(Vec::leak(hashes));
+}
+
+#[test]
+fn a_key_in_the_offline_allowlist_is_valid_without_any_request() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        allow_offline_keys(&[b"another-key", b"test-license-key"]);
+
+        handles.set_timestamp(60_000);
+        // The scripted client panics on any request.
+        let mut client = ScriptedHttpClient::default();
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        assert_eq!(reported_validity(&handles), vec![true]);
+        assert!(!Aura::is_halted());
+    });
+}
+
+#[test]
+fn a_key_missing_from_the_offline_allowlist_halts() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        allow_offline_keys(&[b"another-key"]);
+
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::default();
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        assert_eq!(reported_validity(&handles), vec![false]);
+
+        handles.set_timestamp(90_000);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        assert_eq!(
+            submitted_halt_reason(&handles),
+            Some(REASON_LICENSE_INVALID.to_vec())
+        );
+        assert!(client.requests.is_empty());
+    });
+}
+
+#[test]
+fn fail_closed_halts_when_license_server_is_unreachable() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        FailClosed::set(true);
+
+        handles.set_timestamp(60_000);
+        assert_ok!(Aura::check_license_and_halt_if_needed(
+            &mut unreachable_server()
+        ));
+        assert_eq!(
+            handles.submitted_calls(),
+            vec![RuntimeCall::Aura(
+                pallet::Call::offchain_worker_report_check {
+                    report: LicenseCheckReport::invalid(),
+                }
+            )]
+        );
+
+        // The next run submits the pending halt without contacting the server again.
+        handles.set_timestamp(90_000);
+        assert_ok!(Aura::check_license_and_halt_if_needed(
+            &mut ScriptedHttpClient::default()
+        ));
+        let Some(RuntimeCall::Aura(pallet::Call::offchain_worker_halt_production { reason })) =
+            handles.submitted_calls().pop()
+        else {
+            panic!("expected a halt to be submitted");
+        };
+
+        assert_ok!(Aura::offchain_worker_halt_production(
+            RuntimeOrigin::none(),
+            reason
+        ));
+        assert!(Aura::is_halted());
+    });
+}
+
+#[test]
+fn a_fresh_node_waits_out_the_interval_of_the_check_reported_on_chain() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        SharedCheckResults::set(true);
+        // Another node reported a valid check.
+        pallet_timestamp::Now::<Test>::put(60_000);
+        report_check(true);
+        assert_eq!(
+            pallet::LastCheckReportedTime::<Test>::get(),
+            Some((60_000, true))
+        );
+
+        // This node has no offchain state of its own, yet does not ask the server again.
+        handles.set_timestamp(60_000 + LICENSE_CHECK_INTERVAL - 1);
+        let mut client = ScriptedHttpClient::default();
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        assert!(client.requests.is_empty());
+        assert!(handles.submitted_calls().is_empty());
+
+        handles.set_timestamp(60_000 + LICENSE_CHECK_INTERVAL);
+        let mut client = ScriptedHttpClient::new(vec![http_ok(200, br#"{"valid":true}"#)]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        assert_eq!(client.requests.len(), 1);
+    });
+}
+
+#[test]
+fn without_shared_check_results_a_fresh_node_checks_right_away() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        pallet_timestamp::Now::<Test>::put(60_000);
+        report_check(true);
+
+        handles.set_timestamp(61_000);
+        let mut client = ScriptedHttpClient::new(vec![http_ok(200, br#"{"valid":true}"#)]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        assert_eq!(client.requests.len(), 1);
+    });
+}
+
+#[test]
+fn offchain_worker_stops_requesting_once_its_time_budget_is_spent() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        FailClosed::set(true);
+        OffchainTimeBudget::set(250);
+
+        handles.set_timestamp(60_000);
+        let mut client = unreachable_server();
+        client.latency_ms = 100;
+        assert_eq!(
+            Aura::check_license_and_halt_if_needed(&mut client),
+            Err(OffchainError::TimeBudgetExhausted)
+        );
+
+        // The second attempt starts 50ms before the budget ends; the third is never sent, and
+        // the backoff before it is cut short.
+        assert_eq!(client.requests.len(), 2);
+        assert_eq!(client.sleeps, vec![100, 0]);
+        // Running out of time is not a verdict, even when failing closed.
+        assert!(handles.submitted_calls().is_empty());
+
+        // The check is retried on the next run rather than after the check interval.
+        handles.set_timestamp(61_000);
+        let mut client = ScriptedHttpClient::new(vec![http_ok(200, br#"{"valid":true}"#)]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        assert_eq!(reported_checks(&handles), 1);
+    });
+}
+
+#[test]
+fn license_renewal_is_skipped_once_the_time_budget_is_spent() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        System::set_block_number(1);
+        setup_renewal_due();
+        OffchainTimeBudget::set(100);
+
+        handles.set_timestamp(90_000);
+        let mut client = ScriptedHttpClient::new(vec![http_ok(200, br#"{"valid":true}"#)]);
+        client.latency_ms = 100;
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+
+        assert_eq!(client.requests.len(), 1);
+        assert!(client.posted_bodies.is_empty());
+        assert_eq!(reported_checks(&handles), 1);
+        assert!(submitted_renewals(&handles).is_empty());
+    });
+}
+
+#[test]
+fn fail_open_keeps_producing_when_license_server_is_unreachable() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        handles.set_timestamp(60_000);
+        assert_eq!(
+            Aura::check_license_and_halt_if_needed(&mut unreachable_server()),
+            Err(OffchainError::SendFailed)
+        );
+
+        handles.set_timestamp(90_000);
+        let mut client = unreachable_server();
+        assert!(Aura::check_license_and_halt_if_needed(&mut client).is_err());
+
+        // Every run tries the server again and nothing is ever submitted.
+        assert_eq!(client.requests.len(), 3);
+        assert!(handles.submitted_calls().is_empty());
+        assert!(!Aura::is_halted());
+    });
+}
+
+#[test]
+#[should_panic(expected = "Licensed Aura `SlotDuration` must not be zero")]
+fn genesis_rejects_zero_slot_duration() {
+    MockSlotDuration::set(0);
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {});
+}
+
+#[test]
+#[should_panic(expected = "Licensed Aura `SlotDuration` must not be zero")]
+fn integrity_test_rejects_zero_slot_duration() {
+    MockSlotDuration::set(0);
+    <Aura as frame_support::traits::Hooks<u64>>::integrity_test();
+}
+
+#[test]
+fn try_state_rejects_zero_slot_duration() {
+    let (mut ext, _) = build_offchain_ext(vec![0, 1, 2, 3]);
+    ext.execute_with(|| {
+        MockSlotDuration::set(0);
+        assert_eq!(Aura::do_try_state(), Err(ZERO_SLOT_DURATION.into()));
+    });
+}
+
+const PROOF_NOW: u64 = 1_000_000;
+
+fn license_proof(valid: bool, checked_at: u64) -> LicenseProof {
+    LicenseProof {
+        key_hash: LicenseProof::key_hash(b"test-license-key"),
+        valid,
+        checked_at,
+    }
+}
+
+fn note_proof(proof: Option<LicenseProof>) {
+    pallet_timestamp::Now::<Test>::put(PROOF_NOW);
+    assert_ok!(Aura::note_license_proof(RuntimeOrigin::none(), proof));
+    Aura::on_finalize(System::block_number());
+}
+
+#[test]
+fn license_proof_inherent_is_only_created_when_enforced() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let mut data = InherentData::new();
+        let proof = Some(license_proof(true, PROOF_NOW));
+        data.put_data(inherent::INHERENT_IDENTIFIER, &proof)
+            .unwrap();
+
+        assert_eq!(Aura::create_inherent(&data), None);
+        assert!(Aura::is_inherent_required(&data).unwrap().is_none());
+
+        EnforceLicenseInherent::set(true);
+        assert_eq!(
+            Aura::create_inherent(&data),
+            Some(pallet::Call::note_license_proof { proof })
+        );
+        assert!(Aura::is_inherent_required(&data).unwrap().is_some());
+
+        // An author without a proof still includes the inherent, which then halts.
+        assert_eq!(
+            Aura::create_inherent(&InherentData::new()),
+            Some(pallet::Call::note_license_proof { proof: None })
+        );
+    });
+}
+
+#[test]
+fn fresh_valid_license_proof_keeps_production_running() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        EnforceLicenseInherent::set(true);
+        System::set_block_number(1);
+
+        note_proof(Some(license_proof(true, PROOF_NOW - LICENSE_PROOF_MAX_AGE)));
+
+        assert!(!Aura::is_halted());
+        assert!(aura_events().is_empty());
+    });
+}
+
+#[test]
+fn missing_license_proof_halts_production() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        EnforceLicenseInherent::set(true);
+        System::set_block_number(1);
+
+        note_proof(None);
+
+        assert!(Aura::is_halted());
+        assert_eq!(aura_events(), vec![pallet::Event::ProductionHalted]);
+    });
+}
+
+#[test]
+fn block_with_a_lapsed_license_proof_is_rejected() {
+    let lapsed = [
+        (
+            license_proof(false, PROOF_NOW),
+            pallet::Error::<Test>::LicenseProofInvalid,
+        ),
+        (
+            license_proof(true, PROOF_NOW - LICENSE_PROOF_MAX_AGE - 1),
+            pallet::Error::<Test>::LicenseProofStale,
+        ),
+        (
+            LicenseProof {
+                key_hash: LicenseProof::key_hash(b"another-key"),
+                ..license_proof(true, PROOF_NOW)
+            },
+            pallet::Error::<Test>::LicenseProofForAnotherKey,
+        ),
+    ];
+
+    for (proof, error) in lapsed {
+        build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+            EnforceLicenseInherent::set(true);
+            System::set_block_number(1);
+            pallet_timestamp::Now::<Test>::put(PROOF_NOW);
+
+            assert_noop!(
+                Aura::note_license_proof(RuntimeOrigin::none(), Some(proof)),
+                error
+            );
+            assert!(!Aura::is_halted());
+        });
+    }
+}
+
+#[test]
+fn perpetual_license_accepts_any_license_proof() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        EnforceLicenseInherent::set(true);
+        pallet::PerpetuallyLicensed::<Test>::put(true);
+
+        note_proof(Some(license_proof(false, PROOF_NOW)));
+
+        assert!(!Aura::is_halted());
+    });
+}
+
+#[test]
+fn fresh_license_proof_resumes_production_halted_by_a_proof() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        EnforceLicenseInherent::set(true);
+        System::set_block_number(1);
+
+        note_proof(None);
+        assert!(Aura::is_halted());
+
+        note_proof(Some(license_proof(true, PROOF_NOW)));
+        assert!(!Aura::is_halted());
+        assert_eq!(
+            aura_events(),
+            vec![
+                pallet::Event::ProductionHalted,
+                pallet::Event::ProductionResumed
+            ]
+        );
+    });
+}
+
+#[test]
+fn fresh_license_proof_does_not_override_a_manual_halt() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        EnforceLicenseInherent::set(true);
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+
+        note_proof(Some(license_proof(true, PROOF_NOW)));
+
+        assert!(Aura::is_halted());
+    });
+}
+
+#[test]
+fn fresh_license_proof_does_not_lift_a_manual_halt_over_a_proof_halt() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        EnforceLicenseInherent::set(true);
+        System::set_block_number(1);
+        note_proof(None);
+        assert!(pallet::HaltedByLicenseProof::<Test>::get());
+
+        System::set_block_number(2);
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert!(!pallet::HaltedByLicenseProof::<Test>::get());
+
+        System::set_block_number(3);
+        note_proof(Some(license_proof(true, PROOF_NOW)));
+
+        assert!(Aura::is_halted());
+    });
+}
+
+#[test]
+fn license_proof_from_the_future_is_rejected() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        EnforceLicenseInherent::set(true);
+        pallet_timestamp::Now::<Test>::put(PROOF_NOW);
+
+        assert_noop!(
+            Aura::note_license_proof(
+                RuntimeOrigin::none(),
+                Some(license_proof(true, PROOF_NOW + 1))
+            ),
+            pallet::Error::<Test>::LicenseProofFromFuture
+        );
+    });
+}
+
+#[test]
+#[should_panic(expected = "license proof inherent must be included once per block")]
+fn block_without_license_proof_is_rejected_when_enforced() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        EnforceLicenseInherent::set(true);
+        Aura::on_finalize(1);
+    });
+}
+
+#[test]
+fn offchain_worker_stores_proof_of_its_latest_check() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        let stored_proof = || {
+            sp_io::offchain::local_storage_get(
+                sp_core::offchain::StorageKind::PERSISTENT,
+                LICENSE_PROOF_STORAGE_KEY,
+            )
+            .map(|raw| LicenseProof::decode(&mut &raw[..]).unwrap())
+        };
+
+        handles.set_timestamp(60_000);
+        handles.expect_license_request(br#"{"valid":true}"#);
+        Aura::offchain_worker(1);
+        assert_eq!(stored_proof(), Some(license_proof(true, 60_000)));
+
+        handles.set_timestamp(60_000 + LICENSE_CACHE_TTL);
+        handles.expect_license_request(br#"{"valid":false}"#);
+        Aura::offchain_worker(2);
+        assert_eq!(
+            stored_proof(),
+            Some(license_proof(false, 60_000 + LICENSE_CACHE_TTL))
+        );
+    });
+}
+
+#[test]
+fn debug_dump_reflects_current_state() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let fingerprint = Some(LicenseProof::key_hash(b"test-license-key"));
+        assert_eq!(
+            Aura::debug_dump(),
+            LicensedAuraDebug {
+                halted: false,
+                halt_reason: None,
+                halted_by_license_proof: false,
+                license_key_fingerprint: fingerprint,
+                license_term: None,
+                license_usage_threshold_reached: 0,
+                bypass_cache_once: false,
+                current_slot: 0,
+                authorities_count: 4,
+                recent_checks: 0,
+                recent_valid_checks: 0,
+                last_check_reported_at: None,
+                enforcement_health: 100,
+                halted_at_block: None,
+                safe_mode: false,
+                halted_by_license_quorum: false,
+                simulated_halt_until: None,
+                auto_recovery_paused: false,
+                perpetually_licensed: false,
+                consecutive_check_failures: 0,
+            }
+        );
+
+        System::set_block_number(3);
+        pallet::CurrentSlot::<Test>::put(Slot::from(7));
+        report_check(true);
+        report_check(false);
+        assert_ok!(Aura::sudo_force_fresh_check(RuntimeOrigin::root()));
+        assert_ok!(Aura::sudo_halt_production(
+            RuntimeOrigin::root(),
+            Some(b"maintenance".to_vec())
+        ));
+        assert_ok!(Aura::sudo_set_auto_recovery_paused(
+            RuntimeOrigin::root(),
+            true
+        ));
+
+        let dump = Aura::debug_dump();
+        assert_eq!(
+            dump,
+            LicensedAuraDebug {
+                halted: true,
+                halt_reason: Some(b"maintenance".to_vec()),
+                halted_by_license_proof: false,
+                license_key_fingerprint: fingerprint,
+                license_term: None,
+                license_usage_threshold_reached: 0,
+                bypass_cache_once: true,
+                current_slot: 7,
+                authorities_count: 4,
+                recent_checks: 2,
+                recent_valid_checks: 1,
+                last_check_reported_at: Some(3),
+                enforcement_health: Aura::enforcement_health(),
+                halted_at_block: Some(3),
+                safe_mode: false,
+                halted_by_license_quorum: false,
+                simulated_halt_until: None,
+                auto_recovery_paused: true,
+                perpetually_licensed: false,
+                consecutive_check_failures: 1,
+            }
+        );
+
+        // The raw key never appears in the encoded dump.
+        let encoded = dump.encode();
+        assert!(!encoded
+            .windows(b"test-license-key".len())
+            .any(|window| window == b"test-license-key"));
+    });
+}
+
+#[test]
+fn halt_reason_string_decodes_utf8_reason() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_ok!(Aura::sudo_halt_production(
+            RuntimeOrigin::root(),
+            Some("licence expired ✗".as_bytes().to_vec())
+        ));
+        assert_eq!(
+            Aura::halt_reason_string(),
+            Some("licence expired ✗".to_string())
+        );
+    });
+}
+
+#[test]
+fn halt_reason_string_replaces_non_utf8_reason() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_ok!(Aura::sudo_halt_production(
+            RuntimeOrigin::root(),
+            Some(vec![0x66, 0x6f, 0xff, 0xfe])
+        ));
+        assert_eq!(
+            Aura::halt_reason_string(),
+            Some("<invalid utf8>".to_string())
+        );
+    });
+}
+
+#[test]
+fn halt_reason_string_is_none_without_reason() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_eq!(Aura::halt_reason_string(), None);
+
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert_eq!(Aura::halt_reason_string(), None);
+    });
+}
+
+#[test]
+fn slot_duration_change_migration_keeps_timestamps_consistent() {
+    use crate::migrations::RescaleCurrentSlot;
+    use frame_support::traits::{ConstU64, OnRuntimeUpgrade};
+
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        // Block at timestamp 100 under the mock's 2ms slots.
+        pallet::CurrentSlot::<Test>::put(Slot::from(50));
+        pallet_timestamp::Now::<Test>::put(100);
+
+        // Upgrade to 6ms slots.
+        MockSlotDuration::set(6);
+        RescaleCurrentSlot::<Test, ConstU64<2>, ConstU64<6>>::on_runtime_upgrade();
+        assert_eq!(pallet::CurrentSlot::<Test>::get(), Slot::from(16));
+
+        // The next block, at timestamp 104, is in slot 17 of the new duration.
+        let pre_digest = Digest {
+            logs: vec![DigestItem::PreRuntime(
+                AURA_ENGINE_ID,
+                Slot::from(17).encode(),
+            )],
+        };
+        System::initialize(&2, &System::parent_hash(), &pre_digest);
+        Aura::on_initialize(2);
+        assert_ok!(pallet_timestamp::Pallet::<Test>::set(
+            RuntimeOrigin::none(),
+            104
+        ));
+        assert_eq!(pallet::CurrentSlot::<Test>::get(), Slot::from(17));
+    });
+}
+
+#[test]
+fn slot_duration_migration_is_a_noop_for_unchanged_duration() {
+    use crate::migrations::RescaleCurrentSlot;
+    use frame_support::traits::{ConstU64, OnRuntimeUpgrade};
+
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        pallet::CurrentSlot::<Test>::put(Slot::from(50));
+
+        RescaleCurrentSlot::<Test, ConstU64<2>, ConstU64<2>>::on_runtime_upgrade();
+
+        assert_eq!(pallet::CurrentSlot::<Test>::get(), Slot::from(50));
+    });
+}
+
+#[test]
+fn failed_submission_defers_the_next_attempt() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |_| {
+        System::set_block_number(10);
+        assert!(!Aura::submit_with_backoff("test", || Err(())));
+
+        System::set_block_number(14);
+        assert!(!Aura::submit_with_backoff("test", || {
+            panic!("submission should be deferred")
+        }));
+
+        System::set_block_number(15);
+        assert!(Aura::submit_with_backoff("test", || Ok(())));
+
+        // A success clears the backoff.
+        System::set_block_number(16);
+        assert!(Aura::submit_with_backoff("test", || Ok(())));
+    });
+}
+
+#[test]
+fn pending_halt_waits_out_submission_backoff() {
+    use sp_runtime::offchain::storage::StorageValueRef;
+
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        System::set_block_number(10);
+        assert!(!Aura::submit_with_backoff("test", || Err(())));
+        StorageValueRef::persistent(b"licensed_aura::halt_requested").set(&true);
+
+        System::set_block_number(12);
+        handles.set_timestamp(60_000);
+        Aura::offchain_worker(12);
+        assert!(handles.submitted_calls().is_empty());
+
+        System::set_block_number(15);
+        handles.set_timestamp(90_000);
+        Aura::offchain_worker(15);
+        assert!(matches!(
+            handles.submitted_calls()[..],
+            [RuntimeCall::Aura(
+                pallet::Call::offchain_worker_halt_production { .. }
+            )]
+        ));
+    });
+}
+
+#[test]
+fn enforcement_config_reflects_active_settings() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        let defaults = EnforcementConfig {
+            license_endpoints: vec![DEFAULT_LICENSE_ENDPOINT.as_bytes().to_vec()],
+            endpoint_selection: EndpointSelection::Failover,
+            check_interval: LICENSE_CHECK_INTERVAL,
+            check_interval_override: None,
+            check_every_slots: None,
+            server_min_check_interval: None,
+            license_cache_ttl: LICENSE_CACHE_TTL,
+            offchain_warmup_checks: 0,
+            license_proof_max_age: LICENSE_PROOF_MAX_AGE,
+            max_clock_drift: MAX_CLOCK_DRIFT,
+            auto_recovery_delay: 0,
+            fail_closed: false,
+            enforce_license_inherent: false,
+            halt_if_ocw_absent: false,
+            ocw_absence_threshold: 10,
+            per_authority_keys: false,
+            license_quorum: Percent::from_percent(51),
+            safe_mode: false,
+        };
+        assert_eq!(Aura::enforcement_config(), defaults);
+
+        assert_ok!(Aura::sudo_set_license_endpoints(
+            RuntimeOrigin::root(),
+            vec![
+                (1, b"http://backup".to_vec()),
+                (5, b"http://primary".to_vec())
+            ]
+        ));
+        assert_ok!(Aura::sudo_set_check_interval(
+            RuntimeOrigin::root(),
+            2 * MIN_CHECK_INTERVAL
+        ));
+        assert_ok!(Aura::sudo_set_auto_recovery_delay(
+            RuntimeOrigin::root(),
+            Some(17)
+        ));
+        assert_ok!(Aura::sudo_enter_safe_mode(RuntimeOrigin::root(), None));
+
+        assert_eq!(
+            Aura::enforcement_config(),
+            EnforcementConfig {
+                license_endpoints: vec![b"http://primary".to_vec(), b"http://backup".to_vec()],
+                check_interval: 2 * MIN_CHECK_INTERVAL,
+                check_interval_override: Some(2 * MIN_CHECK_INTERVAL),
+                auto_recovery_delay: 17,
+                safe_mode: true,
+                ..defaults
+            }
+        );
+    });
+}
+
+#[test]
+fn enforcement_config_excludes_secrets() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let encoded = Aura::enforcement_config().encode();
+        assert!(!encoded
+            .windows(b"test-license-key".len())
+            .any(|w| w == b"test-license-key"));
+    });
+}
+
+#[test]
+fn license_endpoints_are_ordered_by_priority() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        assert_eq!(
+            Aura::license_endpoints(),
+            vec![DEFAULT_LICENSE_ENDPOINT.to_string()]
+        );
+
+        assert_ok!(Aura::sudo_set_license_endpoints(
+            RuntimeOrigin::root(),
+            vec![
+                (1, b"http://backup".to_vec()),
+                (5, b"http://primary".to_vec()),
+                (3, b"http://secondary".to_vec()),
+                (5, b"http://primary-2".to_vec()),
+            ]
+        ));
+
+        assert_eq!(
+            Aura::license_endpoints(),
+            vec![
+                "http://primary".to_string(),
+                "http://primary-2".to_string(),
+                "http://secondary".to_string(),
+                "http://backup".to_string(),
+            ]
+        );
+        assert_eq!(
+            aura_events(),
+            vec![pallet::Event::EndpointsConfigured { count: 4 }]
+        );
+
+        // An empty list restores the default.
+        assert_ok!(Aura::sudo_set_license_endpoints(
+            RuntimeOrigin::root(),
+            vec![]
+        ));
+        assert_eq!(
+            Aura::license_endpoints(),
+            vec![DEFAULT_LICENSE_ENDPOINT.to_string()]
+        );
+    });
+}
+
+#[test]
+fn license_endpoints_are_validated() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let too_many = (0..=MAX_LICENSE_ENDPOINTS)
+            .map(|i| (0, format!("http://node-{}", i).into_bytes()))
+            .collect();
+        assert_noop!(
+            Aura::sudo_set_license_endpoints(RuntimeOrigin::root(), too_many),
+            pallet::Error::<Test>::TooManyEndpoints
+        );
+
+        let too_long = vec![b'a'; MAX_LICENSE_ENDPOINT_LEN as usize + 1];
+        assert_noop!(
+            Aura::sudo_set_license_endpoints(RuntimeOrigin::root(), vec![(0, too_long)]),
+            pallet::Error::<Test>::EndpointTooLong
+        );
+
+        for invalid in [vec![], vec![0xff, 0xfe]] {
+            assert_noop!(
+                Aura::sudo_set_license_endpoints(RuntimeOrigin::root(), vec![(0, invalid)]),
+                pallet::Error::<Test>::InvalidEndpoint
+            );
+        }
+
+        assert_noop!(
+            Aura::sudo_set_license_endpoints(RuntimeOrigin::signed(1), vec![]),
+            DispatchError::BadOrigin
+        );
+    });
+}
+
+#[test]
+fn endpoint_schemes_are_detected() {
+    assert_eq!(
+        EndpointScheme::of("http://primary"),
+        Some(EndpointScheme::Http)
+    );
+    assert_eq!(
+        EndpointScheme::of("HTTPS://primary/license"),
+        Some(EndpointScheme::Https)
+    );
+    assert_eq!(
+        EndpointScheme::of("unix:///run/license.sock"),
+        Some(EndpointScheme::Unix)
+    );
+    for unusable in [
+        "unix://run/license.sock",
+        "unix:///",
+        "http://",
+        "ftp://primary",
+        "primary",
+    ] {
+        assert_eq!(EndpointScheme::of(unusable), None, "{unusable}");
+    }
+}
+
+#[test]
+fn unix_endpoints_are_routed_to_the_local_agent() {
+    assert_eq!(route_endpoint("unix:///run/license.sock"), LOCAL_AGENT_URL);
+    assert_eq!(route_endpoint("https://primary"), "https://primary");
+
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        for invalid in [
+            &b"ftp://primary"[..],
+            b"unix://run/license.sock",
+            b"primary",
+        ] {
+            let endpoints = vec![(0, invalid.to_vec())];
+            assert_noop!(
+                Aura::sudo_set_license_endpoints(RuntimeOrigin::root(), endpoints),
+                pallet::Error::<Test>::InvalidEndpoint
+            );
+        }
+    });
+}
+
+#[test]
+fn offchain_worker_queries_a_local_agent_only_with_an_hmac_secret() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        assert_ok!(Aura::sudo_set_license_endpoints(
+            RuntimeOrigin::root(),
+            vec![
+                (9, b"unix:///run/license.sock".to_vec()),
+                (1, b"http://backup".to_vec()),
+            ]
+        ));
+        let query = format!(
+            "key=test-license-key&genesis={}&instance={}",
+            GENESIS_HASH_HEX, INSTANCE_ID_HEX
+        );
+
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::new(vec![http_ok(200, br#"{"valid":true}"#)]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        let urls: Vec<_> = client.requests.iter().map(|r| r.url.clone()).collect();
+        assert_eq!(urls, vec![format!("http://backup?{}", query)]);
+
+        sp_io::offchain::local_storage_set(
+            sp_core::offchain::StorageKind::PERSISTENT,
+            HMAC_SECRET_STORAGE_KEY,
+            b"shared-secret",
+        );
+        assert_ok!(Aura::sudo_force_fresh_check(RuntimeOrigin::root()));
+        handles.set_timestamp(140_000);
+        let mut client = ScriptedHttpClient::new(vec![http_ok(200, br#"{"valid":true}"#)]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        let urls: Vec<_> = client.requests.iter().map(|r| r.url.clone()).collect();
+        assert_eq!(urls, vec![format!("{}?{}", LOCAL_AGENT_URL, query)]);
+    });
+}
+
+#[test]
+fn offchain_worker_fails_over_in_priority_order() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        assert_ok!(Aura::sudo_set_license_endpoints(
+            RuntimeOrigin::root(),
+            vec![
+                (1, b"http://backup".to_vec()),
+                (9, b"http://primary".to_vec()),
+            ]
+        ));
+
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::new(vec![
+            Err(HttpFailure::SendFailed),
+            Err(HttpFailure::SendFailed),
+            http_ok(503, b""),
+            http_ok(200, br#"{"valid":true}"#),
+        ]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+
+        let query = format!(
+            "key=test-license-key&genesis={}&instance={}",
+            GENESIS_HASH_HEX, INSTANCE_ID_HEX
+        );
+        let primary = format!("http://primary?{}", query);
+        let backup = format!("http://backup?{}", query);
+        let urls: Vec<_> = client.requests.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(urls, vec![&primary, &primary, &primary, &backup]);
+        assert_eq!(
+            handles.submitted_calls(),
+            vec![RuntimeCall::Aura(
+                pallet::Call::offchain_worker_report_check {
+                    report: LicenseCheckReport {
+                        valid: true,
+                        issued_at: None,
+                        expires_at: None,
+                        min_check_interval: None,
+                        not_before: None,
+                        features: None,
+                    },
+                }
+            )]
+        );
+    });
+}
+
+/// Runs one license check per seed and returns the endpoint each check queried first.
+fn first_queried_endpoints(handles: &OffchainHandles, seeds: &[u8]) -> Vec<String> {
+    assert_ok!(Aura::sudo_set_license_endpoints(
+        RuntimeOrigin::root(),
+        vec![
+            (9, b"http://primary".to_vec()),
+            (5, b"http://secondary".to_vec()),
+            (1, b"http://backup".to_vec()),
+        ]
+    ));
+
+    seeds
+        .iter()
+        .enumerate()
+        .map(|(i, seed)| {
+            // Past both the check interval and the cached result of the previous check.
+            handles.set_timestamp(60_000 + i as u64 * LICENSE_CACHE_TTL);
+            handles.offchain.write().seed = [*seed; 32];
+            let mut client = ScriptedHttpClient::new(vec![http_ok(200, br#"{"valid":true}"#)]);
+            assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+            let url = &client.requests[0].url;
+            url[..url.find('?').unwrap()].to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn round_robin_selection_rotates_through_endpoints() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        EndpointSelectionMode::set(EndpointSelection::RoundRobin);
+
+        assert_eq!(
+            first_queried_endpoints(handles, &[0; 6]),
+            vec![
+                "http://primary",
+                "http://secondary",
+                "http://backup",
+                "http://primary",
+                "http://secondary",
+                "http://backup",
+            ]
+        );
+    });
+}
+
+#[test]
+fn random_selection_spreads_checks_over_endpoints() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        EndpointSelectionMode::set(EndpointSelection::Random);
+
+        let seeds: Vec<u8> = (0..30).collect();
+        let first = first_queried_endpoints(handles, &seeds);
+        for endpoint in ["http://primary", "http://secondary", "http://backup"] {
+            let count = first.iter().filter(|url| *url == endpoint).count();
+            assert!(
+                count >= 5,
+                "{} was queried first only {} times",
+                endpoint,
+                count
+            );
+        }
+    });
+}
+
+#[test]
+fn failover_selection_always_starts_with_the_highest_priority() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        let seeds: Vec<u8> = (0..6).collect();
+        assert_eq!(
+            first_queried_endpoints(handles, &seeds),
+            vec!["http://primary"; 6]
+        );
+    });
+}
+
+#[test]
+fn selected_endpoint_fails_over_to_the_others_in_turn() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        EndpointSelectionMode::set(EndpointSelection::RoundRobin);
+        assert_eq!(
+            first_queried_endpoints(handles, &[0]),
+            vec!["http://primary"]
+        );
+
+        handles.set_timestamp(60_000 + LICENSE_CACHE_TTL);
+        let mut client = ScriptedHttpClient::new(vec![
+            Err(HttpFailure::SendFailed),
+            Err(HttpFailure::SendFailed),
+            Err(HttpFailure::SendFailed),
+            Err(HttpFailure::SendFailed),
+            Err(HttpFailure::SendFailed),
+            Err(HttpFailure::SendFailed),
+            http_ok(200, br#"{"valid":true}"#),
+        ]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+
+        let mut hosts: Vec<_> = client
+            .requests
+            .iter()
+            .map(|r| &r.url[..r.url.find('?').unwrap()])
+            .collect();
+        hosts.dedup();
+        assert_eq!(
+            hosts,
+            vec!["http://secondary", "http://backup", "http://primary"]
+        );
+    });
+}
+
+fn report_min_check_interval(valid: bool, min_check_interval: Option<u64>) {
+    assert_ok!(Aura::offchain_worker_report_check(
+        RuntimeOrigin::none(),
+        LicenseCheckReport {
+            valid,
+            issued_at: None,
+            expires_at: None,
+            min_check_interval,
+            not_before: None,
+            features: None,
+        }
+    ));
+}
+
+#[test]
+fn effective_check_interval_defaults_to_config() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_eq!(Aura::effective_check_interval(), LICENSE_CHECK_INTERVAL);
+    });
+}
+
+#[test]
+fn effective_check_interval_prefers_override_to_config() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        pallet::CheckIntervalOverride::<Test>::put(10_000);
+        assert_eq!(Aura::effective_check_interval(), 10_000);
+
+        pallet::CheckIntervalOverride::<Test>::put(60_000);
+        assert_eq!(Aura::effective_check_interval(), 60_000);
+    });
+}
+
+#[test]
+fn effective_check_interval_honours_server_minimum() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        report_min_check_interval(true, Some(90_000));
+        assert_eq!(Aura::effective_check_interval(), 90_000);
+
+        // The server minimum only ever slows checks down.
+        pallet::CheckIntervalOverride::<Test>::put(120_000);
+        assert_eq!(Aura::effective_check_interval(), 120_000);
+
+        pallet::CheckIntervalOverride::<Test>::put(60_000);
+        assert_eq!(Aura::effective_check_interval(), 90_000);
+    });
+}
+
+#[test]
+fn effective_check_interval_follows_slot_duration_when_counted_in_slots() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        CheckEverySlots::set(Some(5));
+        MockSlotDuration::set(6_000);
+        assert_eq!(Aura::effective_check_interval(), 5 * Aura::slot_duration());
+        assert_eq!(Aura::effective_check_interval(), 30_000);
+
+        // A longer block time stretches the cadence with it.
+        MockSlotDuration::set(12_000);
+        assert_eq!(Aura::effective_check_interval(), 60_000);
+
+        // Sudo's override and the server minimum still apply on top.
+        report_min_check_interval(true, Some(90_000));
+        assert_eq!(Aura::effective_check_interval(), 90_000);
+        pallet::CheckIntervalOverride::<Test>::put(120_000);
+        assert_eq!(Aura::effective_check_interval(), 120_000);
+
+        CheckEverySlots::set(None);
+        pallet::CheckIntervalOverride::<Test>::kill();
+        pallet::ServerMinCheckInterval::<Test>::kill();
+        assert_eq!(Aura::effective_check_interval(), LICENSE_CHECK_INTERVAL);
+    });
+}
+
+#[test]
+fn once_per_session_checks_fire_on_session_boundaries_only() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        CheckOncePerSession::set(true);
+        let later = 60_000 + LICENSE_CHECK_INTERVAL + 2 * LICENSE_CACHE_TTL;
+        let mut client = ScriptedHttpClient::new(vec![
+            http_ok(200, br#"{"valid":true}"#),
+            http_ok(200, br#"{"valid":true}"#),
+        ]);
+
+        handles.set_timestamp(60_000);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        assert_eq!(client.requests.len(), 1);
+
+        // Long past the check interval and the cache, but still in the same session.
+        handles.set_timestamp(later);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        assert_eq!(client.requests.len(), 1);
+
+        let validators = session_validators(&[0, 1, 2, 3]);
+        let session = validators.iter().map(|(a, k)| (*a, k.clone()));
+        Aura::on_new_session(false, session.clone(), session);
+        assert_eq!(pallet::CurrentSession::<Test>::get(), 1);
+
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        assert_eq!(client.requests.len(), 2);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        assert_eq!(client.requests.len(), 2);
+    });
+}
+
+#[test]
+fn server_minimum_check_interval_follows_valid_reports() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        report_min_check_interval(true, Some(90_000));
+
+        // A failed check carries no server answer to go by.
+        report_min_check_interval(false, None);
+        assert_eq!(pallet::ServerMinCheckInterval::<Test>::get(), Some(90_000));
+
+        report_min_check_interval(true, None);
+        assert_eq!(pallet::ServerMinCheckInterval::<Test>::get(), None);
+        assert_eq!(Aura::effective_check_interval(), LICENSE_CHECK_INTERVAL);
+    });
+}
+
+#[test]
+fn offchain_worker_reports_server_minimum_check_interval() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        handles.set_timestamp(60_000);
+        handles.expect_license_request(br#"{"valid":true,"min_check_interval":120000}"#);
+        Aura::offchain_worker(1);
+
+        assert_eq!(
+            handles.submitted_calls(),
+            vec![RuntimeCall::Aura(
+                pallet::Call::offchain_worker_report_check {
+                    report: LicenseCheckReport {
+                        valid: true,
+                        issued_at: None,
+                        expires_at: None,
+                        min_check_interval: Some(120_000),
+                        not_before: None,
+                        features: None,
+                    },
+                }
+            )]
+        );
+    });
+}
+
+fn submitted_halt_reason(handles: &OffchainHandles) -> Option<Vec<u8>> {
+    handles
+        .submitted_calls()
+        .into_iter()
+        .find_map(|call| match call {
+            RuntimeCall::Aura(pallet::Call::offchain_worker_halt_production { reason }) => reason,
+            _ => None,
+        })
+}
+
+#[test]
+fn not_yet_active_license_halts_with_its_own_reason() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        handles.set_timestamp(60_000);
+        handles.expect_license_request(br#"{"valid":true,"not_before":120000}"#);
+        Aura::offchain_worker(1);
+
+        let report = LicenseCheckReport {
+            valid: false,
+            issued_at: None,
+            expires_at: None,
+            min_check_interval: None,
+            not_before: Some(120_000),
+            features: None,
+        };
+        assert_eq!(
+            handles.submitted_calls(),
+            vec![RuntimeCall::Aura(
+                pallet::Call::offchain_worker_report_check {
+                    report: report.clone()
+                }
+            )]
+        );
+        assert_ok!(Aura::offchain_worker_report_check(
+            RuntimeOrigin::none(),
+            report
+        ));
+        assert_eq!(pallet::LicenseNotBefore::<Test>::get(), Some(120_000));
+
+        handles.set_timestamp(90_000);
+        Aura::offchain_worker(2);
+        let reason = submitted_halt_reason(handles);
+        assert_eq!(reason, Some(REASON_LICENSE_NOT_YET_ACTIVE.to_vec()));
+
+        assert_ok!(Aura::offchain_worker_halt_production(
+            RuntimeOrigin::none(),
+            reason
+        ));
+        assert_eq!(
+            Aura::halt_reason_string(),
+            Some("License not yet active".to_string())
+        );
+    });
+}
+
+#[test]
+fn valid_license_with_a_past_expiry_halts_as_expired() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        capture_logs();
+        handles.set_timestamp(60_000);
+        handles.expect_license_request(br#"{"valid":true,"expires_at":30000}"#);
+        Aura::offchain_worker(1);
+
+        assert_eq!(
+            handles.submitted_calls(),
+            vec![RuntimeCall::Aura(
+                pallet::Call::offchain_worker_report_check {
+                    report: LicenseCheckReport {
+                        valid: false,
+                        issued_at: None,
+                        expires_at: Some(30_000),
+                        min_check_interval: None,
+                        not_before: None,
+                        features: None,
+                    }
+                }
+            )]
+        );
+        assert!(captured_logs().iter().any(|(level, message)| {
+            *level == log::Level::Warn && message.contains("valid license that expired")
+        }));
+
+        handles.set_timestamp(90_000);
+        Aura::offchain_worker(2);
+        let reason = submitted_halt_reason(handles);
+        assert_eq!(reason, Some(REASON_LICENSE_EXPIRED.to_vec()));
+        assert_ok!(Aura::offchain_worker_halt_production(
+            RuntimeOrigin::none(),
+            reason
+        ));
+        assert!(Aura::is_halted());
+    });
+}
+
+#[test]
+fn valid_license_with_a_future_expiry_stays_valid() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        handles.set_timestamp(60_000);
+        handles.expect_license_request(br#"{"valid":true,"expires_at":90000}"#);
+        Aura::offchain_worker(1);
+
+        assert_eq!(
+            handles.submitted_calls(),
+            vec![RuntimeCall::Aura(
+                pallet::Call::offchain_worker_report_check {
+                    report: LicenseCheckReport {
+                        valid: true,
+                        issued_at: None,
+                        expires_at: Some(90_000),
+                        min_check_interval: None,
+                        not_before: None,
+                        features: None,
+                    }
+                }
+            )]
+        );
+    });
+}
+
+#[test]
+fn valid_backup_license_key_keeps_the_chain_running() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        assert_ok!(Aura::set_backup_license_key(
+            RuntimeOrigin::root(),
+            Some(b"backup-key".to_vec())
+        ));
+
+        handles.set_timestamp(60_000);
+        handles.expect_license_request(br#"{"valid":false}"#);
+        handles.expect_request(
+            &LICENSE_URL.replace("key=test-license-key", "key=backup-key"),
+            br#"{"valid":true}"#,
+        );
+        Aura::offchain_worker(1);
+        assert_eq!(reported_validity(handles), vec![true]);
+
+        handles.set_timestamp(90_000);
+        Aura::offchain_worker(2);
+        assert_eq!(submitted_halt_reason(handles), None);
+    });
+}
+
+#[test]
+fn invalid_primary_and_backup_license_keys_halt() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        assert_ok!(Aura::set_backup_license_key(
+            RuntimeOrigin::root(),
+            Some(b"backup-key".to_vec())
+        ));
+
+        handles.set_timestamp(60_000);
+        handles.expect_license_request(br#"{"valid":false}"#);
+        handles.expect_request(
+            &LICENSE_URL.replace("key=test-license-key", "key=backup-key"),
+            br#"{"valid":false}"#,
+        );
+        Aura::offchain_worker(1);
+        assert_eq!(reported_validity(handles), vec![false]);
+
+        handles.set_timestamp(90_000);
+        Aura::offchain_worker(2);
+        assert_eq!(
+            submitted_halt_reason(handles),
+            Some(REASON_LICENSE_INVALID.to_vec())
+        );
+    });
+}
+
+#[test]
+fn set_backup_license_key_is_bounded_and_privileged() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_noop!(
+            Aura::set_backup_license_key(RuntimeOrigin::signed(1), Some(b"key".to_vec())),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Aura::set_backup_license_key(RuntimeOrigin::root(), Some(vec![b'k'; 129])),
+            pallet::Error::<Test>::LicenseKeyTooLong
+        );
+
+        assert_ok!(Aura::set_backup_license_key(
+            RuntimeOrigin::root(),
+            Some(b"key".to_vec())
+        ));
+        assert_eq!(
+            pallet::BackupLicenseKey::<Test>::get().unwrap().to_vec(),
+            b"key".to_vec()
+        );
+        assert_ok!(Aura::set_backup_license_key(RuntimeOrigin::root(), None));
+        assert_eq!(pallet::BackupLicenseKey::<Test>::get(), None);
+    });
+}
+
+#[test]
+fn license_becomes_valid_once_not_before_has_passed() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        assert_ok!(Aura::sudo_halt_production(
+            RuntimeOrigin::root(),
+            Some(REASON_LICENSE_NOT_YET_ACTIVE.to_vec())
+        ));
+
+        handles.set_timestamp(130_000);
+        handles.expect_license_request(br#"{"valid":true,"not_before":120000}"#);
+        Aura::offchain_worker(1);
+
+        let report = LicenseCheckReport {
+            valid: true,
+            issued_at: None,
+            expires_at: None,
+            min_check_interval: None,
+            not_before: Some(120_000),
+            features: None,
+        };
+        assert_eq!(
+            handles.submitted_calls(),
+            vec![RuntimeCall::Aura(
+                pallet::Call::offchain_worker_report_check { report }
+            )]
+        );
+
+        // The pending resume goes out on the next run.
+        handles.set_timestamp(160_000);
+        Aura::offchain_worker(2);
+        assert!(matches!(
+            handles.submitted_calls().last(),
+            Some(RuntimeCall::Aura(
+                pallet::Call::offchain_worker_resume_production {}
+            ))
+        ));
+    });
+}
+
+#[test]
+fn rejected_license_still_halts_with_the_generic_reason() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        handles.set_timestamp(60_000);
+        handles.expect_license_request(br#"{"valid":false}"#);
+        Aura::offchain_worker(1);
+
+        handles.set_timestamp(90_000);
+        Aura::offchain_worker(2);
+        assert_eq!(
+            submitted_halt_reason(handles),
+            Some(REASON_LICENSE_INVALID.to_vec())
+        );
+    });
+}
+
+const PINNED_FINGERPRINT: [u8; 32] = [0xab; 32];
+
+fn reported_validity(handles: &OffchainHandles) -> Vec<bool> {
+    handles
+        .submitted_calls()
+        .into_iter()
+        .filter_map(|call| match call {
+            RuntimeCall::Aura(pallet::Call::offchain_worker_report_check { report }) => {
+                Some(report.valid)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn pin_fingerprint() {
+    assert_ok!(Aura::sudo_set_pinned_cert_fingerprint(
+        RuntimeOrigin::root(),
+        Some(PINNED_FINGERPRINT)
+    ));
+}
+
+#[test]
+fn pinned_cert_fingerprint_can_only_be_set_by_root() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        assert_noop!(
+            Aura::sudo_set_pinned_cert_fingerprint(
+                RuntimeOrigin::signed(1),
+                Some(PINNED_FINGERPRINT)
+            ),
+            DispatchError::BadOrigin
+        );
+
+        pin_fingerprint();
+        assert_eq!(
+            pallet::PinnedCertFingerprint::<Test>::get(),
+            Some(PINNED_FINGERPRINT)
+        );
+        assert_eq!(
+            aura_events(),
+            vec![pallet::Event::CertFingerprintPinned {
+                fingerprint: Some(PINNED_FINGERPRINT)
+            }]
+        );
+    });
+}
+
+#[test]
+fn response_attesting_pinned_fingerprint_is_accepted() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        pin_fingerprint();
+
+        handles.set_timestamp(60_000);
+        let body = format!(
+            r#"{{"valid":true,"cert_fingerprint":"{}"}}"#,
+            to_hex(&PINNED_FINGERPRINT).to_uppercase()
+        );
+        handles.expect_license_request(body.as_bytes());
+        Aura::offchain_worker(1);
+
+        assert_eq!(reported_validity(handles), vec![true]);
+    });
+}
+
+#[test]
+fn response_with_mismatching_fingerprint_is_rejected() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        pin_fingerprint();
+
+        handles.set_timestamp(60_000);
+        let body = format!(
+            r#"{{"valid":true,"cert_fingerprint":"{}"}}"#,
+            to_hex(&[0xcd; 32])
+        );
+        handles.expect_license_request(body.as_bytes());
+        Aura::offchain_worker(1);
+
+        assert_eq!(reported_validity(handles), vec![false]);
+    });
+}
+
+#[test]
+fn response_without_fingerprint_is_rejected_when_pinned() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        pin_fingerprint();
+
+        handles.set_timestamp(60_000);
+        handles.expect_license_request(br#"{"valid":true}"#);
+        Aura::offchain_worker(1);
+
+        assert_eq!(reported_validity(handles), vec![false]);
+    });
+}
+
+#[test]
+fn pinned_fingerprint_must_be_signed_when_secret_is_set() {
+    let secret = b"shared-secret";
+    let fingerprint = to_hex(&PINNED_FINGERPRINT);
+    let nonce = to_hex(&[0x5a; 16]);
+    let mut message = nonce.clone().into_bytes();
+    message.extend_from_slice(fingerprint.as_bytes());
+    let good_sig = to_hex(&hmac_sha256(secret, &message));
+    let bad_sig = to_hex(&hmac_sha256(b"other-secret", &message));
+
+    for (sig, accepted) in [(good_sig, true), (bad_sig, false)] {
+        build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+            pin_fingerprint();
+            handles.set_timestamp(60_000);
+            handles.offchain.write().seed = [0x5a; 32];
+            sp_io::offchain::local_storage_set(
+                sp_core::offchain::StorageKind::PERSISTENT,
+                HMAC_SECRET_STORAGE_KEY,
+                secret,
+            );
+
+            let mut request = license_request();
+            request.sign(secret, b"test-license-key", &nonce, 60_000);
+            let body = format!(
+                r#"{{"valid":true,"cert_fingerprint":"{}","cert_fingerprint_sig":"{}"}}"#,
+                fingerprint, sig
+            );
+            handles.expect_request_with_headers(LICENSE_URL, request.headers, body.as_bytes());
+            Aura::offchain_worker(1);
+
+            assert_eq!(reported_validity(handles), vec![accepted]);
+        });
+    }
+}
+
+#[test]
+fn unsigned_pinned_fingerprint_is_rejected_when_signatures_are_required() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        pin_fingerprint();
+        let body = format!(
+            r#"{{"valid":true,"cert_fingerprint":"{}"}}"#,
+            to_hex(&PINNED_FINGERPRINT)
+        );
+        assert_eq!(reported_valid(body.as_bytes()), Ok(true));
+
+        // Without an HMAC secret the echoed fingerprint proves nothing.
+        RequireSignedCertFingerprint::set(true);
+        assert_eq!(reported_valid(body.as_bytes()), Ok(false));
+
+        // Nothing is required while no fingerprint is pinned.
+        assert_ok!(Aura::sudo_set_pinned_cert_fingerprint(
+            RuntimeOrigin::root(),
+            None
+        ));
+        assert_eq!(reported_valid(br#"{"valid":true}"#), Ok(true));
+    });
+}
+
+#[test]
+fn check_interval_override_takes_precedence_over_config() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        assert_noop!(
+            Aura::sudo_set_check_interval(RuntimeOrigin::signed(1), 60_000),
+            DispatchError::BadOrigin
+        );
+
+        assert_ok!(Aura::sudo_set_check_interval(RuntimeOrigin::root(), 60_000));
+        assert_eq!(Aura::effective_check_interval(), 60_000);
+        assert_eq!(
+            aura_events(),
+            vec![pallet::Event::CheckIntervalSet { interval: 60_000 }]
+        );
+    });
+}
+
+#[test]
+fn check_interval_override_is_clamped() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_ok!(Aura::sudo_set_check_interval(RuntimeOrigin::root(), 1));
+        assert_eq!(Aura::effective_check_interval(), MIN_CHECK_INTERVAL);
+
+        assert_ok!(Aura::sudo_set_check_interval(
+            RuntimeOrigin::root(),
+            u64::MAX
+        ));
+        assert_eq!(Aura::effective_check_interval(), MAX_CHECK_INTERVAL);
+    });
+}
+
+#[test]
+fn offchain_worker_follows_check_interval_override() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        assert_ok!(Aura::sudo_set_check_interval(
+            RuntimeOrigin::root(),
+            120_000
+        ));
+
+        handles.set_timestamp(120_000);
+        handles.expect_license_request(br#"{"valid":false}"#);
+        Aura::offchain_worker(1);
+        assert_eq!(reported_checks(handles), 1);
+
+        // Within the override nothing runs, not even the pending halt.
+        handles.set_timestamp(200_000);
+        Aura::offchain_worker(2);
+        assert_eq!(handles.submitted_calls().len(), 1);
+
+        handles.set_timestamp(240_000);
+        Aura::offchain_worker(3);
+        assert_eq!(handles.submitted_calls().len(), 2);
+    });
+}
+
+#[test]
+fn status_refresh_triggers_an_immediate_check() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        handles.set_timestamp(60_000);
+        handles.expect_license_request(br#"{"valid":true}"#);
+        Aura::offchain_worker(1);
+        assert_eq!(reported_checks(handles), 1);
+
+        // Within both the check interval and the cache TTL, a refresh still hits the server.
+        System::set_block_number(2);
+        assert_ok!(Aura::request_status_refresh(RuntimeOrigin::signed(7)));
+        assert_eq!(
+            aura_events(),
+            vec![pallet::Event::StatusRefreshRequested { who: 7 }]
+        );
+        handles.set_timestamp(65_000);
+        handles.expect_license_request(br#"{"valid":true}"#);
+        Aura::offchain_worker(2);
+        assert_eq!(reported_checks(handles), 2);
+
+        // Until the report lands the same request is not served twice.
+        handles.set_timestamp(70_000);
+        Aura::offchain_worker(3);
+        assert_eq!(reported_checks(handles), 2);
+
+        report_check(true);
+        assert_eq!(pallet::StatusRefreshRequestedAt::<Test>::get(), None);
+    });
+}
+
+#[test]
+fn only_one_status_refresh_can_be_pending() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_noop!(
+            Aura::request_status_refresh(RuntimeOrigin::none()),
+            DispatchError::BadOrigin
+        );
+
+        assert_ok!(Aura::request_status_refresh(RuntimeOrigin::signed(1)));
+        assert_noop!(
+            Aura::request_status_refresh(RuntimeOrigin::signed(2)),
+            pallet::Error::<Test>::RefreshAlreadyRequested
+        );
+
+        report_check(true);
+        assert_ok!(Aura::request_status_refresh(RuntimeOrigin::signed(2)));
+    });
+}
+
+/// Replace the authorities with `count` keys generated in the test keystore, each given its
+/// own license key `key-<index>`.
+fn licensed_keystore_authorities(count: usize) -> Vec<AuthorityId> {
+    let authorities: Vec<AuthorityId> = (0..count)
+        .map(|_| AuthorityId::generate_pair(None))
+        .collect();
+    pallet::Authorities::<Test>::put(BoundedVec::truncate_from(authorities.clone()));
+    for (i, authority) in authorities.iter().enumerate() {
+        assert_ok!(Aura::sudo_set_authority_license_key(
+            RuntimeOrigin::root(),
+            authority.clone(),
+            Some(format!("key-{}", i).into_bytes()),
+        ));
+    }
+    authorities
+}
+
+fn authority_report(
+    authority: &AuthorityId,
+    valid: bool,
+) -> (
+    AuthorityLicenseReport<u64, AuthorityId>,
+    <AuthorityId as RuntimeAppPublic>::Signature,
+) {
+    let report = AuthorityLicenseReport {
+        authority: authority.clone(),
+        valid,
+        block_number: System::block_number(),
+    };
+    let signature = report
+        .using_encoded(|payload| authority.sign(&payload))
+        .unwrap();
+    (report, signature)
+}
+
+fn report_authority_check(authority: &AuthorityId, valid: bool) -> DispatchResult {
+    let (report, signature) = authority_report(authority, valid);
+    Aura::offchain_worker_report_authority_check(RuntimeOrigin::none(), report, signature)
+}
+
+#[test]
+fn minority_of_invalid_authority_licenses_keeps_the_chain_running() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |_| {
+        System::set_block_number(1);
+        let authorities = licensed_keystore_authorities(4);
+
+        assert_ok!(report_authority_check(&authorities[0], false));
+        for authority in &authorities[1..] {
+            assert_ok!(report_authority_check(authority, true));
+        }
+
+        assert!(!Aura::is_halted());
+        assert_eq!(
+            pallet::AuthorityLicenseStatus::<Test>::get(&authorities[0]),
+            Some((false, 1))
+        );
+    });
+}
+
+#[test]
+fn majority_of_invalid_authority_licenses_halts_until_quorum_recovers() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |_| {
+        System::set_block_number(1);
+        let authorities = licensed_keystore_authorities(4);
+
+        for authority in &authorities[..3] {
+            assert_ok!(report_authority_check(authority, false));
+        }
+        assert!(Aura::is_halted());
+        assert!(pallet::HaltedByLicenseQuorum::<Test>::get());
+        assert_eq!(
+            pallet::HaltReason::<Test>::get().unwrap().to_vec(),
+            REASON_LICENSE_QUORUM.to_vec()
+        );
+
+        // A single renewal drops the invalid licenses below the quorum of 3.
+        System::set_block_number(2);
+        assert_ok!(report_authority_check(&authorities[0], true));
+        assert!(!Aura::is_halted());
+        assert!(!pallet::HaltedByLicenseQuorum::<Test>::get());
+    });
+}
+
+#[test]
+fn authority_license_reports_must_be_newer_than_the_last() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |_| {
+        System::set_block_number(1);
+        let authorities = licensed_keystore_authorities(4);
+
+        assert_ok!(report_authority_check(&authorities[0], false));
+        assert_noop!(
+            report_authority_check(&authorities[0], true),
+            pallet::Error::<Test>::StaleAuthorityLicenseReport
+        );
+
+        let unlicensed = AuthorityId::generate_pair(None);
+        assert_noop!(
+            report_authority_check(&unlicensed, true),
+            pallet::Error::<Test>::NoAuthorityLicenseKey
+        );
+    });
+}
+
+#[test]
+fn authority_license_reports_must_be_signed_by_the_authority() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |_| {
+        System::set_block_number(1);
+        let authorities = licensed_keystore_authorities(4);
+
+        let (report, signature) = authority_report(&authorities[0], true);
+        let call = pallet::Call::offchain_worker_report_authority_check {
+            report: report.clone(),
+            signature,
+        };
+        assert!(Aura::validate_unsigned(TransactionSource::Local, &call).is_ok());
+        assert_eq!(
+            Aura::validate_unsigned(TransactionSource::External, &call),
+            InvalidTransaction::BadSigner.into()
+        );
+
+        let (_, forged) = authority_report(&authorities[1], true);
+        let call = pallet::Call::offchain_worker_report_authority_check {
+            report,
+            signature: forged,
+        };
+        assert_eq!(
+            Aura::validate_unsigned(TransactionSource::Local, &call),
+            InvalidTransaction::BadProof.into()
+        );
+    });
+}
+
+/// Run the offchain worker on blocks 1 to 3, a second apart and well within the check interval,
+/// and return how many checks were reported.
+fn checks_over_three_blocks(handles: &OffchainHandles, expected_requests: usize) -> usize {
+    for _ in 0..expected_requests {
+        handles.expect_license_request(br#"{"valid":true}"#);
+    }
+    for block in 1..=3 {
+        System::set_block_number(block);
+        handles.set_timestamp(60_000 + block * 1_000);
+        Aura::offchain_worker(block);
+    }
+    reported_checks(handles)
+}
+
+#[test]
+fn canary_authority_checks_on_every_block() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        let canary = AuthorityId::generate_pair(None);
+        assert_ok!(Aura::sudo_set_canary_authorities(
+            RuntimeOrigin::root(),
+            vec![canary]
+        ));
+        assert_eq!(checks_over_three_blocks(&handles, 3), 3);
+    });
+}
+
+#[test]
+fn non_canary_authority_keeps_the_check_interval() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        // A canary, but not one whose key this node holds.
+        assert_ok!(Aura::sudo_set_canary_authorities(
+            RuntimeOrigin::root(),
+            authority_keys(&[7])
+        ));
+        assert_eq!(checks_over_three_blocks(&handles, 1), 1);
+    });
+}
+
+#[test]
+fn sudo_set_canary_authorities_is_bounded_and_root_only() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        let canaries = authority_keys(&[0, 1]);
+        assert_noop!(
+            Aura::sudo_set_canary_authorities(RuntimeOrigin::signed(1), canaries.clone()),
+            DispatchError::BadOrigin
+        );
+        let max = <Test as pallet::Config>::MaxAuthorities::get() as u64;
+        assert_noop!(
+            Aura::sudo_set_canary_authorities(
+                RuntimeOrigin::root(),
+                authority_keys(&(0..=max).collect::<Vec<_>>())
+            ),
+            pallet::Error::<Test>::TooManyCanaries
+        );
+
+        assert_ok!(Aura::sudo_set_canary_authorities(
+            RuntimeOrigin::root(),
+            canaries.clone()
+        ));
+        assert_eq!(pallet::CanaryAuthorities::<Test>::get().to_vec(), canaries);
+        assert_eq!(
+            aura_events(),
+            vec![pallet::Event::CanaryAuthoritiesSet { count: 2 }]
+        );
+    });
+}
+
+#[test]
+fn offchain_worker_checks_only_the_local_authority_key() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        System::set_block_number(1);
+        let authorities = licensed_keystore_authorities(2);
+        let key_0_url = format!(
+            "http://localhost:3000/license?key=key-0&genesis={}&instance={}",
+            GENESIS_HASH_HEX, INSTANCE_ID_HEX
+        );
+
+        handles.set_timestamp(60_000);
+        handles.expect_request(&key_0_url, br#"{"valid":false}"#);
+        Aura::offchain_worker(1);
+
+        let calls = handles.submitted_calls();
+        assert_eq!(calls.len(), 1);
+        let RuntimeCall::Aura(call) = &calls[0] else {
+            panic!("expected a Licensed Aura call, got {:?}", calls[0]);
+        };
+        let pallet::Call::offchain_worker_report_authority_check { report, .. } = call else {
+            panic!("expected an authority license report, got {:?}", call);
+        };
+        assert_eq!(report.authority, authorities[0]);
+        assert!(!report.valid);
+        assert!(Aura::validate_unsigned(TransactionSource::Local, call).is_ok());
+
+        // The quorum decides, so the next run reports again rather than queueing a halt.
+        handles.set_timestamp(100_000);
+        handles.expect_request(&key_0_url, br#"{"valid":false}"#);
+        Aura::offchain_worker(2);
+        let calls = handles.submitted_calls();
+        assert_eq!(calls.len(), 2);
+        assert!(matches!(
+            calls[1],
+            RuntimeCall::Aura(pallet::Call::offchain_worker_report_authority_check { .. })
+        ));
+    });
+}
+
+#[test]
+fn offchain_worker_without_a_local_authority_key_skips_the_check() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        assert_ok!(Aura::sudo_set_authority_license_key(
+            RuntimeOrigin::root(),
+            UintAuthorityId(0).to_public_key(),
+            Some(b"key-0".to_vec()),
+        ));
+
+        handles.set_timestamp(60_000);
+        Aura::offchain_worker(1);
+        assert!(handles.submitted_calls().is_empty());
+    });
+}
+
+#[test]
+fn setting_authority_license_keys_requires_root() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_noop!(
+            Aura::sudo_set_authority_license_key(
+                RuntimeOrigin::signed(1),
+                UintAuthorityId(0).to_public_key(),
+                Some(b"key-0".to_vec()),
+            ),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Aura::sudo_set_authority_license_key(
+                RuntimeOrigin::root(),
+                UintAuthorityId(0).to_public_key(),
+                Some(vec![b'k'; 129]),
+            ),
+            pallet::Error::<Test>::LicenseKeyTooLong
+        );
+    });
+}
+
+#[test]
+fn license_request_includes_the_genesis_hash() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        let genesis = sp_core::H256::repeat_byte(0xab);
+        frame_system::BlockHash::<Test>::insert(0, genesis);
+
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::new(vec![http_ok(200, br#"{"valid":true}"#)]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+
+        assert_eq!(
+            client.requests[0].url,
+            format!(
+                "http://localhost:3000/license?key=test-license-key&genesis={}&instance={}",
+                to_hex(genesis.as_bytes()),
+                INSTANCE_ID_HEX
+            )
+        );
+    });
+}
+
+fn report_features(valid: bool, features: &[&str]) {
+    assert_ok!(Aura::offchain_worker_report_check(
+        RuntimeOrigin::none(),
+        LicenseCheckReport {
+            valid,
+            features: Some(features.iter().map(|f| f.as_bytes().to_vec()).collect()),
+            ..LicenseCheckReport::invalid()
+        }
+    ));
+}
+
+fn licensed_features() -> Vec<Vec<u8>> {
+    pallet::LicensedFeatures::<Test>::get()
+        .into_iter()
+        .map(|feature| feature.into_inner())
+        .collect()
+}
+
+#[test]
+fn licensed_features_within_the_limit_are_stored() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        report_features(true, &["bridge", "evm"]);
+
+        assert_eq!(
+            licensed_features(),
+            vec![b"bridge".to_vec(), b"evm".to_vec()]
+        );
+        assert!(aura_events().is_empty());
+
+        // An invalid check leaves the last licensed features in place.
+        report_features(false, &[]);
+        assert_eq!(licensed_features().len(), 2);
+    });
+}
+
+#[test]
+fn licensed_features_over_the_limit_keep_the_first_and_emit_an_event() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        report_features(true, &["a", "b", "c", "d", "e"]);
+
+        assert_eq!(
+            licensed_features(),
+            vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]
+        );
+        assert_eq!(
+            aura_events(),
+            vec![pallet::Event::FeaturesTruncated {
+                reported: 5,
+                kept: 3
+            }]
+        );
+    });
+}
+
+#[test]
+fn over_long_licensed_feature_names_are_skipped() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let long = "x".repeat(MAX_LICENSE_FEATURE_LEN as usize + 1);
+        report_features(true, &["a", &long, "b"]);
+
+        assert_eq!(licensed_features(), vec![b"a".to_vec(), b"b".to_vec()]);
+    });
+}
+
+#[test]
+fn a_one_off_feature_change_is_ignored() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        FeatureChangeConfirmations::set(3);
+        report_features(true, &["bridge"]);
+        report_features(true, &["bridge"]);
+        report_features(true, &["bridge"]);
+        assert_eq!(licensed_features(), vec![b"bridge".to_vec()]);
+
+        // A server briefly answering differently changes nothing.
+        report_features(true, &["bridge", "evm"]);
+        report_features(true, &["bridge"]);
+        report_features(true, &["bridge", "evm"]);
+        report_features(true, &["bridge", "evm"]);
+        assert_eq!(licensed_features(), vec![b"bridge".to_vec()]);
+        assert_eq!(
+            pallet::PendingFeatures::<Test>::get().map(|(_, seen)| seen),
+            Some(2)
+        );
+
+        // Nor do checks that report no features at all, or invalid ones.
+        report_check(true);
+        report_features(false, &[]);
+        report_features(true, &["bridge"]);
+        assert_eq!(pallet::PendingFeatures::<Test>::get(), None);
+    });
+}
+
+#[test]
+fn a_sustained_feature_change_is_applied() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        FeatureChangeConfirmations::set(3);
+        report_features(true, &["a", "b", "c", "d"]);
+        report_features(true, &["a", "b", "c", "d"]);
+        assert!(licensed_features().is_empty());
+        assert!(aura_events().is_empty());
+
+        // Counted between checks without features.
+        report_check(true);
+        report_features(true, &["a", "b", "c", "d"]);
+        assert_eq!(
+            licensed_features(),
+            vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]
+        );
+        assert_eq!(pallet::PendingFeatures::<Test>::get(), None);
+        assert_eq!(
+            aura_events(),
+            vec![pallet::Event::FeaturesTruncated {
+                reported: 4,
+                kept: 3
+            }]
+        );
+    });
+}
+
+#[test]
+fn offchain_worker_reports_licensed_features() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        handles.set_timestamp(60_000);
+        handles.expect_license_request(br#"{"valid":true,"features":["bridge", "evm"]}"#);
+        Aura::offchain_worker(1);
+
+        let features = handles
+            .submitted_calls()
+            .into_iter()
+            .find_map(|call| match call {
+                RuntimeCall::Aura(pallet::Call::offchain_worker_report_check { report }) => {
+                    report.features
+                }
+                _ => None,
+            });
+        assert_eq!(features, Some(vec![b"bridge".to_vec(), b"evm".to_vec()]));
+    });
+}
+
+fn offchain_calls() -> Vec<RuntimeCall> {
+    vec![
+        RuntimeCall::Aura(pallet::Call::offchain_worker_halt_production { reason: None }),
+        RuntimeCall::Aura(pallet::Call::offchain_worker_resume_production {}),
+        RuntimeCall::Aura(pallet::Call::offchain_worker_report_check {
+            report: LicenseCheckReport::invalid(),
+        }),
+    ]
+}
+
+#[test]
+fn safe_mode_blocks_offchain_calls_a_normal_halt_allows() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let timestamp = RuntimeCall::Timestamp(pallet_timestamp::Call::set { now: 1 });
+        let resume = RuntimeCall::Aura(pallet::Call::sudo_resume_production {});
+
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        for call in offchain_calls() {
+            assert!(
+                HaltFilter::contains(&call),
+                "{:?} is allowed while halted",
+                call
+            );
+        }
+
+        assert_ok!(Aura::sudo_enter_safe_mode(RuntimeOrigin::root(), None));
+        for call in offchain_calls() {
+            assert!(
+                !HaltFilter::contains(&call),
+                "{:?} is blocked in safe mode",
+                call
+            );
+        }
+        assert!(HaltFilter::contains(&timestamp));
+        assert!(HaltFilter::contains(&resume));
+        assert!(HaltFilter::contains(&RuntimeCall::Aura(
+            pallet::Call::sudo_force_resume {}
+        )));
+    });
+}
+
+fn note_license_term(expires_at: u64) {
+    assert_ok!(Aura::offchain_worker_report_check(
+        RuntimeOrigin::none(),
+        LicenseCheckReport {
+            valid: true,
+            issued_at: Some(1_000),
+            expires_at: Some(expires_at),
+            min_check_interval: None,
+            not_before: None,
+            features: None,
+        }
+    ));
+}
+
+#[test]
+fn sudo_resume_production_is_refused_once_the_license_term_expired() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        pallet_timestamp::Now::<Test>::put(5_000);
+        note_license_term(11_000);
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+
+        pallet_timestamp::Now::<Test>::put(11_000);
+        assert!(Aura::is_license_term_expired());
+        assert_noop!(
+            Aura::sudo_resume_production(RuntimeOrigin::root()),
+            pallet::Error::<Test>::LicenseExpired
+        );
+        assert!(Aura::is_halted());
+
+        // A renewed term lifts the block.
+        note_license_term(111_000);
+        assert_ok!(Aura::sudo_resume_production(RuntimeOrigin::root()));
+        assert!(!Aura::is_halted());
+    });
+}
+
+#[test]
+fn sudo_force_resume_ignores_an_expired_license_term() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        pallet_timestamp::Now::<Test>::put(5_000);
+        note_license_term(11_000);
+        assert_ok!(Aura::sudo_enter_safe_mode(RuntimeOrigin::root(), None));
+        pallet_timestamp::Now::<Test>::put(20_000);
+        System::reset_events();
+
+        assert_noop!(
+            Aura::sudo_force_resume(RuntimeOrigin::signed(1)),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(Aura::sudo_force_resume(RuntimeOrigin::root()));
+        assert!(!Aura::is_halted());
+        assert!(!Aura::is_safe_mode());
+        assert_eq!(
+            aura_events(),
+            vec![
+                pallet::Event::ProductionResumed,
+                pallet::Event::SafeModeExited,
+            ]
+        );
+    });
+}
+
+#[test]
+fn sudo_resume_production_leaves_safe_mode() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        assert_noop!(
+            Aura::sudo_enter_safe_mode(RuntimeOrigin::signed(1), None),
+            DispatchError::BadOrigin
+        );
+
+        assert_ok!(Aura::sudo_enter_safe_mode(RuntimeOrigin::root(), None));
+        assert!(Aura::is_halted());
+        assert!(Aura::is_safe_mode());
+
+        assert_ok!(Aura::sudo_resume_production(RuntimeOrigin::root()));
+        assert!(!Aura::is_halted());
+        assert!(!Aura::is_safe_mode());
+        assert_eq!(
+            aura_events(),
+            vec![
+                pallet::Event::ProductionHalted,
+                pallet::Event::SafeModeEntered,
+                pallet::Event::ProductionResumed,
+                pallet::Event::SafeModeExited,
+            ]
+        );
+    });
+}
+
+#[test]
+fn license_proof_cannot_lift_safe_mode() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        note_proof(None);
+        assert!(pallet::HaltedByLicenseProof::<Test>::get());
+
+        assert_ok!(Aura::sudo_enter_safe_mode(RuntimeOrigin::root(), None));
+        note_proof(Some(license_proof(true, PROOF_NOW)));
+
+        assert!(Aura::is_halted());
+        assert!(Aura::is_safe_mode());
+    });
+}
+
+#[test]
+fn changing_the_license_key_triggers_an_immediate_check() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        handles.set_timestamp(60_000);
+        handles.expect_license_request(br#"{"valid":true}"#);
+        Aura::offchain_worker(1);
+        assert_eq!(reported_checks(handles), 1);
+
+        // Within both the check interval and the cache TTL, the new key is checked right away.
+        System::set_block_number(2);
+        assert_ok!(Aura::set_license_key(
+            RuntimeOrigin::root(),
+            b"new-key".to_vec()
+        ));
+        assert_eq!(pallet::RecheckNow::<Test>::get(), Some(2));
+        handles.set_timestamp(65_000);
+        handles.expect_request(
+            &format!(
+                "http://localhost:3000/license?key=new-key&genesis={}&instance={}",
+                GENESIS_HASH_HEX, INSTANCE_ID_HEX
+            ),
+            br#"{"valid":true}"#,
+        );
+        Aura::offchain_worker(2);
+        assert_eq!(reported_checks(handles), 2);
+
+        report_check(true);
+        assert_eq!(pallet::RecheckNow::<Test>::get(), None);
+
+        // Afterwards the interval applies again.
+        handles.set_timestamp(70_000);
+        Aura::offchain_worker(3);
+        assert_eq!(reported_checks(handles), 2);
+    });
+}
+
+#[test]
+fn setting_the_same_license_key_does_not_trigger_a_recheck() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_ok!(Aura::set_license_key(
+            RuntimeOrigin::root(),
+            b"test-license-key".to_vec()
+        ));
+        assert_eq!(pallet::RecheckNow::<Test>::get(), None);
+    });
+}
+
+#[test]
+fn licensed_streaks_grow_with_valid_reports_and_reset_on_halt() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        for (block, current, longest) in [(10, 0, 0), (20, 10, 10), (35, 25, 25)] {
+            System::set_block_number(block);
+            report_check(true);
+            assert_eq!(Aura::licensed_streaks(), (current, longest));
+        }
+
+        // A halt ends the streak, and valid reports while halted do not start a new one.
+        System::set_block_number(40);
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert_eq!(Aura::licensed_streaks(), (0, 25));
+        System::set_block_number(45);
+        report_check(true);
+        assert_eq!(Aura::licensed_streaks(), (0, 25));
+
+        assert_ok!(Aura::sudo_resume_production(RuntimeOrigin::root()));
+        for (block, current, longest) in [(50, 0, 25), (70, 20, 25), (90, 40, 40)] {
+            System::set_block_number(block);
+            report_check(true);
+            assert_eq!(Aura::licensed_streaks(), (current, longest));
+        }
+    });
+}
+
+#[test]
+fn invalid_report_ends_the_licensed_streak() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(10);
+        report_check(true);
+        System::set_block_number(30);
+        report_check(true);
+
+        System::set_block_number(40);
+        report_check(false);
+        assert_eq!(Aura::licensed_streaks(), (0, 20));
+
+        System::set_block_number(50);
+        report_check(true);
+        System::set_block_number(55);
+        report_check(true);
+        assert_eq!(Aura::licensed_streaks(), (5, 20));
+    });
+}
+
+#[test]
+fn check_not_halted_rejects_blocked_calls_at_validation() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+        let resume = RuntimeCall::Aura(pallet::Call::sudo_resume_production {});
+        let info = DispatchInfo::default();
+        let halted: TransactionValidityError = InvalidTransaction::Custom(INVALID_TX_HALTED).into();
+
+        assert_ok!(CheckNotHalted::<Test>::new().validate(&1, &remark, &info, 0));
+
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert_eq!(
+            CheckNotHalted::<Test>::new().validate(&1, &remark, &info, 0),
+            Err(halted)
+        );
+        assert_eq!(
+            CheckNotHalted::<Test>::new().pre_dispatch(&1, &remark, &info, 0),
+            Err(halted)
+        );
+        assert_ok!(CheckNotHalted::<Test>::new().validate(&1, &resume, &info, 0));
+        assert_ok!(CheckNotHalted::<Test>::new().pre_dispatch(&1, &resume, &info, 0));
+    });
+}
+
+#[test]
+fn not_before_is_enforced_while_the_offchain_clock_is_within_drift() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        pallet_timestamp::Now::<Test>::put(1_000_000);
+        handles.set_timestamp(1_000_000 + MAX_CLOCK_DRIFT);
+        handles.expect_license_request(br#"{"valid":true,"not_before":2000000}"#);
+        Aura::offchain_worker(1);
+
+        assert_eq!(reported_validity(handles), vec![false]);
+    });
+}
+
+#[test]
+fn not_before_is_ignored_when_the_offchain_clock_drifts_too_far() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        // The chain is past `not_before`, but the node's clock lags far behind.
+        pallet_timestamp::Now::<Test>::put(1_000_000);
+        handles.set_timestamp(1_000_000 - MAX_CLOCK_DRIFT - 1);
+        handles.expect_license_request(br#"{"valid":true,"not_before":990000}"#);
+        Aura::offchain_worker(1);
+
+        assert_eq!(reported_validity(handles), vec![true]);
+
+        // No halt is queued for the next run.
+        handles.set_timestamp(1_000_000);
+        Aura::offchain_worker(2);
+        assert_eq!(submitted_halt_reason(handles), None);
+    });
+}
+
+#[test]
+fn configured_halt_origin_can_halt_resume_and_set_the_key() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let admin = RuntimeOrigin::signed(HaltAdmin::get());
+
+        assert_ok!(Aura::sudo_halt_production(admin.clone(), None));
+        assert!(Aura::is_halted());
+        assert_ok!(Aura::sudo_resume_production(admin.clone()));
+        assert!(!Aura::is_halted());
+        assert_ok!(Aura::set_license_key(admin, b"admin-key".to_vec()));
+        assert_eq!(
+            pallet::LicenseKey::<Test>::get().unwrap().to_vec(),
+            b"admin-key".to_vec()
+        );
+    });
+}
+
+#[test]
+fn unauthorized_origin_cannot_halt_resume_or_set_the_key() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let other = RuntimeOrigin::signed(HaltAdmin::get() + 1);
+
+        assert_noop!(
+            Aura::sudo_halt_production(other.clone(), None),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Aura::sudo_resume_production(other.clone()),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Aura::set_license_key(other, b"other-key".to_vec()),
+            DispatchError::BadOrigin
+        );
+    });
+}
+
+fn halt_requested() -> bool {
+    sp_runtime::offchain::storage::StorageValueRef::persistent(b"licensed_aura::halt_requested")
+        .get::<bool>()
+        .unwrap_or(None)
+        .unwrap_or(false)
+}
+
+#[test]
+fn failed_checks_during_warm_up_do_not_request_a_halt() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        OffchainWarmupChecks::set(1);
+
+        handles.set_timestamp(60_000);
+        handles.expect_license_request(br#"{"valid":false}"#);
+        Aura::offchain_worker(1);
+        assert_eq!(reported_validity(handles), vec![false]);
+        assert!(!halt_requested());
+
+        // Once warmed up, a failure requests a halt as usual.
+        handles.set_timestamp(60_000 + LICENSE_CHECK_INTERVAL);
+        handles.expect_license_request(br#"{"valid":false}"#);
+        Aura::offchain_worker(2);
+        assert!(halt_requested());
+    });
+}
+
+#[test]
+fn warm_up_is_skipped_once_the_count_is_reached() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        OffchainWarmupChecks::set(1);
+        sp_runtime::offchain::storage::StorageValueRef::persistent(WARMUP_CHECKS_STORAGE_KEY)
+            .set(&1u32);
+
+        handles.set_timestamp(60_000);
+        handles.expect_license_request(br#"{"valid":false}"#);
+        Aura::offchain_worker(1);
+        assert!(halt_requested());
+    });
+}
+
+#[test]
+fn calls_of_allowed_pallets_pass_while_halted() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let system = 0;
+        HaltAllowedPallets::set(vec![system]);
+        let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![1] });
+        let set_key = RuntimeCall::Aura(pallet::Call::set_license_key {
+            license_key: b"other-key".to_vec(),
+        });
+
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert!(HaltFilter::contains(&remark));
+        assert!(!HaltFilter::contains(&set_key));
+
+        // Safe mode does not consult the allowed pallets.
+        assert_ok!(Aura::sudo_enter_safe_mode(RuntimeOrigin::root(), None));
+        assert!(!HaltFilter::contains(&remark));
+    });
+}
+
+#[test]
+fn calls_of_other_pallets_stay_blocked_while_halted() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let timestamp = 1;
+        HaltAllowedPallets::set(vec![timestamp]);
+        let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![1] });
+
+        assert!(HaltFilter::contains(&remark));
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert!(!HaltFilter::contains(&remark));
+    });
+}
+
+#[test]
+fn halt_whitelist_lists_calls_permitted_while_halted() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let timestamp = 1;
+        let aura = 2;
+        assert_eq!(
+            HaltFilter::halt_whitelist(),
+            vec![
+                (timestamp, 0),
+                (aura, 1),
+                (aura, 2),
+                (aura, 4),
+                (aura, 5),
+                (aura, 7),
+                (aura, 13),
+                (aura, 16),
+                (aura, 18),
+                (aura, 19),
+                (aura, 22),
+                (aura, 24),
+                (aura, 26),
+            ]
+        );
+    });
+}
+
+#[test]
+fn halt_filter_config_reflects_the_whitelists_and_stance() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let system = 0;
+        HaltAllowedPallets::set(vec![system]);
+        let config = HaltFilter::config();
+        assert_eq!(config.stance, HaltFilterStance::Allow);
+        assert_eq!(config.allowed_pallets, vec![system]);
+        assert_eq!(config.allowed_calls, HaltFilter::halt_whitelist());
+        assert!(!config.safe_mode);
+
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert_eq!(HaltFilter::config().stance, HaltFilterStance::Deny);
+
+        assert_ok!(Aura::sudo_enter_safe_mode(RuntimeOrigin::root(), None));
+        let config = HaltFilter::config();
+        assert_eq!(config.stance, HaltFilterStance::Deny);
+        assert!(config.safe_mode);
+    });
+}
+
+#[test]
+fn halt_whitelist_matches_the_encoding_of_permitted_calls() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        let whitelist = HaltFilter::halt_whitelist();
+
+        for call in [
+            RuntimeCall::Timestamp(pallet_timestamp::Call::set { now: 0 }),
+            RuntimeCall::Aura(pallet::Call::sudo_resume_production {}),
+            RuntimeCall::Aura(pallet::Call::offchain_worker_resume_production {}),
+            RuntimeCall::Aura(pallet::Call::offchain_worker_report_check {
+                report: LicenseCheckReport::invalid(),
+            }),
+        ] {
+            assert!(HaltFilter::contains(&call));
+            let encoded = call.encode();
+            assert!(whitelist.contains(&(encoded[0], encoded[1])), "{:?}", call);
+        }
+
+        let blocked = RuntimeCall::Aura(pallet::Call::sudo_force_fresh_check {});
+        assert!(!HaltFilter::contains(&blocked));
+        let encoded = blocked.encode();
+        assert!(!whitelist.contains(&(encoded[0], encoded[1])));
+    });
+}
+
+#[test]
+fn auto_recovery_is_disabled_by_default() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(5);
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert_eq!(pallet::HaltedAtBlock::<Test>::get(), Some(5));
+        assert_eq!(Aura::auto_recovery_block(), None);
+    });
+}
+
+#[test]
+fn auto_recovery_block_counts_the_configured_delay_from_the_halt() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        AutoRecoveryDelay::set(10);
+        System::set_block_number(5);
+        assert_eq!(Aura::auto_recovery_block(), None);
+
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert_eq!(Aura::auto_recovery_block(), Some(15));
+
+        // Halting again does not push recovery back.
+        System::set_block_number(8);
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert_eq!(Aura::auto_recovery_block(), Some(15));
+
+        assert_ok!(Aura::sudo_resume_production(RuntimeOrigin::root()));
+        assert_eq!(Aura::auto_recovery_block(), None);
+        assert_eq!(pallet::HaltedAtBlock::<Test>::get(), None);
+    });
+}
+
+#[test]
+fn auto_recovery_delay_below_the_minimum_is_clamped() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        capture_logs();
+        AutoRecoveryDelay::set(1);
+        System::set_block_number(5);
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+
+        let floor = MIN_AUTO_RECOVERY_BLOCKS as u64;
+        assert_eq!(Aura::auto_recovery_delay(), floor);
+        assert_eq!(Aura::auto_recovery_block(), Some(5 + floor));
+        assert!(captured_logs().iter().any(|(level, message)| {
+            *level == log::Level::Warn && message.contains("below the minimum")
+        }));
+
+        // The override is clamped too, while zero still disables auto-recovery.
+        assert_ok!(Aura::sudo_set_auto_recovery_delay(
+            RuntimeOrigin::root(),
+            Some(2)
+        ));
+        assert_eq!(Aura::auto_recovery_block(), Some(5 + floor));
+        assert_ok!(Aura::sudo_set_auto_recovery_delay(
+            RuntimeOrigin::root(),
+            Some(0)
+        ));
+        assert_eq!(Aura::auto_recovery_block(), None);
+
+        System::set_block_number(6);
+        Aura::on_initialize(6);
+        assert!(Aura::is_halted());
+    });
+}
+
+#[test]
+fn auto_recovery_override_takes_precedence_over_config() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        AutoRecoveryDelay::set(10);
+        System::set_block_number(5);
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+
+        assert_ok!(Aura::sudo_set_auto_recovery_delay(
+            RuntimeOrigin::root(),
+            Some(12)
+        ));
+        assert_eq!(Aura::auto_recovery_block(), Some(17));
+
+        // A zero override disables auto-recovery despite the config.
+        assert_ok!(Aura::sudo_set_auto_recovery_delay(
+            RuntimeOrigin::root(),
+            Some(0)
+        ));
+        assert_eq!(Aura::auto_recovery_block(), None);
+
+        assert_ok!(Aura::sudo_set_auto_recovery_delay(
+            RuntimeOrigin::root(),
+            None
+        ));
+        assert_eq!(Aura::auto_recovery_block(), Some(15));
+
+        // An override also enables auto-recovery when the config disables it.
+        AutoRecoveryDelay::set(0);
+        assert_eq!(Aura::auto_recovery_block(), None);
+        assert_ok!(Aura::sudo_set_auto_recovery_delay(
+            RuntimeOrigin::root(),
+            Some(20)
+        ));
+        assert_eq!(Aura::auto_recovery_block(), Some(25));
+
+        assert_noop!(
+            Aura::sudo_set_auto_recovery_delay(RuntimeOrigin::signed(1), None),
+            DispatchError::BadOrigin
+        );
+    });
+}
+
+#[test]
+fn safe_mode_is_never_auto_recovered() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        AutoRecoveryDelay::set(10);
+        System::set_block_number(5);
+        assert_ok!(Aura::sudo_enter_safe_mode(RuntimeOrigin::root(), None));
+        assert_eq!(Aura::auto_recovery_block(), None);
+
+        Aura::on_initialize(15);
+        assert!(Aura::is_halted());
+    });
+}
+
+#[test]
+fn production_resumes_at_the_auto_recovery_block() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        AutoRecoveryDelay::set(10);
+        System::set_block_number(5);
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+
+        System::set_block_number(14);
+        Aura::on_initialize(14);
+        assert!(Aura::is_halted());
+
+        System::set_block_number(15);
+        Aura::on_initialize(15);
+        assert!(!Aura::is_halted());
+        assert_eq!(Aura::auto_recovery_block(), None);
+        assert!(aura_events().contains(&pallet::Event::AutoRecovered));
+    });
+}
+
+#[test]
+fn paused_auto_recovery_keeps_the_chain_halted() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        AutoRecoveryDelay::set(10);
+        System::set_block_number(5);
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert_noop!(
+            Aura::sudo_set_auto_recovery_paused(RuntimeOrigin::signed(1), true),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(Aura::sudo_set_auto_recovery_paused(
+            RuntimeOrigin::root(),
+            true
+        ));
+        assert_eq!(Aura::auto_recovery_block(), None);
+
+        for block in 15..=20 {
+            System::set_block_number(block);
+            Aura::on_initialize(block);
+            assert!(Aura::is_halted());
+        }
+        assert!(!aura_events().contains(&pallet::Event::AutoRecovered));
+    });
+}
+
+#[test]
+fn unpaused_auto_recovery_is_eligible_again() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        AutoRecoveryDelay::set(10);
+        System::set_block_number(5);
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert_ok!(Aura::sudo_set_auto_recovery_paused(
+            RuntimeOrigin::root(),
+            true
+        ));
+        System::set_block_number(20);
+        Aura::on_initialize(20);
+        assert!(Aura::is_halted());
+
+        assert_ok!(Aura::sudo_set_auto_recovery_paused(
+            RuntimeOrigin::root(),
+            false
+        ));
+        assert_eq!(Aura::auto_recovery_block(), Some(15));
+
+        // The recovery block has passed, so the next block recovers.
+        System::set_block_number(21);
+        Aura::on_initialize(21);
+        assert!(!Aura::is_halted());
+        assert_eq!(
+            aura_events(),
+            vec![
+                pallet::Event::ProductionHalted,
+                pallet::Event::AutoRecoveryPauseSet { paused: true },
+                pallet::Event::AutoRecoveryPauseSet { paused: false },
+                pallet::Event::ProductionResumed,
+                pallet::Event::AutoRecovered,
+            ]
+        );
+    });
+}
+
+#[test]
+fn simulated_halt_lasts_exactly_the_given_blocks() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        AutoRecoveryDelay::set(10);
+        System::set_block_number(5);
+        assert_ok!(Aura::sudo_simulate_halt(
+            RuntimeOrigin::root(),
+            b"drill".to_vec(),
+            30
+        ));
+        assert_eq!(
+            pallet::HaltReason::<Test>::get().unwrap().to_vec(),
+            [REASON_SIMULATION_PREFIX, &b"drill"[..]].concat()
+        );
+
+        // Neither auto-recovery nor a valid license check ends the simulation early.
+        assert_ok!(Aura::offchain_worker_resume_production(
+            RuntimeOrigin::none()
+        ));
+        for block in 6..35 {
+            System::set_block_number(block);
+            Aura::on_initialize(block);
+            assert!(Aura::is_halted(), "resumed early at block {block}");
+        }
+
+        System::set_block_number(35);
+        Aura::on_initialize(35);
+        assert!(!Aura::is_halted());
+        assert_eq!(pallet::SimulatedHaltUntil::<Test>::get(), None);
+        assert_eq!(
+            aura_events(),
+            vec![
+                pallet::Event::SimulatedHaltStarted {
+                    reason: b"drill".to_vec(),
+                    until: 35
+                },
+                pallet::Event::SimulatedHaltEnded,
+            ]
+        );
+    });
+}
+
+#[test]
+fn simulated_halt_requires_a_running_chain_and_at_least_one_block() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(5);
+        assert_noop!(
+            Aura::sudo_simulate_halt(RuntimeOrigin::signed(1), b"drill".to_vec(), 3),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Aura::sudo_simulate_halt(RuntimeOrigin::root(), b"drill".to_vec(), 0),
+            pallet::Error::<Test>::EmptySimulatedHalt
+        );
+
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert_noop!(
+            Aura::sudo_simulate_halt(RuntimeOrigin::root(), b"drill".to_vec(), 3),
+            pallet::Error::<Test>::AlreadyHalted
+        );
+    });
+}
+
+#[test]
+fn real_halt_takes_over_from_a_simulated_one() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(5);
+        assert_ok!(Aura::sudo_simulate_halt(
+            RuntimeOrigin::root(),
+            b"drill".to_vec(),
+            3
+        ));
+        assert_ok!(Aura::offchain_worker_halt_production(
+            RuntimeOrigin::none(),
+            Some(b"License invalid".to_vec())
+        ));
+
+        System::set_block_number(8);
+        Aura::on_initialize(8);
+        assert!(Aura::is_halted());
+        assert!(!aura_events().contains(&pallet::Event::SimulatedHaltEnded));
+    });
+}
+
+#[test]
+fn halt_reason_counts_aggregate_the_halt_log_by_kind() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        let halts: [fn() -> DispatchResult; 5] = [
+            || Aura::sudo_halt_production(RuntimeOrigin::root(), None),
+            || {
+                Aura::offchain_worker_halt_production(
+                    RuntimeOrigin::none(),
+                    Some(REASON_LICENSE_UNREACHABLE.to_vec()),
+                )
+            },
+            || {
+                Aura::offchain_worker_halt_production(
+                    RuntimeOrigin::none(),
+                    Some(REASON_LICENSE_INVALID.to_vec()),
+                )
+            },
+            || Aura::sudo_halt_production(RuntimeOrigin::root(), Some(b"maintenance".to_vec())),
+            || {
+                Aura::offchain_worker_halt_production(
+                    RuntimeOrigin::none(),
+                    Some(REASON_LICENSE_UNREACHABLE.to_vec()),
+                )
+            },
+        ];
+        for halt in halts {
+            assert_ok!(halt());
+            // Halting an already halted chain is not a new halt.
+            assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+            assert_ok!(Aura::sudo_force_resume(RuntimeOrigin::root()));
+        }
+
+        assert_eq!(pallet::HaltLog::<Test>::get().len(), 5);
+        assert_eq!(
+            Aura::halt_reason_counts(),
+            vec![
+                (HaltReasonKind::Manual, 2),
+                (HaltReasonKind::LicenseInvalid, 1),
+                (HaltReasonKind::NetworkFailure, 2),
+            ]
+        );
+    });
+}
+
+/// The license provider's key pair for perpetual license tokens.
+fn license_provider() -> sp_core::ed25519::Pair {
+    use sp_core::Pair;
+    sp_core::ed25519::Pair::from_seed(&[7; 32])
+}
+
+/// `token` signed by [`license_provider`] for this chain.
+fn perpetual_license_signature(token: &[u8]) -> sp_core::ed25519::Signature {
+    use sp_core::Pair;
+    license_provider().sign(&Aura::perpetual_license_message(token))
+}
+
+#[test]
+fn perpetual_license_stops_checks_and_license_halts() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        LicensePublicKey::set(Some(license_provider().public().0));
+        System::set_block_number(1);
+        assert_ok!(Aura::offchain_worker_halt_production(
+            RuntimeOrigin::none(),
+            Some(REASON_LICENSE_INVALID.to_vec())
+        ));
+
+        let token = b"perpetual:chain-1".to_vec();
+        let signature = perpetual_license_signature(&token);
+        assert!(HaltFilter::contains(&RuntimeCall::Aura(
+            pallet::Call::install_perpetual_license {
+                token: token.clone(),
+                signature,
+            }
+        )));
+        assert_ok!(Aura::install_perpetual_license(
+            RuntimeOrigin::signed(1),
+            token,
+            signature
+        ));
+        assert!(Aura::is_perpetually_licensed());
+        assert!(!Aura::is_halted());
+        assert!(aura_events().ends_with(&[
+            pallet::Event::PerpetualLicenseInstalled,
+            pallet::Event::ProductionResumed,
+        ]));
+
+        // Offchain workers no longer check, and their halts are ignored.
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::new(vec![]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        assert!(client.requests.is_empty());
+        assert_ok!(Aura::offchain_worker_halt_production(
+            RuntimeOrigin::none(),
+            Some(REASON_LICENSE_INVALID.to_vec())
+        ));
+        assert!(!Aura::is_halted());
+    });
+}
+
+#[test]
+fn perpetual_license_with_a_bad_signature_is_rejected() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let token = b"perpetual:chain-1".to_vec();
+        let signature = perpetual_license_signature(&token);
+        assert_noop!(
+            Aura::install_perpetual_license(RuntimeOrigin::signed(1), token.clone(), signature),
+            pallet::Error::<Test>::NoLicensePublicKey
+        );
+
+        LicensePublicKey::set(Some(license_provider().public().0));
+        let other_token = b"perpetual:chain-2".to_vec();
+        assert_noop!(
+            Aura::install_perpetual_license(RuntimeOrigin::signed(1), other_token, signature),
+            pallet::Error::<Test>::BadPerpetualLicenseSignature
+        );
+        let forged = {
+            use sp_core::Pair;
+            sp_core::ed25519::Pair::from_seed(&[8; 32])
+                .sign(&Aura::perpetual_license_message(&token))
+        };
+        assert_noop!(
+            Aura::install_perpetual_license(RuntimeOrigin::signed(1), token.clone(), forged),
+            pallet::Error::<Test>::BadPerpetualLicenseSignature
+        );
+        assert_noop!(
+            Aura::install_perpetual_license(RuntimeOrigin::none(), token.clone(), signature),
+            DispatchError::BadOrigin
+        );
+        assert!(!Aura::is_perpetually_licensed());
+
+        // A manual halt is the operator's and stays in place.
+        System::set_block_number(1);
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert_ok!(Aura::install_perpetual_license(
+            RuntimeOrigin::signed(1),
+            token.clone(),
+            signature
+        ));
+        assert!(Aura::is_halted());
+        assert_noop!(
+            Aura::install_perpetual_license(RuntimeOrigin::signed(1), token, signature),
+            pallet::Error::<Test>::AlreadyPerpetuallyLicensed
+        );
+    });
+}
+
+#[test]
+fn perpetual_license_keeps_a_manual_halt_over_a_license_halt() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        LicensePublicKey::set(Some(license_provider().public().0));
+        System::set_block_number(1);
+        assert_ok!(Aura::offchain_worker_halt_production(
+            RuntimeOrigin::none(),
+            Some(REASON_LICENSE_INVALID.to_vec())
+        ));
+        System::set_block_number(2);
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        // The halt is still logged under the kind it started with.
+        assert_eq!(
+            Aura::current_halt_kind(),
+            Some(HaltReasonKind::LicenseInvalid)
+        );
+
+        let token = b"perpetual:chain-1".to_vec();
+        let signature = perpetual_license_signature(&token);
+        assert_ok!(Aura::install_perpetual_license(
+            RuntimeOrigin::signed(1),
+            token,
+            signature
+        ));
+
+        assert!(Aura::is_perpetually_licensed());
+        assert!(Aura::is_halted());
+    });
+}
+
+fn heartbeats() -> Vec<pallet::Event<Test>> {
+    aura_events()
+        .into_iter()
+        .filter(|event| matches!(event, pallet::Event::EnforcementHeartbeat { .. }))
+        .collect()
+}
+
+#[test]
+fn heartbeat_is_emitted_at_the_configured_cadence() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        HeartbeatInterval::set(5);
+        for block in 1..=10 {
+            System::set_block_number(block);
+            Aura::on_initialize(block);
+            match block {
+                2 | 3 => report_check(false),
+                7 => assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None)),
+                8 => report_check(true),
+                _ => {}
+            }
+        }
+
+        assert_eq!(
+            heartbeats(),
+            vec![
+                pallet::Event::EnforcementHeartbeat {
+                    halted: false,
+                    last_check_age: Some(2),
+                    consecutive_failures: 2,
+                },
+                pallet::Event::EnforcementHeartbeat {
+                    halted: true,
+                    last_check_age: Some(2),
+                    consecutive_failures: 0,
+                },
+            ]
+        );
+    });
+}
+
+#[test]
+fn heartbeat_reports_no_check_age_before_the_first_check_and_can_be_disabled() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        HeartbeatInterval::set(3);
+        System::set_block_number(3);
+        Aura::on_initialize(3);
+        assert_eq!(
+            heartbeats(),
+            vec![pallet::Event::EnforcementHeartbeat {
+                halted: false,
+                last_check_age: None,
+                consecutive_failures: 0,
+            }]
+        );
+
+        HeartbeatInterval::set(0);
+        System::reset_events();
+        System::set_block_number(6);
+        Aura::on_initialize(6);
+        assert_eq!(heartbeats(), vec![]);
+    });
+}
+
+#[test]
+fn successes_while_halted_are_counted_until_a_failure_or_resume() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        report_check(true);
+        assert_eq!(Aura::consecutive_successes_while_halted(), 0);
+
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        report_check(true);
+        report_check(true);
+        assert_eq!(Aura::consecutive_successes_while_halted(), 2);
+
+        report_check(false);
+        assert_eq!(Aura::consecutive_successes_while_halted(), 0);
+        report_check(true);
+        assert_eq!(Aura::consecutive_successes_while_halted(), 1);
+
+        assert_ok!(Aura::sudo_resume_production(RuntimeOrigin::root()));
+        assert_eq!(Aura::consecutive_successes_while_halted(), 0);
+    });
+}
+
+#[test]
+fn failure_counter_can_be_reset_by_root() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        report_check(false);
+        report_check(false);
+        assert_eq!(pallet::ConsecutiveCheckFailures::<Test>::get(), 2);
+
+        assert_noop!(
+            Aura::sudo_reset_failure_counter(RuntimeOrigin::signed(1)),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(Aura::sudo_reset_failure_counter(RuntimeOrigin::root()));
+        assert_eq!(pallet::ConsecutiveCheckFailures::<Test>::get(), 0);
+        assert!(aura_events().contains(&pallet::Event::FailureCounterReset { previous: 2 }));
+
+        // The next failure counts from zero again.
+        report_check(false);
+        assert_eq!(pallet::ConsecutiveCheckFailures::<Test>::get(), 1);
+    });
+}
+
+#[test]
+fn offchain_halt_keeps_the_reason_of_a_manual_halt() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        assert_ok!(Aura::sudo_halt_production(
+            RuntimeOrigin::root(),
+            Some(b"maintenance".to_vec())
+        ));
+        assert_ok!(Aura::offchain_worker_halt_production(
+            RuntimeOrigin::none(),
+            Some(REASON_LICENSE_INVALID.to_vec())
+        ));
+        assert!(Aura::is_halted());
+        assert_eq!(Aura::halt_reason_string(), Some("maintenance".to_string()));
+
+        // Another manual halt still replaces it.
+        assert_ok!(Aura::sudo_halt_production(
+            RuntimeOrigin::root(),
+            Some(b"upgrade".to_vec())
+        ));
+        assert_eq!(Aura::halt_reason_string(), Some("upgrade".to_string()));
+    });
+}
+
+#[test]
+fn manual_halt_on_an_offchain_halt_keeps_its_reason() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        assert_ok!(Aura::offchain_worker_halt_production(
+            RuntimeOrigin::none(),
+            Some(REASON_LICENSE_UNREACHABLE.to_vec())
+        ));
+        // The halt was logged as the offchain worker's, but the operator now owns it.
+        assert_ok!(Aura::sudo_halt_production(
+            RuntimeOrigin::root(),
+            Some(b"maintenance".to_vec())
+        ));
+        assert_ok!(Aura::offchain_worker_halt_production(
+            RuntimeOrigin::none(),
+            Some(REASON_LICENSE_INVALID.to_vec())
+        ));
+        assert_eq!(Aura::halt_reason_string(), Some("maintenance".to_string()));
+
+        // Once resumed, an offchain halt records its own reason again.
+        assert_ok!(Aura::sudo_resume_production(RuntimeOrigin::root()));
+        assert_ok!(Aura::offchain_worker_halt_production(
+            RuntimeOrigin::none(),
+            Some(REASON_LICENSE_INVALID.to_vec())
+        ));
+        assert_eq!(
+            Aura::halt_reason_string().unwrap().as_bytes(),
+            REASON_LICENSE_INVALID
+        );
+    });
+}
+
+#[test]
+fn offchain_halt_replaces_the_reason_of_a_license_halt() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        assert_ok!(Aura::offchain_worker_halt_production(
+            RuntimeOrigin::none(),
+            Some(REASON_LICENSE_UNREACHABLE.to_vec())
+        ));
+        assert_ok!(Aura::offchain_worker_halt_production(
+            RuntimeOrigin::none(),
+            Some(REASON_LICENSE_INVALID.to_vec())
+        ));
+        assert_eq!(
+            pallet::HaltReason::<Test>::get().unwrap().to_vec(),
+            REASON_LICENSE_INVALID.to_vec()
+        );
+    });
+}
+
+#[test]
+fn halt_log_keeps_only_the_most_recent_halts() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        assert_ok!(Aura::offchain_worker_halt_production(
+            RuntimeOrigin::none(),
+            None
+        ));
+        assert_ok!(Aura::sudo_force_resume(RuntimeOrigin::root()));
+        for _ in 0..HALT_LOG_LEN {
+            assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+            assert_ok!(Aura::sudo_force_resume(RuntimeOrigin::root()));
+        }
+
+        assert_eq!(pallet::HaltLog::<Test>::get().len() as u32, HALT_LOG_LEN);
+        assert_eq!(
+            Aura::halt_reason_counts(),
+            vec![(HaltReasonKind::Manual, HALT_LOG_LEN)]
+        );
+    });
+}
+
+/// A minimal gzip member holding `data`.
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];
+    out.extend(miniz_oxide::deflate::compress_to_vec(data, 6));
+    out.extend(crc32(data).to_le_bytes());
+    out.extend((data.len() as u32).to_le_bytes());
+    out
+}
+
+fn http_gzip(body: &[u8]) -> Result<HttpResponse, HttpFailure> {
+    Ok(HttpResponse {
+        code: 200,
+        body: gzip(body),
+        content_encoding: Some("gzip".into()),
+    })
+}
+
+#[test]
+fn crc32_matches_the_check_value() {
+    assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+}
+
+#[test]
+fn gunzip_round_trips_and_checks_the_trailer() {
+    let body = br#"{"valid":true,"features":["a","b"]}"#;
+    assert_eq!(gunzip(&gzip(body), 1024), Ok(body.to_vec()));
+
+    let mut corrupted = gzip(body);
+    let crc_at = corrupted.len() - 8;
+    corrupted[crc_at] ^= 0xff;
+    assert_eq!(gunzip(&corrupted, 1024), Err(BodyError::InvalidGzip));
+
+    assert_eq!(gunzip(body, 1024), Err(BodyError::InvalidGzip));
+}
+
+#[test]
+fn decoded_body_rejects_unknown_encodings_and_oversized_bodies() {
+    let response = |encoding: Option<&str>, body: Vec<u8>| HttpResponse {
+        code: 200,
+        body,
+        content_encoding: encoding.map(Into::into),
+    };
+
+    assert_eq!(
+        response(None, b"{}".to_vec()).decoded_body(2),
+        Ok(b"{}".to_vec())
+    );
+    assert_eq!(
+        response(Some("GZIP"), gzip(b"{}")).decoded_body(16),
+        Ok(b"{}".to_vec())
+    );
+    assert_eq!(
+        response(Some("br"), b"{}".to_vec()).decoded_body(2),
+        Err(BodyError::UnsupportedEncoding)
+    );
+    assert_eq!(
+        response(None, b"{}".to_vec()).decoded_body(1),
+        Err(BodyError::TooLarge)
+    );
+}
+
+#[test]
+fn offchain_worker_accepts_gzip_encoded_responses() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::new(vec![http_gzip(br#"{"valid":true}"#)]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        assert_eq!(reported_validity(handles), vec![true]);
+    });
+}
+
+#[test]
+fn offchain_worker_rejects_gzip_responses_inflating_past_the_limit() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        // Compresses to a few dozen bytes but inflates far past the limit.
+        let mut bomb = br#"{"valid":true,"padding":""#.to_vec();
+        bomb.extend(vec![b' '; MAX_RESPONSE_BYTES as usize * 256]);
+        bomb.extend(br#""}"#);
+        assert!(gzip(&bomb).len() < MAX_RESPONSE_BYTES as usize);
+
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::new(vec![http_gzip(&bomb)]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        assert_eq!(reported_validity(handles), vec![false]);
+    });
+}
+
+fn check_failed_events() -> Vec<u32> {
+    aura_events()
+        .into_iter()
+        .filter_map(|event| match event {
+            pallet::Event::LicenseCheckFailed { suppressed } => Some(suppressed),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn failed_check_events_are_throttled() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        EventThrottleBlocks::set(10);
+
+        for block in 1..=10 {
+            System::set_block_number(block);
+            report_check(false);
+        }
+        assert_eq!(check_failed_events(), vec![0]);
+        assert_eq!(pallet::SuppressedCheckFailedEvents::<Test>::get(), 9);
+
+        // The next failure after the window reports how many were held back.
+        System::set_block_number(11);
+        report_check(false);
+        assert_eq!(check_failed_events(), vec![0, 9]);
+        assert_eq!(pallet::SuppressedCheckFailedEvents::<Test>::get(), 0);
+        assert_eq!(pallet::LastCheckFailedEventAt::<Test>::get(), Some(11));
+    });
+}
+
+#[test]
+fn rapid_halts_emit_a_single_halt_event() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        HaltEventDebounce::set(5);
+        let halted_events = || {
+            aura_events()
+                .into_iter()
+                .filter(|event| *event == pallet::Event::ProductionHalted)
+                .count()
+        };
+
+        for block in 1..=5 {
+            System::set_block_number(block);
+            assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+            assert!(Aura::is_halted());
+            assert_ok!(Aura::sudo_resume_production(RuntimeOrigin::root()));
+        }
+        assert_eq!(halted_events(), 1);
+        assert_eq!(pallet::LastHaltEventAt::<Test>::get(), Some(1));
+
+        // A halt once the window has passed is reported again.
+        System::set_block_number(6);
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert_eq!(halted_events(), 2);
+        assert_eq!(pallet::LastHaltEventAt::<Test>::get(), Some(6));
+    });
+}
+
+#[test]
+fn valid_checks_emit_no_failed_check_event() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        EventThrottleBlocks::set(10);
+
+        System::set_block_number(1);
+        report_check(true);
+        assert!(check_failed_events().is_empty());
+
+        System::set_block_number(2);
+        report_check(false);
+        System::set_block_number(3);
+        report_check(true);
+        assert_eq!(check_failed_events(), vec![0]);
+        assert_eq!(pallet::SuppressedCheckFailedEvents::<Test>::get(), 0);
+    });
+}
+
+#[test]
+fn zero_throttle_emits_every_failed_check() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        for block in 1..=3 {
+            System::set_block_number(block);
+            report_check(false);
+            report_check(false);
+        }
+        assert_eq!(check_failed_events(), vec![0; 6]);
+    });
+}
+
+#[test]
+fn license_request_reports_the_runtime_version_when_enabled() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        ReportNodeVersion::set(true);
+
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::new(vec![http_ok(200, br#"{"valid":true}"#)]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+
+        assert_eq!(
+            client.requests[0].url,
+            format!("{}&spec_version=3&impl_version=7", LICENSE_URL)
+        );
+    });
+}
+
+#[test]
+fn license_request_omits_the_runtime_version_by_default() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::new(vec![http_ok(200, br#"{"valid":true}"#)]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+
+        assert_eq!(client.requests[0].url, LICENSE_URL);
+        assert!(!client.requests[0].url.contains("version"));
+    });
+}
+
+#[test]
+fn license_request_reports_the_block_number_when_enabled() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        ReportBlockNumber::set(true);
+        System::set_block_number(12);
+
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::new(vec![http_ok(200, br#"{"valid":true}"#)]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+
+        assert_eq!(client.requests[0].url, format!("{}&block=12", LICENSE_URL));
+    });
+}
+
+#[test]
+fn license_url_builder_can_put_the_key_in_the_path() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        PathStyleLicenseUrls::set(true);
+        ReportBlockNumber::set(true);
+        System::set_block_number(12);
+
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::new(vec![http_ok(200, br#"{"valid":true}"#)]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+
+        assert_eq!(
+            client.requests[0].url,
+            format!(
+                "http://localhost:3000/license/test-license-key/12?genesis={}&instance={}&block=12",
+                GENESIS_HASH_HEX, INSTANCE_ID_HEX
+            )
+        );
+    });
+}
+
+#[test]
+fn request_url_transformer_can_add_a_timestamp() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        TimestampedLicenseUrls::set(true);
+
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::new(vec![http_ok(200, br#"{"valid":true}"#)]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        assert_eq!(
+            client.requests[0].url,
+            LICENSE_URL.replace("test-license-key&", "test-license-key&ts=60000&")
+        );
+    });
+}
+
+#[test]
+fn license_request_omits_the_block_number_by_default() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        System::set_block_number(12);
+
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::new(vec![http_ok(200, br#"{"valid":true}"#)]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+
+        assert_eq!(client.requests[0].url, LICENSE_URL);
+        assert!(!client.requests[0].url.contains("block="));
+    });
+}
+
+fn queue_for_resume(call: RuntimeCall) -> DispatchResult {
+    Aura::sudo_queue_call_for_resume(RuntimeOrigin::root(), Box::new(call))
+}
+
+#[test]
+fn calls_can_only_be_queued_while_halted_and_up_to_the_limit() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        let call = RuntimeCall::Aura(pallet::Call::sudo_set_check_interval { ms: 60_000 });
+        assert_noop!(
+            queue_for_resume(call.clone()),
+            pallet::Error::<Test>::NotHalted
+        );
+
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert_ok!(queue_for_resume(call.clone()));
+        assert_ok!(queue_for_resume(call.clone()));
+        assert_noop!(
+            queue_for_resume(call.clone()),
+            pallet::Error::<Test>::TooManyQueuedCalls
+        );
+        assert_noop!(
+            Aura::sudo_queue_call_for_resume(RuntimeOrigin::signed(1), Box::new(call)),
+            DispatchError::BadOrigin
+        );
+
+        assert_eq!(pallet::QueuedCalls::<Test>::get().len(), 2);
+        assert!(aura_events().contains(&pallet::Event::CallQueuedForResume { index: 1 }));
+    });
+}
+
+#[test]
+fn queued_calls_are_dispatched_after_resume() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert_ok!(queue_for_resume(RuntimeCall::Aura(
+            pallet::Call::sudo_set_check_interval { ms: 60_000 }
+        )));
+        assert_ok!(queue_for_resume(RuntimeCall::Aura(
+            pallet::Call::sudo_set_license_endpoints {
+                endpoints: vec![(0, vec![])]
+            }
+        )));
+
+        // Nothing runs while still halted.
+        Aura::on_initialize(2);
+        assert_eq!(pallet::CheckIntervalOverride::<Test>::get(), None);
+
+        assert_ok!(Aura::sudo_resume_production(RuntimeOrigin::root()));
+        System::reset_events();
+        Aura::on_initialize(3);
+
+        assert_eq!(pallet::CheckIntervalOverride::<Test>::get(), Some(60_000));
+        assert!(pallet::QueuedCalls::<Test>::get().is_empty());
+        assert!(
+            aura_events().contains(&pallet::Event::QueuedCallDispatched {
+                index: 0,
+                result: Ok(())
+            })
+        );
+        assert!(
+            aura_events().contains(&pallet::Event::QueuedCallDispatched {
+                index: 1,
+                result: Err(pallet::Error::<Test>::InvalidEndpoint.into())
+            })
+        );
+    });
+}
+
+#[test]
+fn halted_call_filter_accepts_queueing_for_resume() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        let queue = RuntimeCall::Aura(pallet::Call::sudo_queue_call_for_resume {
+            call: Box::new(RuntimeCall::Aura(pallet::Call::sudo_force_fresh_check {})),
+        });
+        assert!(HaltFilter::contains(&queue));
+
+        // Safe mode only lets the resume through.
+        assert_ok!(Aura::sudo_enter_safe_mode(RuntimeOrigin::root(), None));
+        assert!(!HaltFilter::contains(&queue));
+    });
+}
+
+/// `sudo_queue_call_for_resume` nested `depth` times around `sudo_force_fresh_check`.
+fn nested_queue_call(depth: u32) -> RuntimeCall {
+    (0..depth).fold(
+        RuntimeCall::Aura(pallet::Call::sudo_force_fresh_check {}),
+        |call, _| {
+            RuntimeCall::Aura(pallet::Call::sudo_queue_call_for_resume {
+                call: Box::new(call),
+            })
+        },
+    )
+}
+
+#[test]
+fn halted_call_filter_does_not_recurse_into_nested_calls() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let max_depth = sp_api::MAX_EXTRINSIC_DEPTH;
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+
+        // Only the outermost call is inspected, however deep the nesting.
+        assert!(HaltFilter::contains(&nested_queue_call(1)));
+        assert!(HaltFilter::contains(&nested_queue_call(max_depth)));
+        assert!(HaltFilter::contains(&nested_queue_call(10 * max_depth)));
+
+        // Deeper nesting than an extrinsic may carry never reaches the filter.
+        let too_deep = nested_queue_call(max_depth + 1).encode();
+        assert!(RuntimeCall::decode_with_depth_limit(max_depth, &mut &too_deep[..]).is_err());
+    });
+}
+
+#[test]
+fn auth_token_is_bounded_by_config() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let at_limit = vec![b't'; MAX_AUTH_TOKEN_LEN as usize];
+        assert_ok!(Aura::set_auth_token(
+            RuntimeOrigin::root(),
+            at_limit.clone()
+        ));
+        assert_eq!(
+            pallet::AuthToken::<Test>::get().unwrap().into_inner(),
+            at_limit
+        );
+
+        let beyond_limit = vec![b't'; MAX_AUTH_TOKEN_LEN as usize + 1];
+        assert_noop!(
+            Aura::set_auth_token(RuntimeOrigin::root(), beyond_limit),
+            pallet::Error::<Test>::AuthTokenTooLong
+        );
+        assert_noop!(
+            Aura::set_auth_token(RuntimeOrigin::signed(1), vec![b't']),
+            DispatchError::BadOrigin
+        );
+
+        assert_ok!(Aura::set_auth_token(RuntimeOrigin::root(), vec![]));
+        assert_eq!(pallet::AuthToken::<Test>::get(), None);
+    });
+}
+
+#[test]
+fn auth_token_is_sent_as_a_bearer_token() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        assert_ok!(Aura::set_auth_token(
+            RuntimeOrigin::root(),
+            b"s3cr3t".to_vec()
+        ));
+
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::new(vec![http_ok(200, br#"{"valid":true}"#)]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+
+        assert_eq!(
+            client.requests[0].headers,
+            vec![("Authorization".to_string(), "Bearer s3cr3t".to_string())]
+        );
+    });
+}
+
+fn stored_instance_id() -> Option<[u8; 16]> {
+    sp_runtime::offchain::storage::StorageValueRef::persistent(INSTANCE_ID_STORAGE_KEY)
+        .get::<[u8; 16]>()
+        .unwrap_or(None)
+}
+
+#[test]
+fn license_requests_carry_the_instance_id() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::new(vec![http_ok(200, br#"{"valid":true}"#)]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+
+        assert_eq!(client.requests[0].url, LICENSE_URL);
+        assert!(client.requests[0]
+            .url
+            .contains(&format!("&instance={}", INSTANCE_ID_HEX)));
+    });
+}
+
+#[test]
+fn instance_id_is_generated_once_and_stable_across_checks() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        sp_runtime::offchain::storage::StorageValueRef::persistent(INSTANCE_ID_STORAGE_KEY).clear();
+        handles.offchain.write().seed = [0x5a; 32];
+
+        let mut urls = vec![];
+        for check in 0..3 {
+            handles.set_timestamp(60_000 + check * LICENSE_CACHE_TTL);
+            let mut client = ScriptedHttpClient::new(vec![http_ok(200, br#"{"valid":true}"#)]);
+            assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+            urls.push(client.requests[0].url.clone());
+
+            // A new seed must not change the instance ID once it exists.
+            handles.offchain.write().seed = [check as u8; 32];
+        }
+
+        assert_eq!(stored_instance_id(), Some([0x5a; 16]));
+        let expected = format!("&instance={}", to_hex(&[0x5a; 16]));
+        assert!(urls.iter().all(|url| url.contains(&expected)), "{:?}", urls);
+    });
+}
+
+#[test]
+fn auto_recovery_eta_is_none_unless_halted_with_a_delay() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(5);
+        AutoRecoveryDelay::set(10);
+        assert_eq!(Aura::auto_recovery_eta(5), None);
+
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        AutoRecoveryDelay::set(0);
+        assert_eq!(Aura::auto_recovery_eta(5), None);
+    });
+}
+
+#[test]
+fn auto_recovery_eta_follows_the_delay_for_any_current_block() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(5);
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+
+        for (delay, current_block, eta) in [
+            (10, 5, 15),
+            (10, 14, 15),
+            (10, 15, 15),
+            // Already overdue: fires at the start of the given block.
+            (10, 40, 40),
+            (20, 5, 25),
+            (100, 50, 105),
+        ] {
+            AutoRecoveryDelay::set(delay);
+            assert_eq!(Aura::auto_recovery_eta(current_block), Some(eta));
+        }
+
+        // The override is honoured too, and nothing was changed by asking.
+        assert_ok!(Aura::sudo_set_auto_recovery_delay(
+            RuntimeOrigin::root(),
+            Some(12)
+        ));
+        assert_eq!(Aura::auto_recovery_eta(6), Some(17));
+        assert!(Aura::is_halted());
+        assert_eq!(System::block_number(), 5);
+    });
+}
+
+#[test]
+fn auto_recovery_status_explains_why_production_stays_halted() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(5);
+        AutoRecoveryDelay::set(10);
+        assert_eq!(Aura::auto_recovery_status(), AutoRecoveryStatus::NotHalted);
+
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert_eq!(
+            Aura::auto_recovery_status(),
+            AutoRecoveryStatus::PendingAtBlock(15)
+        );
+
+        AutoRecoveryDelay::set(0);
+        assert_eq!(Aura::auto_recovery_status(), AutoRecoveryStatus::Disabled);
+
+        AutoRecoveryDelay::set(10);
+        assert_ok!(Aura::sudo_set_auto_recovery_paused(
+            RuntimeOrigin::root(),
+            true
+        ));
+        assert_eq!(Aura::auto_recovery_status(), AutoRecoveryStatus::Paused);
+
+        assert_ok!(Aura::sudo_enter_safe_mode(RuntimeOrigin::root(), None));
+        assert_eq!(
+            Aura::auto_recovery_status(),
+            AutoRecoveryStatus::PermanentHalt
+        );
+
+        assert_ok!(Aura::sudo_resume_production(RuntimeOrigin::root()));
+        assert_eq!(Aura::auto_recovery_status(), AutoRecoveryStatus::NotHalted);
+    });
+}
+
+#[test]
+fn simulated_on_initialize_decision_matches_the_next_block() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_storage_noop!(Aura::simulate_on_initialize_decision());
+        assert_eq!(
+            Aura::simulate_on_initialize_decision(),
+            EnforcementDecision::Continue
+        );
+        initialize_block_at_slot(1, 1);
+        assert!(!Aura::is_halted());
+
+        // The offchain worker has been absent for too long by block 11.
+        HaltIfOcwAbsent::set(true);
+        System::set_block_number(10);
+        assert_eq!(
+            Aura::simulate_on_initialize_decision(),
+            EnforcementDecision::Halt(REASON_OCW_ABSENT.to_vec())
+        );
+        initialize_block_at_slot(11, 2);
+        assert!(Aura::is_halted());
+        assert_eq!(
+            Aura::halt_reason_string().unwrap().as_bytes(),
+            REASON_OCW_ABSENT
+        );
+
+        // A halted chain stays halted until auto-recovery is due, at block 21.
+        AutoRecoveryDelay::set(10);
+        assert_eq!(
+            Aura::simulate_on_initialize_decision(),
+            EnforcementDecision::Continue
+        );
+        initialize_block_at_slot(12, 3);
+        assert!(Aura::is_halted());
+
+        // Recovering with the worker still absent halts again in the same block.
+        System::set_block_number(20);
+        assert_eq!(
+            Aura::simulate_on_initialize_decision(),
+            EnforcementDecision::Halt(REASON_OCW_ABSENT.to_vec())
+        );
+        initialize_block_at_slot(21, 4);
+        assert!(Aura::is_halted());
+        assert_eq!(pallet::HaltedAtBlock::<Test>::get(), Some(21));
+        assert_eq!(
+            Aura::halt_reason_string().unwrap().as_bytes(),
+            REASON_OCW_ABSENT
+        );
+
+        // Without the absence rule, the next due recovery resumes for good.
+        HaltIfOcwAbsent::set(false);
+        System::set_block_number(30);
+        assert_eq!(
+            Aura::simulate_on_initialize_decision(),
+            EnforcementDecision::Resume
+        );
+        initialize_block_at_slot(31, 5);
+        assert!(!Aura::is_halted());
+    });
+}
+
+#[test]
+#[should_panic(
+    expected = "Validator with index 2 is disabled and should not be attempting to author blocks."
+)]
+fn simulated_on_initialize_decision_foresees_a_disabled_author() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        initialize_block_at_slot(1, 1);
+        MockDisabledValidators::disable_validator(2);
+        assert_eq!(
+            Aura::simulate_on_initialize_decision(),
+            EnforcementDecision::Panic
+        );
+
+        initialize_block_at_slot(2, 2);
+    });
+}
+
+#[test]
+fn reasons_of_ended_halts_stay_in_the_halt_history() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        AutoRecoveryDelay::set(10);
+        start_block(5);
+        assert_ok!(Aura::sudo_halt_production(
+            RuntimeOrigin::root(),
+            Some(b"maintenance".to_vec())
+        ));
+        assert!(pallet::HaltHistory::<Test>::get().is_empty());
+
+        // Auto-recovery clears the reason but keeps it in the history.
+        start_block(15);
+        assert!(!Aura::is_halted());
+        assert_eq!(Aura::halt_reason_string(), None);
+
+        HaltIfOcwAbsent::set(true);
+        start_block(16);
+        assert!(Aura::is_halted());
+        HaltIfOcwAbsent::set(false);
+        assert_ok!(Aura::sudo_resume_production(RuntimeOrigin::root()));
+        // Resuming a running chain records nothing.
+        assert_ok!(Aura::sudo_resume_production(RuntimeOrigin::root()));
+
+        let reason = |r: &[u8]| Some(BoundedVec::try_from(r.to_vec()).unwrap());
+        assert_eq!(
+            pallet::HaltHistory::<Test>::get().into_inner(),
+            vec![
+                EndedHalt {
+                    halted_at: Some(5),
+                    resumed_at: 15,
+                    kind: Some(HaltReasonKind::Manual),
+                    reason: reason(b"maintenance"),
+                },
+                EndedHalt {
+                    halted_at: Some(16),
+                    resumed_at: 16,
+                    kind: Some(HaltReasonKind::OffchainWorkerAbsent),
+                    reason: reason(REASON_OCW_ABSENT),
+                },
+            ]
+        );
+    });
+}
+
+#[test]
+fn halt_history_keeps_the_most_recent_ended_halts() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        for block in 1..=HALT_HISTORY_LEN as u64 + 2 {
+            System::set_block_number(block);
+            assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+            assert_ok!(Aura::sudo_resume_production(RuntimeOrigin::root()));
+        }
+
+        let history = pallet::HaltHistory::<Test>::get();
+        assert_eq!(history.len() as u32, HALT_HISTORY_LEN);
+        assert_eq!(history.first().unwrap().halted_at, Some(3));
+        assert_eq!(
+            history.last().unwrap().resumed_at,
+            HALT_HISTORY_LEN as u64 + 2
+        );
+    });
+}
+
+#[test]
+fn auto_recovery_status_of_a_simulated_halt_is_its_end() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(5);
+        assert_ok!(Aura::sudo_set_auto_recovery_paused(
+            RuntimeOrigin::root(),
+            true
+        ));
+        assert_ok!(Aura::sudo_simulate_halt(
+            RuntimeOrigin::root(),
+            b"drill".to_vec(),
+            3
+        ));
+
+        assert_eq!(
+            Aura::auto_recovery_status(),
+            AutoRecoveryStatus::PendingAtBlock(8)
+        );
+    });
+}
+
+#[test]
+fn current_slot_and_author_matches_find_author() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        for (block, slot) in [(1u64, 1u64), (2, 2), (3, 7), (4, 12), (5, 13)] {
+            let pre_digest = Digest {
+                logs: vec![DigestItem::PreRuntime(
+                    AURA_ENGINE_ID,
+                    Slot::from(slot).encode(),
+                )],
+            };
+            System::initialize(&block, &System::parent_hash(), &pre_digest);
+            Aura::on_initialize(block);
+
+            let digest = System::digest();
+            let expected_author =
+                Aura::find_author(digest.logs.iter().filter_map(|d| d.as_pre_runtime()));
+            assert_eq!(
+                Aura::current_slot_and_author(),
+                (Slot::from(slot), expected_author)
+            );
+        }
+    });
+}
+
+#[test]
+fn author_distribution_counts_recent_blocks_per_authority() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        // Authority 2 is offline: its slots 2 and 6 produce no blocks.
+        for (block, slot) in [(1u64, 1u64), (2, 3), (3, 4), (4, 5), (5, 7), (6, 8), (7, 9)] {
+            let pre_digest = Digest {
+                logs: vec![DigestItem::PreRuntime(
+                    AURA_ENGINE_ID,
+                    Slot::from(slot).encode(),
+                )],
+            };
+            System::initialize(&block, &System::parent_hash(), &pre_digest);
+            Aura::on_initialize(block);
+        }
+
+        assert_eq!(Aura::author_distribution(100), vec![(0, 2), (1, 3), (3, 2)]);
+        // Only the last three blocks, of slots 7, 8 and 9.
+        assert_eq!(Aura::author_distribution(3), vec![(0, 1), (1, 1), (3, 1)]);
+        assert_eq!(Aura::author_distribution(0), vec![]);
+    });
+}
+
+#[test]
+fn author_history_keeps_only_the_most_recent_blocks() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        for slot in 1..=u64::from(AUTHOR_HISTORY_LEN) + 4 {
+            let pre_digest = Digest {
+                logs: vec![DigestItem::PreRuntime(
+                    AURA_ENGINE_ID,
+                    Slot::from(slot).encode(),
+                )],
+            };
+            System::initialize(&slot, &System::parent_hash(), &pre_digest);
+            Aura::on_initialize(slot);
+        }
+
+        assert_eq!(
+            pallet::RecentAuthors::<Test>::get().len() as u32,
+            AUTHOR_HISTORY_LEN
+        );
+        let per_authority = AUTHOR_HISTORY_LEN / 4;
+        assert_eq!(
+            Aura::author_distribution(u32::MAX),
+            (0..4)
+                .map(|index| (index, per_authority))
+                .collect::<Vec<_>>()
+        );
+    });
+}
+
+/// Initialize block `block` at `slot` and return the author [`FindAuthor`] finds for it.
+fn initialize_block_at_slot(block: u64, slot: u64) -> Option<u32> {
+    let pre_digest = Digest {
+        logs: vec![DigestItem::PreRuntime(
+            AURA_ENGINE_ID,
+            Slot::from(slot).encode(),
+        )],
+    };
+    System::initialize(&block, &System::parent_hash(), &pre_digest);
+    Aura::on_initialize(block);
+    Aura::find_author(pre_digest.logs.iter().filter_map(|d| d.as_pre_runtime()))
+}
+
+#[test]
+fn same_slot_blocks_are_attributed_to_distinct_authors() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        crate::mock::AllowMultipleBlocksPerSlot::set(true);
+        SameSlotAuthorStep::set(Some(1));
+
+        // Slot 5 belongs to authority 1; its later blocks go to the next authorities, until the
+        // refined index leaves the authority set and falls back to the slot's own.
+        let authors: Vec<_> = (1..=4)
+            .map(|block| initialize_block_at_slot(block, 5).unwrap())
+            .collect();
+        assert_eq!(authors, vec![1, 2, 3, 1]);
+        assert_eq!(
+            pallet::SlotBlockPosition::<Test>::get(),
+            Some((Slot::from(5), 3))
+        );
+
+        // A new slot starts counting again.
+        assert_eq!(initialize_block_at_slot(5, 6), Some(2));
+        assert_eq!(Aura::author_distribution(100), vec![(1, 2), (2, 2), (3, 1)]);
+    });
+}
+
+#[test]
+fn same_slot_author_is_only_consulted_with_multiple_blocks_per_slot() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        SameSlotAuthorStep::set(Some(1));
+
+        assert_eq!(initialize_block_at_slot(1, 5), Some(1));
+        assert_eq!(initialize_block_at_slot(2, 6), Some(2));
+        assert_eq!(pallet::SlotBlockPosition::<Test>::get(), None);
+    });
+}
+
+#[test]
+fn slot_history_records_the_slot_of_each_block() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        crate::mock::AllowMultipleBlocksPerSlot::set(true);
+        // Slot 4 stalls for two blocks, then the chain jumps ahead to slot 10.
+        for (block, slot) in [(1, 3), (2, 4), (3, 4), (4, 10), (5, 11)] {
+            initialize_block_at_slot(block, slot);
+        }
+
+        let slots = |pairs: &[(u64, u64)]| {
+            pairs
+                .iter()
+                .map(|&(block, slot)| (block, Slot::from(slot)))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            Aura::slot_history(100),
+            slots(&[(1, 3), (2, 4), (3, 4), (4, 10), (5, 11)])
+        );
+        assert_eq!(Aura::slot_history(2), slots(&[(4, 10), (5, 11)]));
+        assert_eq!(Aura::slot_history(0), vec![]);
+    });
+}
+
+#[test]
+fn slot_history_keeps_only_the_most_recent_blocks() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let blocks = u64::from(SLOT_HISTORY_LEN) + 3;
+        for block in 1..=blocks {
+            initialize_block_at_slot(block, block * 2);
+        }
+
+        let history = Aura::slot_history(u32::MAX);
+        assert_eq!(history.len() as u32, SLOT_HISTORY_LEN);
+        assert_eq!(history.first(), Some(&(4, Slot::from(8))));
+        assert_eq!(history.last(), Some(&(blocks, Slot::from(blocks * 2))));
+    });
+}
+
+#[test]
+fn current_slot_and_author_has_no_author_without_authorities() {
+    build_ext(vec![]).execute_with(|| {
+        pallet::CurrentSlot::<Test>::put(Slot::from(7));
+        assert_eq!(Aura::current_slot_and_author(), (Slot::from(7), None));
+    });
+}
+
+#[test]
+fn next_slot_author_follows_the_round_robin() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let authorities = authority_keys(&[0, 1, 2, 3]);
+        for (slot, next) in [(0u64, 1usize), (1, 2), (2, 3), (3, 0), (10, 3)] {
+            pallet::CurrentSlot::<Test>::put(Slot::from(slot));
+            assert_eq!(Aura::next_slot_author(), Some(authorities[next].clone()));
+        }
+
+        // It is who authors the next slot once it comes.
+        initialize_block_at_slot(1, 11);
+        let expected = Aura::next_slot_author();
+        let author = initialize_block_at_slot(2, 12).unwrap();
+        assert_eq!(Some(authorities[author as usize].clone()), expected);
+    });
+}
+
+#[test]
+fn next_slot_author_is_none_without_authorities() {
+    build_ext(vec![]).execute_with(|| {
+        pallet::CurrentSlot::<Test>::put(Slot::from(7));
+        assert_eq!(Aura::next_slot_author(), None);
+    });
+}
+
+#[test]
+fn oversized_internal_halt_reason_falls_back_and_is_logged() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        capture_logs();
+        assert_eq!(
+            Aura::internal_halt_reason(&[b'x'; 257]),
+            REASON_FALLBACK.to_vec()
+        );
+        assert!(captured_logs().iter().any(|(level, line)| {
+            *level == log::Level::Error
+                && line.contains("257 bytes")
+                && line.contains("does not fit")
+        }));
+
+        // A reason that fits is kept as is, without a word.
+        capture_logs();
+        assert_eq!(Aura::internal_halt_reason(&[b'x'; 256]), vec![b'x'; 256]);
+        assert_eq!(
+            Aura::internal_halt_reason(REASON_LICENSE_QUORUM),
+            REASON_LICENSE_QUORUM
+        );
+        assert!(captured_logs()
+            .iter()
+            .all(|(_, line)| !line.contains("does not fit")));
+    });
+}
+
+#[test]
+fn oversized_pending_halt_reason_still_halts_with_the_fallback() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        handles.set_timestamp(60_000);
+        use sp_runtime::offchain::storage::StorageValueRef;
+        StorageValueRef::persistent(b"licensed_aura::halt_reason").set(&vec![b'x'; 300]);
+        StorageValueRef::persistent(b"licensed_aura::halt_requested").set(&true);
+
+        capture_logs();
+        Aura::offchain_worker(1);
+
+        let reason = submitted_halt_reason(handles).expect("a halt is submitted");
+        assert_eq!(reason, REASON_FALLBACK.to_vec());
+        assert!(captured_logs()
+            .iter()
+            .any(|(_, line)| line.contains("300 bytes")));
+
+        assert_ok!(Aura::offchain_worker_halt_production(
+            RuntimeOrigin::none(),
+            Some(reason)
+        ));
+        assert!(Aura::is_halted());
+        assert_eq!(
+            pallet::HaltReason::<Test>::get().unwrap().to_vec(),
+            REASON_FALLBACK
+        );
+    });
+}
+
+#[test]
+fn offchain_check_without_a_usable_key_fails_with_key_errors() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        handles.set_timestamp(60_000);
+        pallet::LicenseKey::<Test>::kill();
+        let mut client = ScriptedHttpClient::new(vec![]);
+        assert_eq!(
+            Aura::check_license_and_halt_if_needed(&mut client),
+            Err(OffchainError::KeyNotSet)
+        );
+
+        pallet::LicenseKey::<Test>::put(BoundedVec::truncate_from(vec![0xff, 0xfe]));
+        assert_eq!(
+            Aura::check_license_and_halt_if_needed(&mut client),
+            Err(OffchainError::BadKeyUtf8)
+        );
+
+        assert!(client.requests.is_empty());
+        assert_eq!(
+            handles.submitted_calls(),
+            vec![RuntimeCall::Aura(
+                pallet::Call::offchain_worker_report_unusable_key {}
+            )]
+        );
+    });
+}
+
+#[test]
+fn unusable_license_key_is_reported_on_chain_once() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        System::set_block_number(1);
+        pallet::LicenseKey::<Test>::put(BoundedVec::truncate_from(vec![0xff, 0xfe]));
+
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::default();
+        assert_eq!(
+            Aura::check_license_and_halt_if_needed(&mut client),
+            Err(OffchainError::BadKeyUtf8)
+        );
+        let Some(RuntimeCall::Aura(call)) = handles.submitted_calls().pop() else {
+            panic!("expected the unusable key to be reported");
+        };
+        assert!(Aura::validate_unsigned(TransactionSource::Local, &call).is_ok());
+
+        assert_ok!(Aura::offchain_worker_report_unusable_key(
+            RuntimeOrigin::none()
+        ));
+        assert_ok!(Aura::offchain_worker_report_unusable_key(
+            RuntimeOrigin::none()
+        ));
+        assert_eq!(aura_events(), vec![pallet::Event::LicenseKeyUnusable]);
+
+        // Once reported, later runs only log the problem.
+        handles.set_timestamp(120_000);
+        assert_eq!(
+            Aura::check_license_and_halt_if_needed(&mut client),
+            Err(OffchainError::BadKeyUtf8)
+        );
+        assert_eq!(handles.submitted_calls().len(), 1);
+
+        // A new key that is unusable too is reported afresh.
+        assert_ok!(Aura::set_license_key(
+            RuntimeOrigin::root(),
+            vec![0xff, 0xfd]
+        ));
+        assert!(!pallet::LicenseKeyUnusableReported::<Test>::get());
+    });
+}
+
+#[test]
+fn offchain_check_reports_each_transport_failure() {
+    for (failure, error) in [
+        (HttpFailure::SendFailed, OffchainError::SendFailed),
+        (HttpFailure::DeadlineReached, OffchainError::DeadlineReached),
+        (HttpFailure::RequestFailed, OffchainError::RequestFailed),
+    ] {
+        build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+            handles.set_timestamp(60_000);
+            let mut client = ScriptedHttpClient::new(vec![Err(failure); 3]);
+            assert_eq!(
+                Aura::check_license_and_halt_if_needed(&mut client),
+                Err(error)
+            );
+            assert!(handles.submitted_calls().is_empty());
+        });
+    }
+}
+
+#[test]
+fn offchain_check_fails_with_lock_busy_while_another_check_runs() {
+    use sp_runtime::offchain::{
+        storage_lock::{StorageLock, Time},
+        Duration,
+    };
+
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        handles.set_timestamp(60_000);
+        let mut lock = StorageLock::<Time>::with_deadline(
+            CHECK_LOCK_STORAGE_KEY,
+            Duration::from_millis(CHECK_LOCK_DEADLINE_MS),
+        );
+        let guard = lock.try_lock().expect("nobody holds the lock yet");
+
+        let mut client = ScriptedHttpClient::new(vec![]);
+        assert_eq!(
+            Aura::check_license_and_halt_if_needed(&mut client),
+            Err(OffchainError::LockBusy)
+        );
+        assert!(client.requests.is_empty());
+
+        // Once the other check is done, the lock is free again.
+        drop(guard);
+        handles.expect_license_request(br#"{"valid":true}"#);
+        Aura::offchain_worker(1);
+        assert_eq!(handles.submitted_calls().len(), 1);
+    });
+}
+
+#[test]
+fn non_utf8_license_response_is_reported_invalid() {
+    assert_eq!(
+        Aura::report_from_body(&[0xff, 0xfe], None, "nonce"),
+        Err(OffchainError::BadResponseUtf8)
+    );
+
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::new(vec![Ok(HttpResponse {
+            code: 200,
+            body: vec![0xff, 0xfe],
+            content_encoding: None,
+        })]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+
+        assert_eq!(
+            handles.submitted_calls(),
+            vec![RuntimeCall::Aura(
+                pallet::Call::offchain_worker_report_check {
+                    report: LicenseCheckReport::invalid(),
+                }
+            )]
+        );
+    });
+}
+
+const BODY_WITHOUT_VALID: &[u8] = br#"{"status":"ok"}"#;
+
+fn reported_valid(body: &[u8]) -> Result<bool, OffchainError> {
+    Aura::report_from_body(body, None, "nonce").map(|report| report.valid)
+}
+
+#[test]
+fn missing_valid_field_is_invalid_by_default() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_eq!(reported_valid(BODY_WITHOUT_VALID), Ok(false));
+    });
+}
+
+#[test]
+fn missing_valid_field_can_count_as_valid() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        MissingValidField::set(MissingValidFieldPolicy::Valid);
+        assert_eq!(reported_valid(BODY_WITHOUT_VALID), Ok(true));
+        // An explicit verdict still wins.
+        assert_eq!(reported_valid(br#"{"valid":false}"#), Ok(false));
+    });
+}
+
+#[test]
+fn missing_valid_field_can_count_as_unparseable() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        MissingValidField::set(MissingValidFieldPolicy::Unparseable);
+        assert_eq!(
+            reported_valid(BODY_WITHOUT_VALID),
+            Err(OffchainError::MissingValidField)
+        );
+
+        let response = || {
+            Ok(HttpResponse {
+                code: 200,
+                body: BODY_WITHOUT_VALID.to_vec(),
+                content_encoding: None,
+            })
+        };
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::new(vec![response()]);
+        assert_eq!(
+            Aura::check_license_and_halt_if_needed(&mut client),
+            Err(OffchainError::MissingValidField)
+        );
+        assert!(handles.submitted_calls().is_empty());
+        assert!(!halt_requested());
+
+        // Failing closed, it counts as invalid like an unreachable server.
+        FailClosed::set(true);
+        handles.set_timestamp(60_000 + LICENSE_CHECK_INTERVAL);
+        client.script.push_back(response());
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        assert_eq!(reported_validity(handles), vec![false]);
+        assert!(halt_requested());
+    });
+}
+
+#[test]
+fn empty_license_response_is_invalid_by_default() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        assert_eq!(reported_valid(b""), Ok(false));
+        assert_eq!(reported_valid(b" \r\n"), Ok(false));
+
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::new(vec![http_ok(200, b"")]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        assert_eq!(reported_validity(handles), vec![false]);
+        assert!(halt_requested());
+    });
+}
+
+#[test]
+fn empty_license_response_can_mean_valid() {
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        EmptyBodyMeansValid::set(true);
+        assert_eq!(reported_valid(b""), Ok(true));
+        // Only an empty body; anything else is parsed as usual.
+        assert_eq!(reported_valid(br#"{"valid":false}"#), Ok(false));
+
+        handles.set_timestamp(60_000);
+        let mut client = ScriptedHttpClient::new(vec![http_ok(200, b"")]);
+        assert_ok!(Aura::check_license_and_halt_if_needed(&mut client));
+        assert_eq!(reported_validity(handles), vec![true]);
+        assert!(!halt_requested());
+
+        // An empty body cannot attest a pinned certificate.
+        pin_fingerprint();
+        assert_eq!(reported_valid(b""), Ok(false));
+    });
+}
+
+#[test]
+fn alternative_valid_field_is_read_when_valid_is_missing() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        AlternativeValidField::set(Some("licensed"));
+        assert_eq!(reported_valid(br#"{"licensed":true}"#), Ok(true));
+        assert_eq!(reported_valid(br#"{"licensed":false}"#), Ok(false));
+        assert_eq!(
+            reported_valid(br#"{"valid":false,"licensed":true}"#),
+            Ok(false)
+        );
+
+        // Without either field the policy applies as usual.
+        MissingValidField::set(MissingValidFieldPolicy::Valid);
+        assert_eq!(reported_valid(BODY_WITHOUT_VALID), Ok(true));
+    });
+}
+
+#[test]
+fn validity_field_path_reads_a_nested_verdict() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        ValidityFieldPath::set(&["data", "license", "valid"]);
+        let body = br#"{"data":{"id":"x","license":{"valid":true}},"valid":false}"#;
+        assert_eq!(reported_valid(body), Ok(true));
+        assert_eq!(
+            reported_valid(br#"{"data":{"license":{"valid":false}}}"#),
+            Ok(false)
+        );
+    });
+}
+
+#[test]
+fn validity_field_path_without_its_objects_is_invalid() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        ValidityFieldPath::set(&["data", "license", "valid"]);
+        assert_eq!(reported_valid(br#"{"data":{"valid":true}}"#), Ok(false));
+        assert_eq!(
+            reported_valid(br#"{"data":{"license":true},"valid":true}"#),
+            Ok(false)
+        );
+    });
+}
+
+#[test]
+fn license_response_reads_renamed_fields() {
+    let body = br#"{"licensed":true,"valid":false,"valid_until":2000,"expires_at":1,"mods":["a"]}"#;
+    let renamed = [
+        (ResponseField::Valid, "licensed"),
+        (ResponseField::ExpiresAt, "valid_until"),
+        (ResponseField::Features, "mods"),
+    ];
+    let parsed = LicenseResponse::parse_with_field_names(body, &renamed, &[], None).unwrap();
+    assert_eq!(parsed.valid, Some(true));
+    assert_eq!(parsed.expires_at, Some(2000));
+    assert_eq!(parsed.features, Some(vec![b"a".to_vec()]));
+}
+
+#[test]
+fn response_field_names_apply_to_license_checks() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(1);
+        let body = br#"{"ok":true,"exp":2000}"#;
+        assert_eq!(reported_valid(body), Ok(false));
+
+        let rename = |field, name: Option<&str>| {
+            let name = name.map(|name| name.as_bytes().to_vec());
+            Aura::set_response_field_name(RuntimeOrigin::root(), field, name)
+        };
+        assert_ok!(rename(ResponseField::Valid, Some("ok")));
+        assert_ok!(rename(ResponseField::ExpiresAt, Some("exp")));
+        let report = Aura::report_from_body(body, None, "nonce").unwrap();
+        assert!(report.valid);
+        assert_eq!(report.expires_at, Some(2000));
+        assert!(
+            aura_events().contains(&pallet::Event::ResponseFieldRenamed {
+                field: ResponseField::Valid,
+                name: Some(b"ok".to_vec()),
+            })
+        );
+
+        // Back to the default name.
+        assert_ok!(rename(ResponseField::Valid, None));
+        assert_eq!(reported_valid(body), Ok(false));
+
+        let bad_name = pallet::Error::<Test>::BadResponseFieldName;
+        assert_noop!(rename(ResponseField::Key, Some("")), bad_name);
+        assert_noop!(rename(ResponseField::Key, Some("a\"b")), bad_name);
+        let too_long = "k".repeat(MAX_RESPONSE_FIELD_NAME_LEN as usize + 1);
+        assert_noop!(
+            rename(ResponseField::Key, Some(&too_long)),
+            pallet::Error::<Test>::ResponseFieldNameTooLong
+        );
+    });
+}
+
+#[test]
+fn license_response_parses_every_known_field() {
+    let body = br#"{
+        "valid": true,
+        "issued_at": 1000,
+        "expires_at": 2000,
+        "min_check_interval": 30000,
+        "not_before": 500,
+        "features": ["relay", "bridge"],
+        "key": "renewed-key",
+        "cert_fingerprint": "ab12",
+        "cert_fingerprint_sig": "cd34",
+        "unknown": {"nested": 1}
+    }"#;
+
+    assert_eq!(
+        LicenseResponse::parse(body),
+        Ok(LicenseResponse {
+            valid: Some(true),
+            issued_at: Some(1000),
+            expires_at: Some(2000),
+            min_check_interval: Some(30_000),
+            not_before: Some(500),
+            features: Some(vec![b"relay".to_vec(), b"bridge".to_vec()]),
+            key: Some(b"renewed-key".to_vec()),
+            cert_fingerprint: Some(b"ab12".to_vec()),
+            cert_fingerprint_sig: Some(b"cd34".to_vec()),
+        })
+    );
+}
+
+#[test]
+fn license_response_leaves_missing_and_mistyped_fields_empty() {
+    assert_eq!(
+        LicenseResponse::parse(b"{}"),
+        Ok(LicenseResponse::default())
+    );
+    assert_eq!(LicenseResponse::parse(b""), Ok(LicenseResponse::default()));
+
+    let parsed = LicenseResponse::parse(
+        br#"{"valid":"yes","expires_at":"soon","features":"relay","key":7}"#,
+    )
+    .unwrap();
+    // Only `true` is a positive verdict.
+    assert_eq!(parsed.valid, Some(false));
+    assert_eq!(parsed.expires_at, None);
+    assert_eq!(parsed.features, None);
+    assert_eq!(parsed.key, None);
+
+    // An empty array lists no features, which is not the same as not listing any.
+    assert_eq!(
+        LicenseResponse::parse(br#"{"features":[]}"#)
+            .unwrap()
+            .features,
+        Some(vec![])
+    );
+    // A feature that is not a string spoils the whole list.
+    assert_eq!(
+        LicenseResponse::parse(br#"{"features":["a",1]}"#)
+            .unwrap()
+            .features,
+        None
+    );
+}
+
+#[test]
+fn license_response_reads_the_alternative_valid_field_only_as_a_fallback() {
+    let parse = |body: &[u8]| {
+        LicenseResponse::parse_with_valid_field(body, Some("licensed"))
+            .unwrap()
+            .valid
+    };
+    assert_eq!(parse(br#"{"licensed":true}"#), Some(true));
+    assert_eq!(parse(br#"{"valid":false,"licensed":true}"#), Some(false));
+    assert_eq!(parse(br#"{"status":"ok"}"#), None);
+
+    assert_eq!(
+        LicenseResponse::parse(br#"{"licensed":true}"#)
+            .unwrap()
+            .valid,
+        None
+    );
+}
+
+#[test]
+fn license_response_reads_top_level_keys_only() {
+    // A nested object's key of the same name does not shadow the top-level one.
+    assert_eq!(
+        LicenseResponse::parse(br#"{"data":{"expires_at":1},"expires_at":2}"#)
+            .unwrap()
+            .expires_at,
+        Some(2)
+    );
+    // Nor does a string that is not a key, such as an array item or a value.
+    assert_eq!(
+        LicenseResponse::parse(br#"{"features":["valid"],"valid":true}"#)
+            .unwrap()
+            .valid,
+        Some(true)
+    );
+    assert_eq!(
+        LicenseResponse::parse(br#"{"key":"valid","valid":true}"#)
+            .unwrap()
+            .valid,
+        Some(true)
+    );
+    assert_eq!(
+        LicenseResponse::parse(br#"{"data":{"valid":true}}"#)
+            .unwrap()
+            .valid,
+        None
+    );
+}
+
+#[test]
+fn license_response_rejects_non_utf8_bodies() {
+    assert_eq!(
+        LicenseResponse::parse(&[0xff, 0xfe]),
+        Err(ParseError::BadUtf8)
+    );
+}
+
+#[test]
+fn license_response_round_trips_through_scale() {
+    let response = LicenseResponse::parse(br#"{"valid":true,"features":["relay"]}"#).unwrap();
+    assert_eq!(
+        LicenseResponse::decode(&mut &response.encode()[..]).unwrap(),
+        response
+    );
+}
+
+#[test]
+fn set_halt_reason_annotates_an_ongoing_halt() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(5);
+        assert_ok!(Aura::sudo_halt_production(
+            RuntimeOrigin::root(),
+            Some(b"manual".to_vec())
+        ));
+        System::set_block_number(9);
+
+        let call = RuntimeCall::Aura(pallet::Call::set_halt_reason {
+            reason: b"tracking incident #42".to_vec(),
+        });
+        assert!(HaltFilter::contains(&call));
+
+        assert_ok!(Aura::set_halt_reason(
+            RuntimeOrigin::root(),
+            b"tracking incident #42".to_vec()
+        ));
+        assert_eq!(
+            Aura::halt_reason_string(),
+            Some("tracking incident #42".to_string())
+        );
+        assert!(aura_events().contains(&pallet::Event::HaltReasonSet));
+
+        // Only the reason changed.
+        assert!(Aura::is_halted());
+        assert_eq!(pallet::HaltedAtBlock::<Test>::get(), Some(5));
+    });
+}
+
+#[test]
+fn set_halt_reason_is_rejected_while_running() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_noop!(
+            Aura::set_halt_reason(RuntimeOrigin::root(), b"tracking incident #42".to_vec()),
+            pallet::Error::<Test>::NotHalted
+        );
+        assert_eq!(pallet::HaltReason::<Test>::get(), None);
+        assert!(!Aura::is_halted());
+    });
+}
+
+#[test]
+fn set_halt_reason_requires_root_and_a_bounded_reason() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_ok!(Aura::sudo_halt_production(
+            RuntimeOrigin::root(),
+            Some(b"manual".to_vec())
+        ));
+
+        assert_noop!(
+            Aura::set_halt_reason(RuntimeOrigin::signed(1), b"note".to_vec()),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Aura::set_halt_reason(RuntimeOrigin::root(), vec![b'x'; 257]),
+            pallet::Error::<Test>::ReasonTooLong
+        );
+        assert_eq!(Aura::halt_reason_string(), Some("manual".to_string()));
+    });
+}
+
+#[test]
+fn license_endpoint_is_the_highest_priority_endpoint() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_eq!(
+            Aura::license_endpoint(),
+            Some(DEFAULT_LICENSE_ENDPOINT.as_bytes().to_vec())
+        );
+
+        assert_ok!(Aura::sudo_set_license_endpoints(
+            RuntimeOrigin::root(),
+            vec![
+                (1, b"http://backup".to_vec()),
+                (5, b"http://primary".to_vec())
+            ]
+        ));
+        assert_eq!(Aura::license_endpoint(), Some(b"http://primary".to_vec()));
+    });
+}
+
+#[test]
+fn license_key_fingerprint_is_stable_per_key() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_ok!(Aura::set_license_key(
+            RuntimeOrigin::root(),
+            b"key-one".to_vec()
+        ));
+        let fingerprint = Aura::license_key_fingerprint().expect("a key is set");
+        assert_eq!(Aura::license_key_fingerprint(), Some(fingerprint));
+        assert_eq!(fingerprint[..], LicenseProof::key_hash(b"key-one")[..4]);
+
+        assert_ok!(Aura::set_license_key(
+            RuntimeOrigin::root(),
+            b"key-two".to_vec()
+        ));
+        assert_ne!(Aura::license_key_fingerprint(), Some(fingerprint));
+
+        // Back to the first key, back to its fingerprint.
+        assert_ok!(Aura::set_license_key(
+            RuntimeOrigin::root(),
+            b"key-one".to_vec()
+        ));
+        assert_eq!(Aura::license_key_fingerprint(), Some(fingerprint));
+
+        pallet::LicenseKey::<Test>::kill();
+        assert_eq!(Aura::license_key_fingerprint(), None);
+    });
+}
+
+#[test]
+fn license_key_len_reports_the_stored_key_length() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        assert_ok!(Aura::set_license_key(
+            RuntimeOrigin::root(),
+            b"key-one".to_vec()
+        ));
+        assert_eq!(Aura::license_key_len(), Some(7));
+
+        assert_ok!(Aura::set_license_key(
+            RuntimeOrigin::root(),
+            vec![b'k'; 128]
+        ));
+        assert_eq!(Aura::license_key_len(), Some(128));
+
+        pallet::LicenseKey::<Test>::kill();
+        assert_eq!(Aura::license_key_len(), None);
+    });
+}
+
+fn start_block(n: u64) {
+    System::set_block_number(n);
+    Aura::on_initialize(n);
+}
+
+fn report_valid_check() {
+    let report = LicenseCheckReport {
+        valid: true,
+        ..LicenseCheckReport::invalid()
+    };
+    assert_ok!(Aura::offchain_worker_report_check(
+        RuntimeOrigin::none(),
+        report
+    ));
+}
+
+#[test]
+fn absent_offchain_worker_halts_when_configured() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        HaltIfOcwAbsent::set(true);
+
+        // The genesis key is missed from block zero, for `OcwAbsenceThreshold` (10) blocks.
+        start_block(10);
+        assert!(!Aura::is_halted());
+
+        start_block(11);
+        assert!(Aura::is_halted());
+        assert_eq!(
+            Aura::halt_reason_string().unwrap().as_bytes(),
+            REASON_OCW_ABSENT
+        );
+        assert!(aura_events().contains(&pallet::Event::OffchainWorkerAbsent { last_run: None }));
+        assert!(aura_events().contains(&pallet::Event::ProductionHalted));
+    });
+}
+
+#[test]
+fn halting_in_on_initialize_indexes_a_halt_record() {
+    let mut ext = build_ext(vec![0, 1, 2, 3]);
+    ext.execute_with(|| {
+        HaltIfOcwAbsent::set(true);
+        start_block(11);
+        assert!(Aura::is_halted());
+
+        // Blocks of an ongoing halt do not index another record.
+        start_block(12);
+    });
+    ext.persist_offchain_overlay();
+
+    let db = ext.offchain_db();
+    let record = db
+        .get(&Aura::halt_record_key(11))
+        .expect("the halt should be indexed");
+    assert_eq!(
+        HaltRecord::<u64>::decode(&mut &record[..]).unwrap(),
+        HaltRecord {
+            block: 11,
+            reason: REASON_OCW_ABSENT.to_vec(),
+            source: HaltReasonKind::OffchainWorkerAbsent,
+        }
+    );
+    assert_eq!(db.get(&Aura::halt_record_key(12)), None);
+}
+
+#[test]
+fn landing_offchain_worker_transactions_keep_production_going() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        HaltIfOcwAbsent::set(true);
+
+        start_block(8);
+        report_valid_check();
+        assert_eq!(pallet::LastOcwRun::<Test>::get(), Some(8));
+
+        start_block(18);
+        assert!(!Aura::is_halted());
+
+        start_block(19);
+        assert!(Aura::is_halted());
+        assert!(aura_events().contains(&pallet::Event::OffchainWorkerAbsent { last_run: Some(8) }));
+
+        // A worker that comes back resumes production as usual.
+        assert_ok!(Aura::offchain_worker_resume_production(
+            RuntimeOrigin::none()
+        ));
+        assert_eq!(pallet::LastOcwRun::<Test>::get(), Some(19));
+        start_block(20);
+        assert!(!Aura::is_halted());
+    });
+}
+
+#[test]
+fn absent_offchain_worker_is_tolerated_unless_configured_and_keyed() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        // Not configured.
+        start_block(100);
+        assert!(!Aura::is_halted());
+
+        // Configured, but there is no license to enforce.
+        HaltIfOcwAbsent::set(true);
+        pallet::LicenseKey::<Test>::kill();
+        start_block(101);
+        assert!(!Aura::is_halted());
+
+        // The first key starts the clock rather than halting at once.
+        assert_ok!(Aura::set_license_key(
+            RuntimeOrigin::root(),
+            b"new-key".to_vec()
+        ));
+        assert_eq!(pallet::LastOcwRun::<Test>::get(), Some(101));
+        start_block(111);
+        assert!(!Aura::is_halted());
+        start_block(112);
+        assert!(Aura::is_halted());
+    });
+}
+
+#[test]
+fn manual_resume_drops_the_server_minimum_interval() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        let report = LicenseCheckReport {
+            valid: true,
+            min_check_interval: Some(120_000),
+            ..LicenseCheckReport::invalid()
+        };
+        assert_ok!(Aura::offchain_worker_report_check(
+            RuntimeOrigin::none(),
+            report
+        ));
+        assert_eq!(Aura::effective_check_interval(), 120_000);
+
+        System::set_block_number(7);
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert_ok!(Aura::sudo_resume_production(RuntimeOrigin::root()));
+
+        assert_eq!(Aura::effective_check_interval(), LICENSE_CHECK_INTERVAL);
+        assert_eq!(pallet::ResetBackoff::<Test>::get(), Some(7));
+    });
+}
+
+#[test]
+fn next_check_after_manual_resume_runs_at_the_base_pace() {
+    use sp_runtime::offchain::storage::StorageValueRef;
+
+    build_offchain_ext_and_execute_test(vec![0, 1, 2, 3], |handles| {
+        // Checks were backed off by the server, and a submission failed.
+        let report = LicenseCheckReport {
+            valid: true,
+            min_check_interval: Some(120_000),
+            ..LicenseCheckReport::invalid()
+        };
+        assert_ok!(Aura::offchain_worker_report_check(
+            RuntimeOrigin::none(),
+            report
+        ));
+        StorageValueRef::persistent(b"licensed_aura::last_check").set(&60_000u64);
+        System::set_block_number(10);
+        assert!(!Aura::submit_with_backoff("test", || Err(())));
+
+        assert_ok!(Aura::sudo_halt_production(RuntimeOrigin::root(), None));
+        assert_ok!(Aura::sudo_resume_production(RuntimeOrigin::root()));
+
+        // One base interval later, well within both backoffs, the worker checks and reports.
+        System::set_block_number(11);
+        handles.set_timestamp(60_000 + LICENSE_CHECK_INTERVAL);
+        handles.expect_license_request(br#"{"valid":true}"#);
+        Aura::offchain_worker(11);
+        assert!(matches!(
+            handles.submitted_calls()[..],
+            [RuntimeCall::Aura(
+                pallet::Call::offchain_worker_report_check { .. }
+            )]
+        ));
+
+        // The reset is honoured once: a new failure backs off again.
+        assert!(!Aura::submit_with_backoff("test", || Err(())));
+        System::set_block_number(12);
+        Aura::offchain_worker(12);
+        assert_eq!(
+            StorageValueRef::persistent(b"licensed_aura::submit_backoff_until").get::<u64>(),
+            Ok(Some(16))
+        );
+    });
+}
+
+#[test]
+fn request_halt_halts_on_behalf_of_runtime_code() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(3);
+        assert_ok!(Aura::request_halt(
+            b"emergency brake".to_vec(),
+            HaltSource::Emergency
+        ));
+
+        assert!(Aura::is_halted());
+        assert_eq!(
+            Aura::halt_reason_string(),
+            Some("emergency brake".to_string())
+        );
+        assert_eq!(pallet::HaltedAtBlock::<Test>::get(), Some(3));
+        assert!(aura_events().contains(&pallet::Event::ProductionHalted));
+        assert!(aura_events().contains(&pallet::Event::HaltRequested {
+            source: HaltSource::Emergency
+        }));
+
+        // The halt is an ordinary one: the call filter applies and sudo can lift it.
+        assert!(!HaltFilter::contains(&RuntimeCall::Aura(
+            pallet::Call::sudo_force_fresh_check {}
+        )));
+        assert_ok!(Aura::sudo_resume_production(RuntimeOrigin::root()));
+        assert!(!Aura::is_halted());
+    });
+}
+
+#[test]
+fn request_halt_with_an_oversized_reason_changes_nothing() {
+    build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+        System::set_block_number(3);
+        assert_noop!(
+            Aura::request_halt(vec![b'x'; 257], HaltSource::Pallet(9)),
+            pallet::Error::<Test>::ReasonTooLong
+        );
+        assert!(!Aura::is_halted());
+        assert_eq!(pallet::HaltedAtBlock::<Test>::get(), None);
+    });
+}
@@ -38,7 +38,7 @@ pub use frame_support::{
 };
 use frame_support::{
     genesis_builder_helper::{build_state, get_preset},
-    traits::VariantCountOf,
+    traits::{PalletInfoAccess, VariantCountOf},
 };
 pub use frame_system::Call as SystemCall;
 pub use pallet_balances::Call as BalancesCall;
@@ -46,7 +46,7 @@ pub use pallet_timestamp::Call as TimestampCall;
 use pallet_transaction_payment::{ConstFeeMultiplier, FungibleAdapter, Multiplier};
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
-pub use sp_runtime::{Perbill, Permill};
+pub use sp_runtime::{Perbill, Percent, Permill};
 
 /// Import the licensed aura pallet.
 pub use pallet_licensed_aura;
@@ -106,10 +106,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     //   `spec_version`, and `authoring_version` are the same between Wasm and native.
     // This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
     //   the compatible custom types.
-    spec_version: 100,
+    spec_version: 101,
     impl_version: 1,
     apis: RUNTIME_API_VERSIONS,
-    transaction_version: 1,
+    transaction_version: 2,
     state_version: 1,
 };
 
@@ -157,7 +157,8 @@ parameter_types! {
 
 // Import the filter from the licensed aura pallet
 use pallet_licensed_aura::filter::{
-    AuraHaltFilter, IsDefaultInherentExstrinsicCall, IsLicensedAuraCall, IsSudoCall,
+    call_indices, AuraHaltFilter, HaltWhitelistCallIndices, IsDefaultInherentExstrinsicCall,
+    IsLicensedAuraCall, IsSudoCall,
 };
 
 // Implement the traits required by the AuraHaltFilter for our RuntimeCall
@@ -170,7 +171,13 @@ impl IsLicensedAuraCall for RuntimeCall {
         )
     }
 
-    // You can add more calls to the licensed aura pallet here if needed.
+    fn is_sudo_force_resume(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_licensed_aura::Call::sudo_force_resume { .. })
+        )
+    }
+
     fn is_offchain_worker_halt(&self) -> bool {
         matches!(
             self,
@@ -178,13 +185,77 @@ impl IsLicensedAuraCall for RuntimeCall {
         )
     }
 
-    // You can add more calls to the licensed aura pallet here if needed.
     fn is_offchain_worker_resume(&self) -> bool {
         matches!(
             self,
             RuntimeCall::Aura(pallet_licensed_aura::Call::offchain_worker_resume_production { .. })
         )
     }
+
+    fn is_offchain_worker_report(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_licensed_aura::Call::offchain_worker_report_check { .. })
+        )
+    }
+
+    fn is_offchain_worker_authority_report(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(
+                pallet_licensed_aura::Call::offchain_worker_report_authority_check { .. }
+            )
+        )
+    }
+
+    fn is_offchain_worker_unusable_key_report(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(
+                pallet_licensed_aura::Call::offchain_worker_report_unusable_key { .. }
+            )
+        )
+    }
+
+    fn is_queue_call_for_resume(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_licensed_aura::Call::sudo_queue_call_for_resume { .. })
+        )
+    }
+
+    fn is_set_halt_reason(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_licensed_aura::Call::set_halt_reason { .. })
+        )
+    }
+
+    fn is_set_auto_recovery_paused(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_licensed_aura::Call::sudo_set_auto_recovery_paused { .. })
+        )
+    }
+
+    fn is_install_perpetual_license(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_licensed_aura::Call::install_perpetual_license { .. })
+        )
+    }
+
+    fn pallet_index(&self) -> u8 {
+        let index = match self {
+            RuntimeCall::System(_) => System::index(),
+            RuntimeCall::Timestamp(_) => Timestamp::index(),
+            RuntimeCall::Aura(_) => Aura::index(),
+            RuntimeCall::Grandpa(_) => Grandpa::index(),
+            RuntimeCall::Balances(_) => Balances::index(),
+            RuntimeCall::Sudo(_) => Sudo::index(),
+        };
+        index as u8
+    }
 }
 
 impl IsDefaultInherentExstrinsicCall for RuntimeCall {
@@ -195,6 +266,13 @@ impl IsDefaultInherentExstrinsicCall for RuntimeCall {
             RuntimeCall::Timestamp(pallet_timestamp::Call::set { .. })
         )
     }
+
+    fn is_license_proof(&self) -> bool {
+        matches!(
+            self,
+            RuntimeCall::Aura(pallet_licensed_aura::Call::note_license_proof { .. })
+        )
+    }
 }
 
 impl IsSudoCall<RuntimeCall> for RuntimeCall {
@@ -204,14 +282,39 @@ impl IsSudoCall<RuntimeCall> for RuntimeCall {
             | RuntimeCall::Sudo(pallet_sudo::Call::sudo_unchecked_weight { call, .. }) => {
                 // Check if the inner call is allowed (resume or halt)
                 call.is_sudo_resume_production()
+                    || call.is_sudo_force_resume()
                     || call.is_offchain_worker_halt()
                     || call.is_offchain_worker_resume()
+                    || call.is_queue_call_for_resume()
+                    || call.is_set_halt_reason()
+                    || call.is_set_auto_recovery_paused()
+                    || call.is_install_perpetual_license()
+            }
+            _ => false,
+        }
+    }
+
+    fn is_sudo_wrapping_resume(&self) -> bool {
+        match self {
+            RuntimeCall::Sudo(pallet_sudo::Call::sudo { call })
+            | RuntimeCall::Sudo(pallet_sudo::Call::sudo_unchecked_weight { call, .. }) => {
+                call.is_sudo_resume_production() || call.is_sudo_force_resume()
             }
             _ => false,
         }
     }
 }
 
+impl HaltWhitelistCallIndices for RuntimeCall {
+    fn inherent_call_indices() -> Vec<(u8, u8)> {
+        call_indices::<Timestamp, pallet_timestamp::Call<Runtime>>(&["set"])
+    }
+
+    fn sudo_call_indices() -> Vec<(u8, u8)> {
+        call_indices::<Sudo, pallet_sudo::Call<Runtime>>(&["sudo", "sudo_unchecked_weight"])
+    }
+}
+
 /// The default types are being injected by [`derive_impl`](`frame_support::derive_impl`) from
 /// [`SoloChainDefaultConfig`](`struct@frame_system::config_preludes::SolochainDefaultConfig`),
 /// but overridden as needed.
@@ -243,13 +346,102 @@ impl frame_system::Config for Runtime {
     type BaseCallFilter = AuraHaltFilter<RuntimeCall, Runtime>;
 }
 
+parameter_types! {
+    pub const OversizedAuthoritySetPolicy: pallet_licensed_aura::OversizedAuthoritySetPolicy =
+        pallet_licensed_aura::OversizedAuthoritySetPolicy::Truncate;
+    pub const DuplicateAuthorityPolicy: pallet_licensed_aura::DuplicateAuthorityPolicy =
+        pallet_licensed_aura::DuplicateAuthorityPolicy::Dedup;
+    pub const AuthorityUpdatePolicy: pallet_licensed_aura::AuthorityUpdatePolicy =
+        pallet_licensed_aura::AuthorityUpdatePolicy::Replace;
+    pub LicenseUsageThresholds: Vec<u8> = vec![50, 80];
+    pub HealthScoreThresholds: Vec<u8> = vec![25, 50, 75];
+    pub const LicenseQuorum: Percent = Percent::from_percent(51);
+    pub const LicenseEndpointSelection: pallet_licensed_aura::EndpointSelection =
+        pallet_licensed_aura::EndpointSelection::Failover;
+    pub const LicenseCheckEverySlots: Option<u32> = None;
+    pub const LicenseRenewalUrl: Option<&'static str> = None;
+    pub HaltAllowedPallets: Vec<u8> = vec![];
+    pub const MissingValidField: pallet_licensed_aura::MissingValidFieldPolicy =
+        pallet_licensed_aura::MissingValidFieldPolicy::Invalid;
+    pub const AlternativeValidField: Option<&'static str> = None;
+    pub const ValidityFieldPath: &'static [&'static str] = &[];
+    pub const LicensePublicKey: Option<[u8; 32]> = None;
+    pub const OfflineKeyHashes: &'static [[u8; 32]] = &[];
+}
+
 impl pallet_licensed_aura::Config for Runtime {
     type RuntimeEvent = RuntimeEvent;
     type AuthorityId = AuraId;
     type DisabledValidators = ();
+    type DisabledAuthorGrace = ConstU32<3>;
     type MaxAuthorities = ConstU32<32>;
+    type MinAuthorities = ConstU32<1>;
+    type OversizedAuthoritySetPolicy = OversizedAuthoritySetPolicy;
+    type DuplicateAuthorityPolicy = DuplicateAuthorityPolicy;
+    type AuthorityUpdatePolicy = AuthorityUpdatePolicy;
     type AllowMultipleBlocksPerSlot = ConstBool<false>;
+    type SameSlotAuthor = ();
     type SlotDuration = pallet_licensed_aura::MinimumPeriodTimesTwo<Runtime>;
+    type LicenseUsageThresholds = LicenseUsageThresholds;
+    type LicenseCacheTtl = ConstU64<{ 5 * 60 * 1000 }>;
+    type SharedCheckResults = ConstBool<true>;
+    type LicenseCheckInterval = ConstU64<30_000>;
+    type CheckEverySlots = LicenseCheckEverySlots;
+    type CheckOncePerSession = ConstBool<false>;
+    type LicenseRequestAttempts = ConstU32<3>;
+    type LicenseRetryBackoff = ConstU64<500>;
+    type OffchainTimeBudget = ConstU64<20_000>;
+    type HealthStalenessBlocks = ConstU32<{ 10 * MINUTES }>;
+    type HealthScoreThresholds = HealthScoreThresholds;
+    type FailClosed = ConstBool<false>;
+    type EnforceLicenseInherent = ConstBool<false>;
+    type LicenseProofMaxAge = ConstU64<{ 10 * 60 * 1000 }>;
+    type SubmissionRetryBackoff = ConstU32<10>;
+    type LicenseQuorum = LicenseQuorum;
+    type MaxLicensedFeatures = ConstU32<32>;
+    type FeatureChangeConfirmations = ConstU32<3>;
+    type MaxClockDrift = ConstU64<{ 5 * 60 * 1000 }>;
+    type HaltOrigin = frame_system::EnsureRoot<AccountId>;
+    type OffchainWarmupChecks = ConstU32<3>;
+    type EndpointSelection = LicenseEndpointSelection;
+    type LicenseUrlBuilder = ();
+    type RequestUrlTransformer = ();
+    type RenewalUrl = LicenseRenewalUrl;
+    type HaltAllowedPallets = HaltAllowedPallets;
+    type LicensePublicKey = LicensePublicKey;
+    type RequireSignedCertFingerprint = ConstBool<true>;
+    type OfflineKeyHashes = OfflineKeyHashes;
+    type MissingValidField = MissingValidField;
+    type AlternativeValidField = AlternativeValidField;
+    type ValidityFieldPath = ValidityFieldPath;
+    type EmptyBodyMeansValid = ConstBool<false>;
+    type AutoRecoveryDelay = ConstU32<0>;
+    type MaxResponseBytes = ConstU32<{ 64 * 1024 }>;
+    type EventThrottleBlocks = ConstU32<{ 10 * MINUTES }>;
+    type HaltEventDebounce = ConstU32<{ 10 * MINUTES }>;
+    type ReportNodeVersion = ConstBool<true>;
+    type ReportBlockNumber = ConstBool<true>;
+    type RuntimeCall = RuntimeCall;
+    #[cfg(feature = "runtime-benchmarks")]
+    type BenchmarkHelper = LicensedAuraBenchmarkHelper;
+    type MaxQueuedCalls = ConstU32<8>;
+    type MaxAuthTokenLen = ConstU32<256>;
+    type HaltIfOcwAbsent = ConstBool<false>;
+    type OcwAbsenceThreshold = ConstU32<{ HOURS }>;
+    type HeartbeatInterval = ConstU32<{ HOURS }>;
+}
+
+/// Wraps benchmarked calls in `pallet_sudo`, as operators send them.
+#[cfg(feature = "runtime-benchmarks")]
+pub struct LicensedAuraBenchmarkHelper;
+
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet_licensed_aura::BenchmarkHelper<RuntimeCall> for LicensedAuraBenchmarkHelper {
+    fn sudo(call: RuntimeCall) -> RuntimeCall {
+        RuntimeCall::Sudo(pallet_sudo::Call::sudo {
+            call: alloc::boxed::Box::new(call),
+        })
+    }
 }
 
 impl pallet_grandpa::Config for Runtime {
@@ -367,6 +559,7 @@ pub type SignedExtra = (
     frame_system::CheckNonce<Runtime>,
     frame_system::CheckWeight<Runtime>,
     pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+    pallet_licensed_aura::filter::CheckNotHalted<Runtime>,
 );
 
 impl frame_system::offchain::SigningTypes for Runtime {
@@ -417,6 +610,7 @@ where
             frame_system::CheckNonce::<Runtime>::from(nonce),
             frame_system::CheckWeight::<Runtime>::new(),
             pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(tip),
+            pallet_licensed_aura::filter::CheckNotHalted::<Runtime>::new(),
         );
         let raw_payload = SignedPayload::new(call, extra).ok()?;
         let signature = raw_payload.using_encoded(|payload| AppC::sign(payload, public))?;
@@ -462,6 +656,7 @@ mod benches {
         [pallet_balances, Balances]
         [pallet_timestamp, Timestamp]
         [pallet_sudo, Sudo]
+        [pallet_licensed_aura, Aura]
     );
 }
 
@@ -541,6 +736,84 @@ impl_runtime_apis! {
         }
     }
 
+    impl pallet_licensed_aura::runtime_api::LicensedAuraApi<Block, BlockNumber> for Runtime {
+        fn enforcement_health() -> u8 {
+            Aura::enforcement_health()
+        }
+
+        fn debug_dump() -> pallet_licensed_aura::LicensedAuraDebug<BlockNumber> {
+            Aura::debug_dump()
+        }
+
+        fn effective_check_interval() -> u64 {
+            Aura::effective_check_interval()
+        }
+
+        fn licensed_streaks() -> (BlockNumber, BlockNumber) {
+            Aura::licensed_streaks()
+        }
+
+        fn halt_whitelist() -> Vec<(u8, u8)> {
+            AuraHaltFilter::<RuntimeCall, Runtime>::halt_whitelist()
+        }
+
+        fn halt_filter_config() -> pallet_licensed_aura::filter::HaltFilterConfig {
+            AuraHaltFilter::<RuntimeCall, Runtime>::config()
+        }
+
+        fn auto_recovery_block() -> Option<BlockNumber> {
+            Aura::auto_recovery_block()
+        }
+
+        fn auto_recovery_eta(current_block: BlockNumber) -> Option<BlockNumber> {
+            Aura::auto_recovery_eta(current_block)
+        }
+
+        fn auto_recovery_status() -> pallet_licensed_aura::AutoRecoveryStatus<BlockNumber> {
+            Aura::auto_recovery_status()
+        }
+
+        fn simulate_on_initialize_decision() -> pallet_licensed_aura::EnforcementDecision {
+            Aura::simulate_on_initialize_decision()
+        }
+
+        fn consecutive_successes_while_halted() -> u32 {
+            Aura::consecutive_successes_while_halted()
+        }
+
+        fn current_slot_and_author() -> (sp_consensus_aura::Slot, Option<u32>) {
+            Aura::current_slot_and_author()
+        }
+
+        fn license_endpoint() -> Option<Vec<u8>> {
+            Aura::license_endpoint()
+        }
+
+        fn license_key_fingerprint() -> Option<[u8; 4]> {
+            Aura::license_key_fingerprint()
+        }
+
+        fn license_key_len() -> Option<u32> {
+            Aura::license_key_len()
+        }
+
+        fn enforcement_config() -> pallet_licensed_aura::EnforcementConfig<BlockNumber> {
+            Aura::enforcement_config()
+        }
+
+        fn halt_reason_counts() -> Vec<(pallet_licensed_aura::HaltReasonKind, u32)> {
+            Aura::halt_reason_counts()
+        }
+
+        fn author_distribution(window: u32) -> Vec<(u32, u32)> {
+            Aura::author_distribution(window)
+        }
+
+        fn slot_history(window: u32) -> Vec<(BlockNumber, sp_consensus_aura::Slot)> {
+            Aura::slot_history(window)
+        }
+    }
+
     impl sp_session::SessionKeys<Block> for Runtime {
         fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
             opaque::SessionKeys::generate(seed)